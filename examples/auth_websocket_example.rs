@@ -9,8 +9,8 @@ use tradier::wssession::{MarketSession, MarketSessionFilter, MarketSessionPayloa
 ///
 /// - Sets up the logger using `setup_logger` for structured logging output.
 /// - Loads API configuration from `Config`.
-/// - Creates a `SessionManager` to manage singleton WebSocket sessions, enforcing that only one session
-///   exists at a time.
+/// - Relies on the default `SessionManager`, which allows one market and one account session at a
+///   time and frees each slot when its session is dropped.
 ///
 /// The program enters an infinite loop to continuously create and manage WebSocket streaming sessions.
 /// For each loop iteration:
@@ -1,9 +1,8 @@
 use crate::accounts::types::{
-    AccountNumber, EventType, GainLossSortBy, GetAccountBalancesResponse,
+    AccountGainLossRequest, AccountHistoryRequest, AccountNumber, GetAccountBalancesResponse,
     GetAccountGainLossResponse, GetAccountHistoryResponse, GetAccountOrdersResponse, IncludeTags,
     Limit, Page,
 };
-use crate::common::SortOrder;
 use crate::types::GetAccountPositionsResponse;
 use crate::{error::Result, utils::Sealed};
 
@@ -25,18 +24,13 @@ pub mod non_blocking {
         async fn get_account_history(
             &self,
             account_number: &AccountNumber,
-            page: Option<Page>,
-            limit: Option<Limit>,
-            event_type: Option<EventType>,
+            request: &AccountHistoryRequest,
         ) -> Result<GetAccountHistoryResponse>;
 
         async fn get_account_gain_loss(
             &self,
             account_number: &AccountNumber,
-            page: Option<Page>,
-            limit: Option<Limit>,
-            sort_by: Option<GainLossSortBy>,
-            sort_order: Option<SortOrder>,
+            request: &AccountGainLossRequest,
         ) -> Result<GetAccountGainLossResponse>;
 
         async fn get_account_orders(
@@ -48,6 +42,7 @@ pub mod non_blocking {
         ) -> Result<GetAccountOrdersResponse>;
     }
 }
+#[cfg(feature = "blocking")]
 pub mod blocking {
     use super::*;
 
@@ -65,18 +60,13 @@ pub mod blocking {
         fn get_account_history(
             &self,
             account_number: &AccountNumber,
-            page: Option<Page>,
-            limit: Option<Limit>,
-            event_type: Option<EventType>,
+            request: &AccountHistoryRequest,
         ) -> Result<GetAccountHistoryResponse>;
 
         fn get_account_gain_loss(
             &self,
             account_number: &AccountNumber,
-            page: Option<Page>,
-            limit: Option<Limit>,
-            sort_by: Option<GainLossSortBy>,
-            sort_order: Option<SortOrder>,
+            request: &AccountGainLossRequest,
         ) -> Result<GetAccountGainLossResponse>;
 
         fn get_account_orders(
@@ -1,9 +1,9 @@
 use std::str::FromStr;
 
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, NaiveDate, Utc};
 use serde::Deserialize;
 
-use crate::common::AccountType;
+use crate::common::{AccountType, SortOrder, Symbol};
 use crate::utils::OneOrMany;
 
 #[derive(Debug)]
@@ -23,6 +23,21 @@ impl FromStr for AccountNumber {
     }
 }
 
+impl AccountNumber {
+    /// Returns the account number with all but the last four characters
+    /// masked, suitable for logs and tracing fields. Numbers of four
+    /// characters or fewer are masked entirely.
+    #[must_use]
+    pub fn redacted(&self) -> String {
+        let len = self.0.len();
+        if len <= 4 {
+            return "*".repeat(len);
+        }
+        // `FromStr` only accepts printable ASCII, so byte slicing is safe.
+        format!("{}{}", "*".repeat(len - 4), &self.0[len - 4..])
+    }
+}
+
 impl std::fmt::Display for AccountNumber {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.write_str(&self.0)
@@ -140,7 +155,7 @@ impl std::fmt::Display for EventType {
     }
 }
 #[non_exhaustive]
-#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Eq, Hash)]
 #[serde(rename_all = "snake_case")]
 pub enum OrderType {
     Market,
@@ -152,8 +167,23 @@ pub enum OrderType {
     Even,
 }
 
+impl std::fmt::Display for OrderType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let value = match self {
+            OrderType::Market => "market",
+            OrderType::Limit => "limit",
+            OrderType::Stop => "stop",
+            OrderType::StopLimit => "stop_limit",
+            OrderType::Debit => "debit",
+            OrderType::Credit => "credit",
+            OrderType::Even => "even",
+        };
+        f.write_str(value)
+    }
+}
+
 #[non_exhaustive]
-#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Eq, Hash)]
 #[serde(rename_all = "snake_case")]
 pub enum OrderSide {
     Buy,
@@ -166,8 +196,24 @@ pub enum OrderSide {
     SellToClose,
 }
 
+impl std::fmt::Display for OrderSide {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let value = match self {
+            OrderSide::Buy => "buy",
+            OrderSide::BuyToCover => "buy_to_cover",
+            OrderSide::Sell => "sell",
+            OrderSide::SellShort => "sell_short",
+            OrderSide::BuyToOpen => "buy_to_open",
+            OrderSide::BuyToClose => "buy_to_close",
+            OrderSide::SellToOpen => "sell_to_open",
+            OrderSide::SellToClose => "sell_to_close",
+        };
+        f.write_str(value)
+    }
+}
+
 #[non_exhaustive]
-#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Eq, Hash)]
 #[serde(rename_all = "snake_case")]
 pub enum OrderStatus {
     Pending,
@@ -180,8 +226,42 @@ pub enum OrderStatus {
     PendingCancel,
 }
 
+impl OrderStatus {
+    /// Returns `true` for statuses an order never leaves: filled,
+    /// expired, canceled or rejected.
+    #[must_use]
+    pub fn is_terminal(self) -> bool {
+        matches!(
+            self,
+            OrderStatus::Filled
+                | OrderStatus::Expired
+                | OrderStatus::Canceled
+                | OrderStatus::Rejected
+        )
+    }
+}
+
+impl FromStr for OrderStatus {
+    type Err = crate::Error;
+
+    /// Parses the snake_case status Tradier sends, e.g. `partially_filled`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "pending" => OrderStatus::Pending,
+            "open" => OrderStatus::Open,
+            "partially_filled" => OrderStatus::PartiallyFilled,
+            "filled" => OrderStatus::Filled,
+            "expired" => OrderStatus::Expired,
+            "canceled" => OrderStatus::Canceled,
+            "rejected" => OrderStatus::Rejected,
+            "pending_cancel" => OrderStatus::PendingCancel,
+            other => return Err(crate::Error::UnsupportedOrderStatus(other.to_owned())),
+        })
+    }
+}
+
 #[non_exhaustive]
-#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Eq, Hash)]
 #[serde(rename_all = "lowercase")]
 pub enum OrderDuration {
     Day,
@@ -190,6 +270,18 @@ pub enum OrderDuration {
     Post,
 }
 
+impl std::fmt::Display for OrderDuration {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let value = match self {
+            OrderDuration::Day => "day",
+            OrderDuration::Gtc => "gtc",
+            OrderDuration::Pre => "pre",
+            OrderDuration::Post => "post",
+        };
+        f.write_str(value)
+    }
+}
+
 #[non_exhaustive]
 #[derive(Clone, Debug, Deserialize, PartialEq)]
 #[serde(rename_all = "lowercase")]
@@ -257,6 +349,78 @@ pub struct GetAccountOrdersResponse {
     orders: AccountOrders,
 }
 
+impl GetAccountOrdersResponse {
+    /// Returns the orders page contained in the response.
+    #[must_use]
+    pub fn orders(&self) -> &AccountOrders {
+        &self.orders
+    }
+}
+
+impl AccountOrders {
+    /// Returns the orders on this page.
+    #[must_use]
+    pub fn orders(&self) -> &[Order] {
+        self.order.as_slice()
+    }
+
+    /// Returns the 1-based number of this page.
+    #[must_use]
+    pub fn page(&self) -> u32 {
+        self.page
+    }
+
+    /// Returns how many pages of orders there are.
+    #[must_use]
+    pub fn total_pages(&self) -> u32 {
+        self.total_pages
+    }
+}
+
+impl Order {
+    /// Returns the Tradier order id.
+    #[must_use]
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+
+    /// Returns the symbol the order is against.
+    #[must_use]
+    pub fn symbol(&self) -> &str {
+        &self.symbol
+    }
+
+    /// Returns the order status.
+    #[must_use]
+    pub fn status(&self) -> OrderStatus {
+        self.status
+    }
+
+    /// Returns the requested quantity.
+    #[must_use]
+    pub fn quantity(&self) -> f64 {
+        self.quantity
+    }
+
+    /// Returns the quantity filled so far.
+    #[must_use]
+    pub fn exec_quantity(&self) -> f64 {
+        self.exec_quantity
+    }
+
+    /// Returns the average fill price across all fills.
+    #[must_use]
+    pub fn avg_fill_price(&self) -> f64 {
+        self.avg_fill_price
+    }
+
+    /// Returns the unfilled quantity, if Tradier reported it.
+    #[must_use]
+    pub fn remaining_quantity(&self) -> Option<f64> {
+        self.remaining_quantity
+    }
+}
+
 /// Field to sort by when querying account gain/loss.
 ///
 /// Currently specific to `get_account_gain_loss`. May be moved to `crate::common`
@@ -280,6 +444,49 @@ impl std::fmt::Display for GainLossSortBy {
     }
 }
 
+/// Query filters for `GET /v1/accounts/{account_id}/gainloss`.
+///
+/// Every filter is optional; [`AccountGainLossRequest::default`] sends no
+/// query parameters and lets Tradier apply its own defaults.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct AccountGainLossRequest {
+    pub page: Option<Page>,
+    pub limit: Option<Limit>,
+    pub sort_by: Option<GainLossSortBy>,
+    pub sort_order: Option<SortOrder>,
+    /// Only include positions closed on or after this date.
+    pub start: Option<NaiveDate>,
+    /// Only include positions closed on or before this date.
+    pub end: Option<NaiveDate>,
+    /// Only include positions for this symbol.
+    pub symbol: Option<Symbol>,
+}
+
+#[bon::bon]
+impl AccountGainLossRequest {
+    /// Constructs a new `AccountGainLossRequest` from the supplied filters.
+    #[builder(builder_type(vis = "pub"))]
+    fn new(
+        page: Option<Page>,
+        limit: Option<Limit>,
+        sort_by: Option<GainLossSortBy>,
+        sort_order: Option<SortOrder>,
+        start: Option<NaiveDate>,
+        end: Option<NaiveDate>,
+        symbol: Option<Symbol>,
+    ) -> Self {
+        AccountGainLossRequest {
+            page,
+            limit,
+            sort_by,
+            sort_order,
+            start,
+            end,
+            symbol,
+        }
+    }
+}
+
 #[derive(Clone, Debug, Deserialize, PartialEq)]
 pub struct GetAccountGainLossResponse {
     gainloss: AccountGainLoss,
@@ -306,6 +513,39 @@ pub struct ClosedPosition {
     term: u32,
 }
 
+impl GetAccountGainLossResponse {
+    /// Returns the gain/loss page contained in the response.
+    #[must_use]
+    pub fn gain_loss(&self) -> &AccountGainLoss {
+        &self.gainloss
+    }
+}
+
+impl AccountGainLoss {
+    /// Returns the closed positions on this page.
+    #[must_use]
+    pub fn closed_positions(&self) -> &[ClosedPosition] {
+        &self.closed_position
+    }
+}
+
+impl ClosedPosition {
+    /// Returns the symbol of the closed position.
+    #[must_use]
+    pub fn symbol(&self) -> &str {
+        &self.symbol
+    }
+}
+
+#[cfg(feature = "decimal")]
+crate::utils::decimal::decimal_accessors!(ClosedPosition {
+    value cost => cost_decimal,
+    value gain_loss => gain_loss_decimal,
+    value gain_loss_percent => gain_loss_percent_decimal,
+    value proceeds => proceeds_decimal,
+    value quantity => quantity_decimal,
+});
+
 #[derive(Clone, Debug, Deserialize, PartialEq)]
 pub struct GetAccountBalancesResponse {
     balances: AccountBalances,
@@ -344,6 +584,51 @@ pub struct Margin {
     sweep: f64,
 }
 
+impl GetAccountBalancesResponse {
+    /// Returns the balances contained in the response.
+    #[must_use]
+    pub fn balances(&self) -> &AccountBalances {
+        &self.balances
+    }
+}
+
+impl AccountBalances {
+    /// Returns the margin section of the balances.
+    #[must_use]
+    pub fn margin(&self) -> &Margin {
+        &self.margin
+    }
+}
+
+#[cfg(feature = "decimal")]
+crate::utils::decimal::decimal_accessors!(AccountBalances {
+    value option_short_value => option_short_value_decimal,
+    value total_equity => total_equity_decimal,
+    value close_pl => close_pl_decimal,
+    value current_requirement => current_requirement_decimal,
+    value equity => equity_decimal,
+    value long_market_value => long_market_value_decimal,
+    value market_value => market_value_decimal,
+    value open_pl => open_pl_decimal,
+    value option_long_value => option_long_value_decimal,
+    value option_requirement => option_requirement_decimal,
+    value short_market_value => short_market_value_decimal,
+    value stock_long_value => stock_long_value_decimal,
+    value total_cash => total_cash_decimal,
+    value uncleared_funds => uncleared_funds_decimal,
+    value pending_cash => pending_cash_decimal,
+});
+
+#[cfg(feature = "decimal")]
+crate::utils::decimal::decimal_accessors!(Margin {
+    value fed_call => fed_call_decimal,
+    value maintenance_call => maintenance_call_decimal,
+    value option_buying_power => option_buying_power_decimal,
+    value stock_buying_power => stock_buying_power_decimal,
+    value stock_short_value => stock_short_value_decimal,
+    value sweep => sweep_decimal,
+});
+
 #[derive(Clone, Debug, Deserialize, PartialEq)]
 pub struct Position {
     cost_basis: f64,
@@ -358,6 +643,51 @@ pub struct GetAccountPositionsResponse {
     positions: Vec<Position>,
 }
 
+/// Query filters for `GET /v1/accounts/{account_id}/history`.
+///
+/// Every filter is optional; [`AccountHistoryRequest::default`] sends no
+/// query parameters and lets Tradier apply its own defaults.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct AccountHistoryRequest {
+    pub page: Option<Page>,
+    pub limit: Option<Limit>,
+    pub event_type: Option<EventType>,
+    /// Only include events on or after this date.
+    pub start: Option<NaiveDate>,
+    /// Only include events on or before this date.
+    pub end: Option<NaiveDate>,
+    /// Only include events for this symbol.
+    pub symbol: Option<Symbol>,
+    /// When `true`, `symbol` must match exactly instead of also matching
+    /// option contracts on the same underlying.
+    pub exact_match: Option<bool>,
+}
+
+#[bon::bon]
+impl AccountHistoryRequest {
+    /// Constructs a new `AccountHistoryRequest` from the supplied filters.
+    #[builder(builder_type(vis = "pub"))]
+    fn new(
+        page: Option<Page>,
+        limit: Option<Limit>,
+        event_type: Option<EventType>,
+        start: Option<NaiveDate>,
+        end: Option<NaiveDate>,
+        symbol: Option<Symbol>,
+        exact_match: Option<bool>,
+    ) -> Self {
+        AccountHistoryRequest {
+            page,
+            limit,
+            event_type,
+            start,
+            end,
+            symbol,
+            exact_match,
+        }
+    }
+}
+
 #[derive(Clone, Debug, Deserialize, PartialEq)]
 pub struct GetAccountHistoryResponse {
     pub history: AccountHistoryEvents,
@@ -482,6 +812,24 @@ mod test {
         }
     }
 
+    #[cfg(feature = "decimal")]
+    #[test]
+    fn test_account_balances_decimal_accessors_match_wire_literals() {
+        let json = r#"{"balances":{"option_short_value":0,"total_equity":17798.36,"account_number":"VA00000000","account_type":"margin","close_pl":-4813.0,"current_requirement":2557.0,"equity":0,"long_market_value":11434.5,"market_value":11434.5,"open_pl":546.02,"option_long_value":5732.0,"option_requirement":0,"pending_orders_count":0,"short_market_value":0,"stock_long_value":5702.5,"total_cash":6363.86,"uncleared_funds":0,"pending_cash":0,"margin":{"fed_call":0,"maintenance_call":0,"option_buying_power":6363.86,"stock_buying_power":12727.72,"stock_short_value":0,"sweep":0}}}"#;
+        let response: GetAccountBalancesResponse = serde_json::from_str(json).expect("parse");
+        let balances = response.balances();
+        let sum = balances.total_cash_decimal().unwrap() + balances.open_pl_decimal().unwrap();
+        assert_eq!(sum.to_string(), "6909.88");
+        assert_eq!(
+            balances
+                .margin()
+                .stock_buying_power_decimal()
+                .unwrap()
+                .to_string(),
+            "12727.72"
+        );
+    }
+
     #[test]
     fn test_include_tags_default_is_false() {
         assert_eq!(IncludeTags::default().to_string(), "false");
@@ -512,6 +860,14 @@ mod test {
         assert!(result.is_ok());
     }
 
+    #[test]
+    fn test_account_number_redacted_keeps_last_four_characters() {
+        let account_number: AccountNumber = "VA12345678".parse().expect("should parse");
+        assert_eq!(account_number.redacted(), "******5678");
+        let short: AccountNumber = "AB1".parse().expect("should parse");
+        assert_eq!(short.redacted(), "***");
+    }
+
     #[test]
     fn test_account_number_display_preserves_input() {
         let input = "Account 123";
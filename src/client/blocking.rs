@@ -19,27 +19,29 @@
 //! # When **not** to use
 //! - Any code already running under Tokio (e.g., `#[tokio::main]`, `#[tokio::test]`).
 //!   In those cases, import and call the async client directly.
+use std::sync::Arc;
+
 use chrono::{DateTime, NaiveDate, Utc};
 use tokio::runtime::{Handle, Runtime};
 
+#[cfg(feature = "fundamentals")]
+use crate::fundamentals::{
+    api::blocking::Fundamentals,
+    api::non_blocking::Fundamentals as NonBlockingFundamentals,
+    types::{
+        CompanyResponse, CorporateActionResponse, CorporateCalendarResponse, DividendResponse,
+        FinancialsResponse, RatiosResponse, StatisticsResponse,
+    },
+};
 use crate::{
     Config, Result,
     accounts::types::{
-        AccountNumber, EventType, GainLossSortBy, GetAccountBalancesResponse,
+        AccountGainLossRequest, AccountHistoryRequest, AccountNumber, GetAccountBalancesResponse,
         GetAccountGainLossResponse, GetAccountHistoryResponse, GetAccountOrdersResponse,
         IncludeTags, Limit, Page,
     },
     accounts::{api::blocking::Accounts, api::non_blocking::Accounts as NonBlockingAccounts},
     client::non_blocking::TradierRestClient as AsyncClient,
-    common::SortOrder,
-    fundamentals::{
-        api::blocking::Fundamentals,
-        api::non_blocking::Fundamentals as NonBlockingFundamentals,
-        types::{
-            CompanyResponse, CorporateActionResponse, CorporateCalendarResponse, DividendResponse,
-            FinancialsResponse, RatiosResponse, StatisticsResponse,
-        },
-    },
     market_data::{
         api::blocking::MarketData,
         api::non_blocking::MarketData as NonBlockingMarketData,
@@ -98,7 +100,7 @@ pub struct BlockingTradierRestClient {
     /// Private single-thread runtime for blocking operations when no external
     /// runtime exists. Never used if a Tokio runtime is currently active,
     /// because construction/usage in that state is rejected.
-    runtime: Runtime,
+    runtime: Arc<Runtime>,
 }
 
 impl BlockingTradierRestClient {
@@ -117,11 +119,26 @@ impl BlockingTradierRestClient {
 
         Ok(Self {
             rest_client: AsyncClient::new(config),
-            runtime: tokio::runtime::Builder::new_current_thread()
-                .enable_all()
-                .build()?,
+            runtime: Arc::new(
+                tokio::runtime::Builder::new_current_thread()
+                    .enable_all()
+                    .build()?,
+            ),
         })
     }
+
+    /// Returns the wrapped async client.
+    #[cfg(feature = "streaming")]
+    pub(crate) fn rest_client(&self) -> &AsyncClient {
+        &self.rest_client
+    }
+
+    /// Returns the private runtime, shared with streams opened through
+    /// this client.
+    #[cfg(feature = "streaming")]
+    pub(crate) fn runtime(&self) -> &Arc<Runtime> {
+        &self.runtime
+    }
 }
 
 impl Sealed for BlockingTradierRestClient {}
@@ -152,34 +169,23 @@ impl Accounts for BlockingTradierRestClient {
     fn get_account_history(
         &self,
         account_number: &AccountNumber,
-        page: Option<Page>,
-        limit: Option<Limit>,
-        event_type: Option<EventType>,
+        request: &AccountHistoryRequest,
     ) -> Result<GetAccountHistoryResponse> {
-        self.runtime.block_on(self.rest_client.get_account_history(
-            account_number,
-            page,
-            limit,
-            event_type,
-        ))
+        self.runtime.block_on(
+            self.rest_client
+                .get_account_history(account_number, request),
+        )
     }
 
     fn get_account_gain_loss(
         &self,
         account_number: &AccountNumber,
-        page: Option<Page>,
-        limit: Option<Limit>,
-        sort_by: Option<GainLossSortBy>,
-        sort_order: Option<SortOrder>,
+        request: &AccountGainLossRequest,
     ) -> Result<GetAccountGainLossResponse> {
-        self.runtime
-            .block_on(self.rest_client.get_account_gain_loss(
-                account_number,
-                page,
-                limit,
-                sort_by,
-                sort_order,
-            ))
+        self.runtime.block_on(
+            self.rest_client
+                .get_account_gain_loss(account_number, request),
+        )
     }
 
     fn get_account_orders(
@@ -320,6 +326,7 @@ impl MarketData for BlockingTradierRestClient {
     }
 }
 
+#[cfg(feature = "fundamentals")]
 impl Fundamentals for BlockingTradierRestClient {
     fn get_company(&self, symbols: &[Symbol]) -> Result<Vec<CompanyResponse>> {
         self.runtime.block_on(self.rest_client.get_company(symbols))
@@ -362,6 +369,7 @@ impl Fundamentals for BlockingTradierRestClient {
 mod test {
     use super::*;
     use std::cell::RefCell;
+    use std::str::FromStr;
 
     use crate::{
         Config,
@@ -370,8 +378,10 @@ mod test {
             GetAccountHistoryResponseWire, GetAccountOrdersResponseWire,
             GetAccountPositionsResponseWire,
         },
-        accounts::types::{EventType, GainLossSortBy, Limit, Page},
-        common::SortOrder,
+        accounts::types::{
+            AccountGainLossRequest, AccountHistoryRequest, EventType, GainLossSortBy, Limit, Page,
+        },
+        common::{SortOrder, Symbol},
         user::test_support::GetUserProfileResponseWire,
         utils::tests::with_env_vars,
     };
@@ -379,13 +389,50 @@ mod test {
     use httpmock::MockServer;
     use proptest::prelude::*;
 
+    fn run_history_proptest(
+        server: &RefCell<MockServer>,
+        expected_query_params: &[(&str, &str)],
+        request: AccountHistoryRequest,
+    ) {
+        proptest!(|(response in any::<GetAccountHistoryResponseWire>(),
+                ascii_string in prop::collection::vec(0x20u8..0x7fu8, 1..256)
+            .prop_flat_map(|vec| {
+                Just(vec.into_iter().map(|c| c as char).collect::<String>())
+            })
+            .prop_filter("Strings must not be empty or blank", |v| !v.trim().is_empty()))| {
+            let server = server.borrow_mut();
+            let mut operation = server.mock(|when, then| {
+                let mut when = when
+                    .path(url::Url::parse(&server.url(format!("/v1/accounts/{ascii_string}/history"))).unwrap().path())
+                    .header("accept", "application/json");
+                for (k, v) in expected_query_params {
+                    when = when.query_param(*k, *v);
+                }
+                then.status(200)
+                    .header("content-type", "application/json")
+                    .body(serde_json::to_vec(&response)
+                        .expect("serialization of wire type for tests to work"));
+            });
+            with_env_vars(vec![("TRADIER_REST_BASE_URL", &server.base_url()),
+            ("TRADIER_ACCESS_TOKEN", "testToken")], || {
+                let config = Config::new();
+                let sut = BlockingTradierRestClient::new(config).expect("client to initialize");
+                let response = sut.get_account_history(
+                    &ascii_string.parse().expect("valid ascii"),
+                    &request,
+                );
+                operation.assert();
+                assert_eq!(operation.calls(), 1);
+                assert!(response.is_ok());
+                operation.delete();
+            });
+        });
+    }
+
     fn run_gain_loss_proptest(
         server: &RefCell<MockServer>,
         expected_query_params: &[(&str, &str)],
-        page: Option<Page>,
-        limit: Option<Limit>,
-        sort_by: Option<GainLossSortBy>,
-        sort_order: Option<SortOrder>,
+        request: AccountGainLossRequest,
     ) {
         proptest!(|(response in any::<GetAccountGainLossResponseWire>(),
                 ascii_string in prop::collection::vec(0x20u8..0x7fu8, 1..256)
@@ -411,10 +458,7 @@ mod test {
                 let sut = BlockingTradierRestClient::new(config).expect("client to initialize");
                 let response = sut.get_account_gain_loss(
                     &ascii_string.parse().expect("valid ascii"),
-                    page.clone(),
-                    limit.clone(),
-                    sort_by.clone(),
-                    sort_order.clone(),
+                    &request,
                 );
                 operation.assert();
                 assert_eq!(operation.calls(), 1);
@@ -516,80 +560,35 @@ mod test {
             });
         });
 
-        // Test GetAccountHistory (no query params)
+        run_history_proptest(&server, &[], AccountHistoryRequest::default());
 
-        proptest!(|(response in any::<GetAccountHistoryResponseWire>(),
-                ascii_string in prop::collection::vec(0x20u8..0x7fu8, 1..256)
-            .prop_flat_map(|vec| {
-                Just(vec.into_iter().map(|c| c as char).collect::<String>())
-            })
-            .prop_filter("Strings must not be empty or blank", |v| !v.trim().is_empty()))| {
-            let server = server.borrow_mut();
-            let mut operation = server.mock(|when, then| {
-                when.path(url::Url::parse(&server.url(format!("/v1/accounts/{ascii_string}/history"))).unwrap().path())
-                    .header("accept", "application/json");
-                then.status(200)
-                    .header("content-type", "application/json")
-                    .body(serde_json::to_vec(&response)
-                        .expect("serialization of wire type for tests to work"));
-            });
-
-            with_env_vars(vec![("TRADIER_REST_BASE_URL", &server.base_url()),
-            ("TRADIER_ACCESS_TOKEN", "testToken")], || {
-                let config = Config::new();
-                let sut = BlockingTradierRestClient::new(config).expect("client to initialize");
-                let response = sut.get_account_history(
-                    &ascii_string.parse().expect("valid ascii"),
-                    None,
-                    None,
-                    None,
-                );
-                operation.assert();
-                assert_eq!(operation.calls(), 1);
-                assert!(response.is_ok());
-                operation.delete();
-            });
-        });
-
-        // Test GetAccountHistory (with query params)
-
-        proptest!(|(response in any::<GetAccountHistoryResponseWire>(),
-                ascii_string in prop::collection::vec(0x20u8..0x7fu8, 1..256)
-            .prop_flat_map(|vec| {
-                Just(vec.into_iter().map(|c| c as char).collect::<String>())
-            })
-            .prop_filter("Strings must not be empty or blank", |v| !v.trim().is_empty()))| {
-            let server = server.borrow_mut();
-            let mut operation = server.mock(|when, then| {
-                when.path(url::Url::parse(&server.url(format!("/v1/accounts/{ascii_string}/history"))).unwrap().path())
-                    .header("accept", "application/json")
-                    .query_param("page", "2")
-                    .query_param("limit", "50")
-                    .query_param("type", "trade");
-                then.status(200)
-                    .header("content-type", "application/json")
-                    .body(serde_json::to_vec(&response)
-                        .expect("serialization of wire type for tests to work"));
-            });
+        run_history_proptest(
+            &server,
+            &[("page", "2"), ("limit", "50"), ("type", "trade")],
+            AccountHistoryRequest::builder()
+                .page(Page::new(2))
+                .limit(Limit::new(50))
+                .event_type(EventType::Trade)
+                .build(),
+        );
 
-            with_env_vars(vec![("TRADIER_REST_BASE_URL", &server.base_url()),
-            ("TRADIER_ACCESS_TOKEN", "testToken")], || {
-                let config = Config::new();
-                let sut = BlockingTradierRestClient::new(config).expect("client to initialize");
-                let response = sut.get_account_history(
-                    &ascii_string.parse().expect("valid ascii"),
-                    Some(Page::new(2)),
-                    Some(Limit::new(50)),
-                    Some(EventType::Trade),
-                );
-                operation.assert();
-                assert_eq!(operation.calls(), 1);
-                assert!(response.is_ok());
-                operation.delete();
-            });
-        });
+        run_history_proptest(
+            &server,
+            &[
+                ("start", "2024-01-01"),
+                ("end", "2024-03-31"),
+                ("symbol", "AAPL"),
+                ("exactMatch", "true"),
+            ],
+            AccountHistoryRequest::builder()
+                .start(NaiveDate::from_ymd_opt(2024, 1, 1).unwrap())
+                .end(NaiveDate::from_ymd_opt(2024, 3, 31).unwrap())
+                .symbol(Symbol::from_str("AAPL").unwrap())
+                .exact_match(true)
+                .build(),
+        );
 
-        run_gain_loss_proptest(&server, &[], None, None, None, None);
+        run_gain_loss_proptest(&server, &[], AccountGainLossRequest::default());
 
         run_gain_loss_proptest(
             &server,
@@ -599,37 +598,51 @@ mod test {
                 ("sortBy", "symbol"),
                 ("sort", "asc"),
             ],
-            Some(Page::new(2)),
-            Some(Limit::new(50)),
-            Some(GainLossSortBy::Symbol),
-            Some(SortOrder::Asc),
+            AccountGainLossRequest::builder()
+                .page(Page::new(2))
+                .limit(Limit::new(50))
+                .sort_by(GainLossSortBy::Symbol)
+                .sort_order(SortOrder::Asc)
+                .build(),
         );
 
         run_gain_loss_proptest(
             &server,
             &[("sortBy", "closedate"), ("sort", "desc")],
-            None,
-            None,
-            Some(GainLossSortBy::CloseDate),
-            Some(SortOrder::Desc),
+            AccountGainLossRequest::builder()
+                .sort_by(GainLossSortBy::CloseDate)
+                .sort_order(SortOrder::Desc)
+                .build(),
         );
 
         run_gain_loss_proptest(
             &server,
             &[("sortBy", "opendate")],
-            None,
-            None,
-            Some(GainLossSortBy::OpenDate),
-            None,
+            AccountGainLossRequest::builder()
+                .sort_by(GainLossSortBy::OpenDate)
+                .build(),
         );
 
         run_gain_loss_proptest(
             &server,
             &[("sortBy", "gainloss")],
-            None,
-            None,
-            Some(GainLossSortBy::GainLoss),
-            None,
+            AccountGainLossRequest::builder()
+                .sort_by(GainLossSortBy::GainLoss)
+                .build(),
+        );
+
+        run_gain_loss_proptest(
+            &server,
+            &[
+                ("start", "2024-01-01"),
+                ("end", "2024-12-31"),
+                ("symbol", "SPY"),
+            ],
+            AccountGainLossRequest::builder()
+                .start(NaiveDate::from_ymd_opt(2024, 1, 1).unwrap())
+                .end(NaiveDate::from_ymd_opt(2024, 12, 31).unwrap())
+                .symbol(Symbol::from_str("SPY").unwrap())
+                .build(),
         );
 
         // Test GetAccountOrders with includeTags=true
@@ -1389,7 +1402,7 @@ mod market_data_tests {
     }
 }
 
-#[cfg(test)]
+#[cfg(all(test, feature = "fundamentals"))]
 mod fundamentals_tests {
     use super::BlockingTradierRestClient;
     use crate::{
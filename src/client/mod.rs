@@ -1,2 +1,3 @@
+#[cfg(feature = "blocking")]
 pub mod blocking;
 pub mod non_blocking;
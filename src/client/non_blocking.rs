@@ -1,24 +1,26 @@
 use chrono::{DateTime, NaiveDate, Utc};
+use tracing::{field::Empty, instrument};
 use url::Url;
 
+#[cfg(feature = "fundamentals")]
+use crate::fundamentals::{
+    api::non_blocking::Fundamentals,
+    types::{
+        CompanyResponse, CorporateActionResponse, CorporateCalendarResponse, DividendResponse,
+        FinancialsResponse, RatiosResponse, StatisticsResponse,
+    },
+};
 use crate::{
     Error, Result,
     accounts::{
         api::non_blocking::Accounts,
         types::{
-            AccountNumber, EventType, GainLossSortBy, GetAccountBalancesResponse,
-            GetAccountGainLossResponse, GetAccountOrdersResponse, IncludeTags, Limit, Page,
+            AccountGainLossRequest, AccountHistoryRequest, AccountNumber,
+            GetAccountBalancesResponse, GetAccountGainLossResponse, GetAccountOrdersResponse,
+            IncludeTags, Limit, Page,
         },
     },
-    common::SortOrder,
     config::Config,
-    fundamentals::{
-        api::non_blocking::Fundamentals,
-        types::{
-            CompanyResponse, CorporateActionResponse, CorporateCalendarResponse, DividendResponse,
-            FinancialsResponse, RatiosResponse, StatisticsResponse,
-        },
-    },
     market_data::{
         api::non_blocking::MarketData,
         types::{
@@ -33,7 +35,7 @@ use crate::{
     },
     types::{GetAccountHistoryResponse, GetAccountPositionsResponse},
     user::{UserProfileResponse, api::non_blocking::User},
-    utils::Sealed,
+    utils::{Sealed, telemetry::RestCall},
 };
 
 #[derive(Debug)]
@@ -68,12 +70,14 @@ impl TradierRestClient {
     /// sibling crates (notably `streaming::http_stream`) can reuse the
     /// same HTTP/TLS pool instead of constructing a new client per
     /// stream.
+    #[cfg(feature = "streaming")]
     #[inline]
     pub(crate) fn http_client(&self) -> &reqwest::Client {
         &self.http_client
     }
 
     /// Returns a reference to the [`Config`] this client was built with.
+    #[cfg(feature = "streaming")]
     #[inline]
     pub(crate) fn http_client_config(&self) -> &Config {
         &self.http_client_config
@@ -84,13 +88,40 @@ impl TradierRestClient {
         url: Url,
         bearer_token: String,
     ) -> Result<reqwest::Response> {
-        self.http_client
+        let call = RestCall::start(&url);
+        let response = self
+            .http_client
             .get(url)
             .bearer_auth(bearer_token)
             .header("accept", "application/json")
             .send()
-            .await
-            .map_err(Error::NetworkError)
+            .await;
+        call.finish(&response);
+        response.map_err(Error::NetworkError)
+    }
+
+    /// Sends `GET /v1/markets/options/chains` and returns the raw response,
+    /// so callers that pace themselves can read its status and rate-limit
+    /// headers before decoding it. Carries the request's `tradier.rest`
+    /// span, so every caller is traced alike.
+    #[instrument(name = "tradier.rest", skip_all, fields(endpoint = "/v1/markets/options/chains", symbols = 1, http.status = Empty, latency_ms = Empty))]
+    pub(crate) async fn option_chains_response(
+        &self,
+        symbol: &Symbol,
+        expiration: &NaiveDate,
+        greeks: Option<Greeks>,
+    ) -> Result<reqwest::Response> {
+        let mut url = self.get_request_url("/v1/markets/options/chains")?;
+        {
+            let mut qp = url.query_pairs_mut();
+            qp.append_pair("symbol", &symbol.to_string());
+            qp.append_pair("expiration", &format_naive_date(expiration));
+            if let Some(g) = greeks {
+                qp.append_pair("greeks", &g.to_string());
+            }
+        }
+        let bearer = self.get_bearer_token()?;
+        self.make_service_call(url, bearer).await
     }
 
     /// POSTs a form body and parses the JSON response into `T`.
@@ -106,13 +137,17 @@ impl TradierRestClient {
             .into_iter()
             .map(|(k, v)| (k.as_ref().to_owned(), v.as_ref().to_owned()))
             .collect();
-        self.http_client
+        let call = RestCall::start(&url);
+        let response = self
+            .http_client
             .post(url)
             .bearer_auth(bearer)
             .header("accept", "application/json")
             .form(&pairs)
             .send()
-            .await
+            .await;
+        call.finish(&response);
+        response
             .map_err(Error::NetworkError)?
             .json::<T>()
             .await
@@ -124,6 +159,7 @@ impl Sealed for TradierRestClient {}
 
 #[async_trait::async_trait]
 impl User for TradierRestClient {
+    #[instrument(name = "tradier.rest", skip_all, fields(endpoint = "/v1/user/profile", http.status = Empty, latency_ms = Empty))]
     async fn get_user_profile(&self) -> Result<UserProfileResponse> {
         let url = self.get_request_url("/v1/user/profile")?;
         let bearer_auth = self.get_bearer_token()?;
@@ -137,6 +173,7 @@ impl User for TradierRestClient {
 
 #[async_trait::async_trait]
 impl Accounts for TradierRestClient {
+    #[instrument(name = "tradier.rest", skip_all, fields(endpoint = "/v1/accounts/{account_id}/balances", account = %account_id.redacted(), http.status = Empty, latency_ms = Empty))]
     async fn get_account_balances(
         &self,
         account_id: &AccountNumber,
@@ -150,6 +187,7 @@ impl Accounts for TradierRestClient {
             .map_err(Error::NetworkError)
     }
 
+    #[instrument(name = "tradier.rest", skip_all, fields(endpoint = "/v1/accounts/{account_id}/positions", account = %account_id.redacted(), http.status = Empty, latency_ms = Empty))]
     async fn get_account_positions(
         &self,
         account_id: &AccountNumber,
@@ -163,25 +201,36 @@ impl Accounts for TradierRestClient {
             .map_err(Error::NetworkError)
     }
 
+    #[instrument(name = "tradier.rest", skip_all, fields(endpoint = "/v1/accounts/{account_id}/history", account = %account_id.redacted(), http.status = Empty, latency_ms = Empty))]
     async fn get_account_history(
         &self,
         account_id: &AccountNumber,
-        page: Option<Page>,
-        limit: Option<Limit>,
-        event_type: Option<EventType>,
+        request: &AccountHistoryRequest,
     ) -> Result<GetAccountHistoryResponse> {
         let mut url = self.get_request_url(&format!("/v1/accounts/{account_id}/history"))?;
         {
             let mut query_pairs = url.query_pairs_mut();
-            if let Some(page) = page {
+            if let Some(page) = &request.page {
                 query_pairs.append_pair("page", &page.to_string());
             }
-            if let Some(limit) = limit {
+            if let Some(limit) = &request.limit {
                 query_pairs.append_pair("limit", &limit.to_string());
             }
-            if let Some(event_type) = event_type {
+            if let Some(event_type) = &request.event_type {
                 query_pairs.append_pair("type", &event_type.to_string());
             }
+            if let Some(start) = &request.start {
+                query_pairs.append_pair("start", &format_naive_date(start));
+            }
+            if let Some(end) = &request.end {
+                query_pairs.append_pair("end", &format_naive_date(end));
+            }
+            if let Some(symbol) = &request.symbol {
+                query_pairs.append_pair("symbol", symbol.as_str());
+            }
+            if let Some(exact_match) = request.exact_match {
+                query_pairs.append_pair("exactMatch", &exact_match.to_string());
+            }
         }
         let bearer_auth = self.get_bearer_token()?;
         let raw_response = self.make_service_call(url, bearer_auth).await?;
@@ -191,29 +240,36 @@ impl Accounts for TradierRestClient {
             .map_err(Error::NetworkError)
     }
 
+    #[instrument(name = "tradier.rest", skip_all, fields(endpoint = "/v1/accounts/{account_id}/gainloss", account = %account_number.redacted(), http.status = Empty, latency_ms = Empty))]
     async fn get_account_gain_loss(
         &self,
         account_number: &AccountNumber,
-        page: Option<Page>,
-        limit: Option<Limit>,
-        sort_by: Option<GainLossSortBy>,
-        sort_order: Option<SortOrder>,
+        request: &AccountGainLossRequest,
     ) -> Result<GetAccountGainLossResponse> {
         let mut url = self.get_request_url(&format!("/v1/accounts/{account_number}/gainloss"))?;
         {
             let mut query_pairs = url.query_pairs_mut();
-            if let Some(page) = page {
+            if let Some(page) = &request.page {
                 query_pairs.append_pair("page", &page.to_string());
             }
-            if let Some(limit) = limit {
+            if let Some(limit) = &request.limit {
                 query_pairs.append_pair("limit", &limit.to_string());
             }
-            if let Some(sort_by) = sort_by {
+            if let Some(sort_by) = &request.sort_by {
                 query_pairs.append_pair("sortBy", &sort_by.to_string());
             }
-            if let Some(sort_order) = sort_order {
+            if let Some(sort_order) = &request.sort_order {
                 query_pairs.append_pair("sort", &sort_order.to_string());
             }
+            if let Some(start) = &request.start {
+                query_pairs.append_pair("start", &format_naive_date(start));
+            }
+            if let Some(end) = &request.end {
+                query_pairs.append_pair("end", &format_naive_date(end));
+            }
+            if let Some(symbol) = &request.symbol {
+                query_pairs.append_pair("symbol", symbol.as_str());
+            }
         }
         let bearer_auth = self.get_bearer_token()?;
         let raw_response = self.make_service_call(url, bearer_auth).await?;
@@ -223,6 +279,7 @@ impl Accounts for TradierRestClient {
             .map_err(Error::NetworkError)
     }
 
+    #[instrument(name = "tradier.rest", skip_all, fields(endpoint = "/v1/accounts/{account_id}/orders", account = %account_id.redacted(), http.status = Empty, latency_ms = Empty))]
     async fn get_account_orders(
         &self,
         account_id: &AccountNumber,
@@ -246,6 +303,7 @@ impl Accounts for TradierRestClient {
 
 #[async_trait::async_trait]
 impl MarketData for TradierRestClient {
+    #[instrument(name = "tradier.rest", skip_all, fields(endpoint = "/v1/markets/quotes", symbols = symbols.len(), http.status = Empty, latency_ms = Empty))]
     async fn get_quotes(
         &self,
         symbols: &Symbols,
@@ -267,6 +325,7 @@ impl MarketData for TradierRestClient {
             .map_err(Error::NetworkError)
     }
 
+    #[instrument(name = "tradier.rest", skip_all, fields(endpoint = "/v1/markets/quotes", symbols = symbols.len(), http.status = Empty, latency_ms = Empty))]
     async fn post_quotes(
         &self,
         symbols: &Symbols,
@@ -288,23 +347,14 @@ impl MarketData for TradierRestClient {
         expiration: &NaiveDate,
         greeks: Option<Greeks>,
     ) -> Result<GetOptionChainsResponse> {
-        let mut url = self.get_request_url("/v1/markets/options/chains")?;
-        {
-            let mut qp = url.query_pairs_mut();
-            qp.append_pair("symbol", &symbol.to_string());
-            qp.append_pair("expiration", &format_naive_date(expiration));
-            if let Some(g) = greeks {
-                qp.append_pair("greeks", &g.to_string());
-            }
-        }
-        let bearer = self.get_bearer_token()?;
-        self.make_service_call(url, bearer)
+        self.option_chains_response(symbol, expiration, greeks)
             .await?
             .json::<GetOptionChainsResponse>()
             .await
             .map_err(Error::NetworkError)
     }
 
+    #[instrument(name = "tradier.rest", skip_all, fields(endpoint = "/v1/markets/options/strikes", symbols = 1, http.status = Empty, latency_ms = Empty))]
     async fn get_option_strikes(
         &self,
         symbol: &Symbol,
@@ -322,6 +372,7 @@ impl MarketData for TradierRestClient {
             .map_err(Error::NetworkError)
     }
 
+    #[instrument(name = "tradier.rest", skip_all, fields(endpoint = "/v1/markets/options/expirations", symbols = 1, http.status = Empty, latency_ms = Empty))]
     async fn get_option_expirations(
         &self,
         symbol: &Symbol,
@@ -347,6 +398,7 @@ impl MarketData for TradierRestClient {
             .map_err(Error::NetworkError)
     }
 
+    #[instrument(name = "tradier.rest", skip_all, fields(endpoint = "/v1/markets/options/lookup", symbols = 1, http.status = Empty, latency_ms = Empty))]
     async fn lookup_option_symbols(
         &self,
         underlying: &Symbol,
@@ -362,6 +414,7 @@ impl MarketData for TradierRestClient {
             .map_err(Error::NetworkError)
     }
 
+    #[instrument(name = "tradier.rest", skip_all, fields(endpoint = "/v1/markets/history", symbols = 1, http.status = Empty, latency_ms = Empty))]
     async fn get_historical_quotes(
         &self,
         symbol: &Symbol,
@@ -395,6 +448,7 @@ impl MarketData for TradierRestClient {
             .map_err(Error::NetworkError)
     }
 
+    #[instrument(name = "tradier.rest", skip_all, fields(endpoint = "/v1/markets/timesales", symbols = 1, http.status = Empty, latency_ms = Empty))]
     async fn get_time_and_sales(
         &self,
         symbol: &Symbol,
@@ -428,6 +482,7 @@ impl MarketData for TradierRestClient {
             .map_err(Error::NetworkError)
     }
 
+    #[instrument(name = "tradier.rest", skip_all, fields(endpoint = "/v1/markets/etb", http.status = Empty, latency_ms = Empty))]
     async fn get_etb_securities(&self) -> Result<GetEtbSecuritiesResponse> {
         let url = self.get_request_url("/v1/markets/etb")?;
         let bearer = self.get_bearer_token()?;
@@ -438,6 +493,7 @@ impl MarketData for TradierRestClient {
             .map_err(Error::NetworkError)
     }
 
+    #[instrument(name = "tradier.rest", skip_all, fields(endpoint = "/v1/markets/clock", http.status = Empty, latency_ms = Empty))]
     async fn get_clock(&self, delayed: Option<DelayedFlag>) -> Result<GetClockResponse> {
         let mut url = self.get_request_url("/v1/markets/clock")?;
         if let Some(d) = delayed {
@@ -451,6 +507,7 @@ impl MarketData for TradierRestClient {
             .map_err(Error::NetworkError)
     }
 
+    #[instrument(name = "tradier.rest", skip_all, fields(endpoint = "/v1/markets/calendar", http.status = Empty, latency_ms = Empty))]
     async fn get_calendar(
         &self,
         month: Option<CalendarMonth>,
@@ -474,6 +531,7 @@ impl MarketData for TradierRestClient {
             .map_err(Error::NetworkError)
     }
 
+    #[instrument(name = "tradier.rest", skip_all, fields(endpoint = "/v1/markets/search", http.status = Empty, latency_ms = Empty))]
     async fn search_companies(
         &self,
         q: &str,
@@ -495,6 +553,7 @@ impl MarketData for TradierRestClient {
             .map_err(Error::NetworkError)
     }
 
+    #[instrument(name = "tradier.rest", skip_all, fields(endpoint = "/v1/markets/lookup", http.status = Empty, latency_ms = Empty))]
     async fn lookup_symbol(
         &self,
         q: &str,
@@ -529,6 +588,7 @@ impl MarketData for TradierRestClient {
 // Fundamentals (beta) impl
 // -----------------------------------------------------------------------------
 
+#[cfg(feature = "fundamentals")]
 impl TradierRestClient {
     /// Shared helper for fundamentals endpoints: build the URL with the CSV
     /// `symbols` query param and GET/parse the JSON array response.
@@ -550,6 +610,7 @@ impl TradierRestClient {
 
 /// Render a slice of [`Symbol`] as a comma-separated CSV, as required by
 /// all fundamentals endpoints.
+#[cfg(feature = "fundamentals")]
 #[inline]
 fn symbols_to_csv(symbols: &[Symbol]) -> String {
     let mut out = String::with_capacity(symbols.len() * 6);
@@ -562,13 +623,16 @@ fn symbols_to_csv(symbols: &[Symbol]) -> String {
     out
 }
 
+#[cfg(feature = "fundamentals")]
 #[async_trait::async_trait]
 impl Fundamentals for TradierRestClient {
+    #[instrument(name = "tradier.rest", skip_all, fields(endpoint = "/beta/markets/fundamentals/company", symbols = symbols.len(), http.status = Empty, latency_ms = Empty))]
     async fn get_company(&self, symbols: &[Symbol]) -> Result<Vec<CompanyResponse>> {
         self.get_fundamentals_array("/beta/markets/fundamentals/company", symbols)
             .await
     }
 
+    #[instrument(name = "tradier.rest", skip_all, fields(endpoint = "/beta/markets/fundamentals/corporate_calendars", symbols = symbols.len(), http.status = Empty, latency_ms = Empty))]
     async fn get_corporate_calendars(
         &self,
         symbols: &[Symbol],
@@ -577,11 +641,13 @@ impl Fundamentals for TradierRestClient {
             .await
     }
 
+    #[instrument(name = "tradier.rest", skip_all, fields(endpoint = "/beta/markets/fundamentals/dividends", symbols = symbols.len(), http.status = Empty, latency_ms = Empty))]
     async fn get_dividends(&self, symbols: &[Symbol]) -> Result<Vec<DividendResponse>> {
         self.get_fundamentals_array("/beta/markets/fundamentals/dividends", symbols)
             .await
     }
 
+    #[instrument(name = "tradier.rest", skip_all, fields(endpoint = "/beta/markets/fundamentals/corporate_actions", symbols = symbols.len(), http.status = Empty, latency_ms = Empty))]
     async fn get_corporate_actions(
         &self,
         symbols: &[Symbol],
@@ -590,16 +656,19 @@ impl Fundamentals for TradierRestClient {
             .await
     }
 
+    #[instrument(name = "tradier.rest", skip_all, fields(endpoint = "/beta/markets/fundamentals/ratios", symbols = symbols.len(), http.status = Empty, latency_ms = Empty))]
     async fn get_ratios(&self, symbols: &[Symbol]) -> Result<Vec<RatiosResponse>> {
         self.get_fundamentals_array("/beta/markets/fundamentals/ratios", symbols)
             .await
     }
 
+    #[instrument(name = "tradier.rest", skip_all, fields(endpoint = "/beta/markets/fundamentals/financials", symbols = symbols.len(), http.status = Empty, latency_ms = Empty))]
     async fn get_financials(&self, symbols: &[Symbol]) -> Result<Vec<FinancialsResponse>> {
         self.get_fundamentals_array("/beta/markets/fundamentals/financials", symbols)
             .await
     }
 
+    #[instrument(name = "tradier.rest", skip_all, fields(endpoint = "/beta/markets/fundamentals/statistics", symbols = symbols.len(), http.status = Empty, latency_ms = Empty))]
     async fn get_statistics(&self, symbols: &[Symbol]) -> Result<Vec<StatisticsResponse>> {
         self.get_fundamentals_array("/beta/markets/fundamentals/statistics", symbols)
             .await
@@ -607,6 +676,89 @@ impl Fundamentals for TradierRestClient {
 }
 
 #[cfg(test)]
+mod tracing_tests {
+    use std::collections::HashMap;
+    use std::sync::{Arc, Mutex};
+
+    use super::*;
+    use crate::{Config, utils::tests::with_env_vars};
+    use httpmock::MockServer;
+    use tracing::field::{Field, Visit};
+    use tracing::span::{Attributes, Id, Record};
+    use tracing_subscriber::layer::{Context, SubscriberExt};
+    use tracing_subscriber::{Layer, Registry};
+
+    /// Collects every field recorded on `tradier.rest` spans.
+    #[derive(Clone, Default)]
+    struct FieldCapture(Arc<Mutex<HashMap<String, String>>>);
+
+    impl Visit for FieldCapture {
+        fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+            self.0
+                .lock()
+                .unwrap()
+                .insert(field.name().to_owned(), format!("{value:?}"));
+        }
+    }
+
+    impl<S: tracing::Subscriber> Layer<S> for FieldCapture {
+        fn on_new_span(&self, attrs: &Attributes<'_>, _id: &Id, _ctx: Context<'_, S>) {
+            if attrs.metadata().name() == "tradier.rest" {
+                attrs.record(&mut self.clone());
+            }
+        }
+
+        fn on_record(&self, _id: &Id, values: &Record<'_>, _ctx: Context<'_, S>) {
+            values.record(&mut self.clone());
+        }
+    }
+
+    #[test]
+    fn test_account_call_span_records_endpoint_redacted_account_status_and_latency() {
+        let server = MockServer::start();
+        let op = server.mock(|when, then| {
+            when.path("/v1/accounts/VA12345678/positions");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body(r#"{"positions":"null"}"#);
+        });
+        let capture = FieldCapture::default();
+        let subscriber = Registry::default().with(capture.clone());
+        with_env_vars(
+            vec![
+                ("TRADIER_REST_BASE_URL", &server.base_url()),
+                ("TRADIER_ACCESS_TOKEN", "testToken"),
+            ],
+            || {
+                tracing::subscriber::with_default(subscriber, || {
+                    let rt = tokio::runtime::Builder::new_current_thread()
+                        .enable_all()
+                        .build()
+                        .expect("runtime");
+                    rt.block_on(async {
+                        let client = TradierRestClient::new(Config::new());
+                        let account = "VA12345678".parse().expect("account");
+                        let _ = client.get_account_positions(&account).await;
+                    });
+                });
+            },
+        );
+        op.assert();
+        let fields = capture.0.lock().unwrap();
+        assert_eq!(
+            fields.get("endpoint").map(String::as_str),
+            Some("\"/v1/accounts/{account_id}/positions\"")
+        );
+        assert_eq!(
+            fields.get("account").map(String::as_str),
+            Some("******5678")
+        );
+        assert_eq!(fields.get("http.status").map(String::as_str), Some("200"));
+        assert!(fields.contains_key("latency_ms"));
+    }
+}
+
+#[cfg(all(test, feature = "fundamentals"))]
 mod fundamentals_tests {
     use super::*;
     use crate::{
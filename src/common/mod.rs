@@ -2,6 +2,10 @@ use std::str::FromStr;
 
 use serde::Deserialize;
 
+mod option_symbol;
+
+pub use option_symbol::{OptionSymbol, OptionType};
+
 #[derive(Debug, Clone, Deserialize, PartialEq)]
 #[serde(rename_all = "lowercase")]
 #[non_exhaustive]
@@ -84,6 +88,13 @@ impl Symbols {
         &self.0
     }
 
+    /// Returns the number of symbols.
+    #[inline]
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
     /// Returns `true` if no symbols have been added.
     #[inline]
     #[must_use]
@@ -0,0 +1,461 @@
+//! OCC option symbols.
+//!
+//! Tradier identifies option contracts by their OCC (OSI) symbol: the
+//! underlying's option root, the expiration as `YYMMDD`, `C` or `P`, and
+//! the strike times 1000 as eight digits. `SPY240119C00450000` is the SPY
+//! $450 call expiring 2024-01-19. Tradier sends the compact form without
+//! the space padding the OCC specification puts after roots shorter than
+//! six characters; [`OptionSymbol`] parses both.
+
+use std::fmt;
+use std::str::FromStr;
+
+use chrono::{Datelike, NaiveDate};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use super::Symbol;
+use crate::{Error, Result};
+
+/// Longest option root the OCC format allows.
+const MAX_ROOT_LEN: usize = 6;
+/// Length of the `YYMMDD` + `C`/`P` + strike suffix.
+const SUFFIX_LEN: usize = 15;
+/// Largest strike, in thousandths, that fits in eight digits.
+const MAX_STRIKE_THOUSANDTHS: u32 = 99_999_999;
+
+/// Call or put.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum OptionType {
+    Call,
+    Put,
+}
+
+impl OptionType {
+    /// Returns the OCC code: `C` or `P`.
+    #[must_use]
+    pub fn code(self) -> char {
+        match self {
+            OptionType::Call => 'C',
+            OptionType::Put => 'P',
+        }
+    }
+}
+
+impl FromStr for OptionType {
+    type Err = Error;
+
+    /// Parses Tradier's `call` / `put` or the OCC `C` / `P` code, ignoring
+    /// case.
+    fn from_str(s: &str) -> Result<Self> {
+        if s.eq_ignore_ascii_case("call") || s.eq_ignore_ascii_case("c") {
+            Ok(OptionType::Call)
+        } else if s.eq_ignore_ascii_case("put") || s.eq_ignore_ascii_case("p") {
+            Ok(OptionType::Put)
+        } else {
+            Err(Error::MarketDataParseError(format!(
+                "invalid option type: '{s}' must be call or put"
+            )))
+        }
+    }
+}
+
+impl fmt::Display for OptionType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            OptionType::Call => "call",
+            OptionType::Put => "put",
+        })
+    }
+}
+
+/// A parsed OCC option symbol.
+///
+/// The root is kept as sent, so adjusted contracts (`AAPL1` after a
+/// corporate action) and non-standard roots (`SPXW` weeklies, `BRKB`)
+/// round-trip unchanged. It is not necessarily the underlying's ticker.
+///
+/// # Example
+/// ```
+/// use chrono::NaiveDate;
+/// use tradier::common::{OptionSymbol, OptionType};
+///
+/// let symbol: OptionSymbol = "SPXW240119P04712500".parse()?;
+/// assert_eq!(symbol.root(), "SPXW");
+/// assert_eq!(symbol.expiration(), NaiveDate::from_ymd_opt(2024, 1, 19).unwrap());
+/// assert_eq!(symbol.option_type(), OptionType::Put);
+/// assert_eq!(symbol.strike(), 4712.5);
+///
+/// let built = OptionSymbol::builder()
+///     .root("SPXW")
+///     .expiration(NaiveDate::from_ymd_opt(2024, 1, 19).unwrap())
+///     .option_type(OptionType::Put)
+///     .strike(4712.5)
+///     .build()?;
+/// assert_eq!(built, symbol);
+/// assert_eq!(built.to_osi(), "SPXW  240119P04712500");
+/// # Ok::<(), tradier::Error>(())
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct OptionSymbol {
+    root: String,
+    expiration: NaiveDate,
+    option_type: OptionType,
+    strike_thousandths: u32,
+}
+
+#[bon::bon]
+impl OptionSymbol {
+    /// Builds a symbol from its components. `strike` is rounded to the
+    /// nearest 1/1000.
+    ///
+    /// # Errors
+    /// Returns [`Error::MarketDataParseError`] if the root is not one to
+    /// six ASCII uppercase letters or digits, the expiration is outside
+    /// 2000–2099, or the strike is not positive or does not fit in eight
+    /// digits.
+    #[builder(builder_type(vis = "pub"))]
+    fn new(
+        root: &str,
+        expiration: NaiveDate,
+        option_type: OptionType,
+        strike: f64,
+    ) -> Result<Self> {
+        validate_root(root)?;
+        if !(2000..=2099).contains(&expiration.year()) {
+            return Err(invalid(format!(
+                "expiration {expiration} is outside 2000-2099"
+            )));
+        }
+        let thousandths = (strike * 1000.0).round();
+        if !(1.0..=f64::from(MAX_STRIKE_THOUSANDTHS)).contains(&thousandths) {
+            return Err(invalid(format!("strike {strike} is out of range")));
+        }
+        Ok(Self {
+            root: root.to_owned(),
+            expiration,
+            option_type,
+            strike_thousandths: thousandths as u32,
+        })
+    }
+
+    /// Returns the option root, e.g. `SPY`, `SPXW` or `AAPL1`.
+    #[must_use]
+    pub fn root(&self) -> &str {
+        &self.root
+    }
+
+    /// Returns the expiration date.
+    #[must_use]
+    pub fn expiration(&self) -> NaiveDate {
+        self.expiration
+    }
+
+    /// Returns whether this is a call or a put.
+    #[must_use]
+    pub fn option_type(&self) -> OptionType {
+        self.option_type
+    }
+
+    /// Returns `true` for calls.
+    #[must_use]
+    pub fn is_call(&self) -> bool {
+        self.option_type == OptionType::Call
+    }
+
+    /// Returns `true` for puts.
+    #[must_use]
+    pub fn is_put(&self) -> bool {
+        self.option_type == OptionType::Put
+    }
+
+    /// Returns the strike price.
+    #[must_use]
+    pub fn strike(&self) -> f64 {
+        f64::from(self.strike_thousandths) / 1000.0
+    }
+
+    /// Returns the strike in thousandths of a dollar, exactly as encoded
+    /// in the symbol.
+    #[must_use]
+    pub fn strike_thousandths(&self) -> u32 {
+        self.strike_thousandths
+    }
+
+    /// Returns the strike price as an exact [`rust_decimal::Decimal`].
+    #[cfg(feature = "decimal")]
+    #[must_use]
+    pub fn strike_decimal(&self) -> rust_decimal::Decimal {
+        rust_decimal::Decimal::new(i64::from(self.strike_thousandths), 3)
+    }
+
+    /// Formats the 21-character OSI form, with the root padded to six
+    /// characters: `SPY   240119C00450000`.
+    #[must_use]
+    pub fn to_osi(&self) -> String {
+        format!("{:<MAX_ROOT_LEN$}{}", self.root, self.suffix())
+    }
+
+    fn suffix(&self) -> String {
+        format!(
+            "{:02}{:02}{:02}{}{:08}",
+            self.expiration.year() - 2000,
+            self.expiration.month(),
+            self.expiration.day(),
+            self.option_type.code(),
+            self.strike_thousandths
+        )
+    }
+}
+
+fn invalid(reason: String) -> Error {
+    Error::MarketDataParseError(format!("invalid option symbol: {reason}"))
+}
+
+fn validate_root(root: &str) -> Result<()> {
+    if root.is_empty() || root.len() > MAX_ROOT_LEN {
+        return Err(invalid(format!("root '{root}' must be 1 to 6 characters")));
+    }
+    if !root
+        .bytes()
+        .all(|b| b.is_ascii_uppercase() || b.is_ascii_digit())
+    {
+        return Err(invalid(format!(
+            "root '{root}' must be uppercase letters or digits"
+        )));
+    }
+    Ok(())
+}
+
+fn parse_digits(digits: &str, field: &str, s: &str) -> Result<u32> {
+    if !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(invalid(format!("'{s}' has a non-numeric {field}")));
+    }
+    digits
+        .parse()
+        .map_err(|_| invalid(format!("'{s}' has a non-numeric {field}")))
+}
+
+impl FromStr for OptionSymbol {
+    type Err = Error;
+
+    /// Parses the compact (`SPY240119C00450000`) or space-padded OSI
+    /// (`SPY   240119C00450000`) form.
+    fn from_str(s: &str) -> Result<Self> {
+        if !s.is_ascii() || s.len() <= SUFFIX_LEN {
+            return Err(invalid(format!("'{s}' is too short or not ASCII")));
+        }
+        let (root, suffix) = s.split_at(s.len() - SUFFIX_LEN);
+        let root = root.trim_end_matches(' ');
+        validate_root(root)?;
+
+        let year = parse_digits(&suffix[0..2], "expiration", s)?;
+        let month = parse_digits(&suffix[2..4], "expiration", s)?;
+        let day = parse_digits(&suffix[4..6], "expiration", s)?;
+        let expiration = NaiveDate::from_ymd_opt(2000 + year as i32, month, day)
+            .ok_or_else(|| invalid(format!("'{s}' has an invalid expiration date")))?;
+        let option_type = match &suffix[6..7] {
+            "C" => OptionType::Call,
+            "P" => OptionType::Put,
+            other => return Err(invalid(format!("'{s}' has type '{other}', not C or P"))),
+        };
+        let strike_thousandths = parse_digits(&suffix[7..], "strike", s)?;
+        if strike_thousandths == 0 {
+            return Err(invalid(format!("'{s}' has a zero strike")));
+        }
+        Ok(Self {
+            root: root.to_owned(),
+            expiration,
+            option_type,
+            strike_thousandths,
+        })
+    }
+}
+
+impl fmt::Display for OptionSymbol {
+    /// Formats the compact form Tradier uses: `SPY240119C00450000`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.root)?;
+        f.write_str(&self.suffix())
+    }
+}
+
+impl From<&OptionSymbol> for Symbol {
+    fn from(symbol: &OptionSymbol) -> Self {
+        Symbol(symbol.to_string())
+    }
+}
+
+impl From<OptionSymbol> for Symbol {
+    fn from(symbol: OptionSymbol) -> Self {
+        Symbol::from(&symbol)
+    }
+}
+
+impl TryFrom<&Symbol> for OptionSymbol {
+    type Error = Error;
+
+    fn try_from(symbol: &Symbol) -> Result<Self> {
+        symbol.as_str().parse()
+    }
+}
+
+impl Serialize for OptionSymbol {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for OptionSymbol {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::*;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[test]
+    fn test_parse_compact_and_osi_forms() {
+        let compact: OptionSymbol = "SPY240119C00450000".parse().unwrap();
+        let osi: OptionSymbol = "SPY   240119C00450000".parse().unwrap();
+        assert_eq!(compact, osi);
+        assert_eq!(compact.root(), "SPY");
+        assert_eq!(compact.expiration(), date(2024, 1, 19));
+        assert!(compact.is_call());
+        assert_eq!(compact.strike(), 450.0);
+        assert_eq!(compact.to_string(), "SPY240119C00450000");
+        assert_eq!(compact.to_osi(), "SPY   240119C00450000");
+    }
+
+    #[test]
+    fn test_parse_keeps_adjusted_roots_and_fractional_strikes() {
+        let adjusted: OptionSymbol = "AAPL1250620P00132500".parse().unwrap();
+        assert_eq!(adjusted.root(), "AAPL1");
+        assert_eq!(adjusted.strike(), 132.5);
+        assert_eq!(adjusted.strike_thousandths(), 132_500);
+
+        let penny: OptionSymbol = "F250117C00012125".parse().unwrap();
+        assert_eq!(penny.strike(), 12.125);
+        assert_eq!(penny.to_string(), "F250117C00012125");
+    }
+
+    #[test]
+    fn test_parse_rejects_malformed_symbols() {
+        for bad in [
+            "",
+            "SPY",
+            "240119C00450000",
+            "TOOLONG240119C00450000",
+            "spy240119C00450000",
+            "SPY241319C00450000",
+            "SPY240119X00450000",
+            "SPY240119C0045000A",
+            "SPY240119C00000000",
+            "SPY24011９C00450000",
+        ] {
+            assert!(
+                matches!(
+                    bad.parse::<OptionSymbol>(),
+                    Err(Error::MarketDataParseError(_))
+                ),
+                "accepted {bad:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_builder_validates_components() {
+        let symbol = OptionSymbol::builder()
+            .root("SPY")
+            .expiration(date(2024, 1, 19))
+            .option_type(OptionType::Call)
+            .strike(450.0)
+            .build()
+            .unwrap();
+        assert_eq!(symbol.to_string(), "SPY240119C00450000");
+
+        let build = |root: &str, expiration: NaiveDate, strike: f64| {
+            OptionSymbol::builder()
+                .root(root)
+                .expiration(expiration)
+                .option_type(OptionType::Put)
+                .strike(strike)
+                .build()
+        };
+        assert!(build("", date(2024, 1, 19), 1.0).is_err());
+        assert!(build("SPY", date(1999, 1, 19), 1.0).is_err());
+        assert!(build("SPY", date(2024, 1, 19), 0.0).is_err());
+        assert!(build("SPY", date(2024, 1, 19), 100_000.0).is_err());
+        assert!(build("SPY", date(2024, 1, 19), f64::NAN).is_err());
+    }
+
+    #[test]
+    fn test_symbol_and_serde_conversions() {
+        let option: OptionSymbol = "SPY240119C00450000".parse().unwrap();
+        let symbol = Symbol::from(&option);
+        assert_eq!(symbol.as_str(), "SPY240119C00450000");
+        assert_eq!(OptionSymbol::try_from(&symbol).unwrap(), option);
+        assert!(OptionSymbol::try_from(&"SPY".parse::<Symbol>().unwrap()).is_err());
+
+        let json = serde_json::to_string(&option).unwrap();
+        assert_eq!(json, r#""SPY240119C00450000""#);
+        assert_eq!(serde_json::from_str::<OptionSymbol>(&json).unwrap(), option);
+    }
+
+    #[test]
+    fn test_option_type_parses_names_and_codes() {
+        assert_eq!("call".parse::<OptionType>().unwrap(), OptionType::Call);
+        assert_eq!("P".parse::<OptionType>().unwrap(), OptionType::Put);
+        assert!("straddle".parse::<OptionType>().is_err());
+        assert_eq!(
+            OptionType::Put.to_string().parse::<OptionType>().unwrap(),
+            OptionType::Put
+        );
+    }
+
+    fn option_symbol() -> impl Strategy<Value = OptionSymbol> {
+        (
+            "[A-Z][A-Z0-9]{0,5}",
+            0..36_525i64,
+            any::<bool>(),
+            1..=MAX_STRIKE_THOUSANDTHS,
+        )
+            .prop_map(|(root, days, call, strike_thousandths)| OptionSymbol {
+                root,
+                expiration: date(2000, 1, 1) + chrono::Duration::days(days),
+                option_type: if call {
+                    OptionType::Call
+                } else {
+                    OptionType::Put
+                },
+                strike_thousandths,
+            })
+    }
+
+    proptest! {
+        #[test]
+        fn test_round_trips_through_every_representation(symbol in option_symbol()) {
+            prop_assert_eq!(symbol.to_string().parse::<OptionSymbol>().unwrap(), symbol.clone());
+            prop_assert_eq!(symbol.to_osi().parse::<OptionSymbol>().unwrap(), symbol.clone());
+            prop_assert_eq!(symbol.to_osi().len(), 21);
+            prop_assert_eq!(OptionSymbol::try_from(&Symbol::from(&symbol)).unwrap(), symbol.clone());
+
+            let rebuilt = OptionSymbol::builder()
+                .root(symbol.root())
+                .expiration(symbol.expiration())
+                .option_type(symbol.option_type())
+                .strike(symbol.strike())
+                .build()
+                .unwrap();
+            prop_assert_eq!(rebuilt, symbol);
+        }
+    }
+}
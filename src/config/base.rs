@@ -1,12 +1,13 @@
 use crate::constants::{
-    TRADIER_API_BASE_URL, TRADIER_STREAM_EVENTS_PATH, TRADIER_STREAM_HTTP_BASE_URL,
-    TRADIER_WS_BASE_URL,
+    TRADIER_API_BASE_URL, TRADIER_SESSION_TIMEOUT, TRADIER_STREAM_EVENTS_PATH,
+    TRADIER_STREAM_HTTP_BASE_URL, TRADIER_WS_BASE_URL,
 };
 use serde::Deserialize;
 use std::env;
 use std::fmt;
 use std::fmt::Debug;
 use std::str::FromStr;
+use std::time::Duration;
 use tracing::error;
 
 /// The `Credentials` struct stores sensitive information required for
@@ -57,12 +58,39 @@ pub struct RestApiConfig {
 /// - `ws_base_url`: The base URL for WebSocket streaming.
 /// - `events_path`: Path for event streams.
 /// - `reconnect_interval`: Interval (in seconds) for reconnect attempts.
+/// - `session_timeout`: Time (in seconds) after which a streaming session id is considered stale.
+/// - `idle_timeout`: Time (in seconds) without any received frame after which a stream fails with
+///   `Error::StreamIdle`. `0` disables the check.
+/// - `ping_interval`: Interval (in seconds) between client pings on WebSocket streams. `0`
+///   disables pings.
 #[derive(Debug, Deserialize, Clone)]
 pub struct StreamingConfig {
     pub http_base_url: String,
     pub ws_base_url: String,
     pub events_path: String,
     pub reconnect_interval: u64,
+    #[serde(default = "default_session_timeout")]
+    pub session_timeout: u64,
+    #[serde(default)]
+    pub idle_timeout: u64,
+    #[serde(default)]
+    pub ping_interval: u64,
+}
+
+fn default_session_timeout() -> u64 {
+    TRADIER_SESSION_TIMEOUT
+}
+
+impl StreamingConfig {
+    /// Returns `idle_timeout` as a [`Duration`], or `None` when disabled.
+    pub fn idle_timeout(&self) -> Option<Duration> {
+        (self.idle_timeout > 0).then(|| Duration::from_secs(self.idle_timeout))
+    }
+
+    /// Returns `ping_interval` as a [`Duration`], or `None` when disabled.
+    pub fn ping_interval(&self) -> Option<Duration> {
+        (self.ping_interval > 0).then(|| Duration::from_secs(self.ping_interval))
+    }
 }
 
 /// Implements `fmt::Display` for `Credentials`, providing a JSON-style output
@@ -105,13 +133,19 @@ impl fmt::Display for RestApiConfig {
 }
 
 /// Implements `fmt::Display` for `StreamingConfig`, displaying HTTP and WebSocket URLs,
-/// event path, and reconnect interval in JSON format.
+/// event path, reconnect interval, session timeout and heartbeat settings in JSON format.
 impl fmt::Display for StreamingConfig {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(
             f,
-            "{{\"http_base_url\":\"{}\",\"ws_base_url\":\"{}\",\"events_path\":\"{}\",\"reconnect_interval\":{}}}",
-            self.http_base_url, self.ws_base_url, self.events_path, self.reconnect_interval
+            "{{\"http_base_url\":\"{}\",\"ws_base_url\":\"{}\",\"events_path\":\"{}\",\"reconnect_interval\":{},\"session_timeout\":{},\"idle_timeout\":{},\"ping_interval\":{}}}",
+            self.http_base_url,
+            self.ws_base_url,
+            self.events_path,
+            self.reconnect_interval,
+            self.session_timeout,
+            self.idle_timeout,
+            self.ping_interval
         )
     }
 }
@@ -184,6 +218,12 @@ impl Config {
                     String::from(TRADIER_STREAM_EVENTS_PATH),
                 ),
                 reconnect_interval: get_env_or_default("TRADIER_STREAM_RECONNECT_INTERVAL", 5),
+                session_timeout: get_env_or_default(
+                    "TRADIER_STREAM_SESSION_TIMEOUT",
+                    TRADIER_SESSION_TIMEOUT,
+                ),
+                idle_timeout: get_env_or_default("TRADIER_STREAM_IDLE_TIMEOUT", 0),
+                ping_interval: get_env_or_default("TRADIER_STREAM_PING_INTERVAL", 0),
             },
         }
     }
@@ -236,6 +276,9 @@ mod tests_config {
             assert_eq!(config.streaming.ws_base_url, TRADIER_WS_BASE_URL);
             assert_eq!(config.streaming.events_path, TRADIER_STREAM_EVENTS_PATH);
             assert_eq!(config.streaming.reconnect_interval, 5);
+            assert_eq!(config.streaming.session_timeout, 300);
+            assert_eq!(config.streaming.idle_timeout, 0);
+            assert_eq!(config.streaming.ping_interval, 0);
             assert_eq!(config.rest_api.timeout, 30);
         });
     }
@@ -258,6 +301,9 @@ mod tests_config {
                 ("TRADIER_WS_BASE_URL", "wss://test-ws.tradier.com"),
                 ("TRADIER_STREAM_EVENTS_PATH", "/v1/test/events"),
                 ("TRADIER_STREAM_RECONNECT_INTERVAL", "10"),
+                ("TRADIER_STREAM_SESSION_TIMEOUT", "120"),
+                ("TRADIER_STREAM_IDLE_TIMEOUT", "30"),
+                ("TRADIER_STREAM_PING_INTERVAL", "10"),
             ],
             || {
                 let config = Config::new();
@@ -280,6 +326,9 @@ mod tests_config {
                 assert_eq!(config.streaming.ws_base_url, "wss://test-ws.tradier.com");
                 assert_eq!(config.streaming.events_path, "/v1/test/events");
                 assert_eq!(config.streaming.reconnect_interval, 10);
+                assert_eq!(config.streaming.session_timeout, 120);
+                assert_eq!(config.streaming.idle_timeout, 30);
+                assert_eq!(config.streaming.ping_interval, 10);
             },
         );
     }
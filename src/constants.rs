@@ -20,6 +20,7 @@ pub(crate) const TRADIER_STREAM_HTTP_BASE_URL: &str = "https://stream.tradier.co
 /// This path is appended to the base URL (either WebSocket or HTTP) to access market event streams.
 pub(crate) const TRADIER_STREAM_EVENTS_PATH: &str = "/v1/markets/events";
 
-/// The default session timeout in seconds for Tradier API sessions.
-/// This value is used to set timeout limits for API session-based requests.
-pub(crate) const TRADIER_SESSION_TIMEOUT: i64 = 5;
+/// The default lifetime, in seconds, of a Tradier streaming session id.
+/// Tradier expires session ids five minutes after creation unless they are
+/// used to connect.
+pub(crate) const TRADIER_SESSION_TIMEOUT: u64 = 300;
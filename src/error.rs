@@ -1,5 +1,7 @@
+#[cfg(any(feature = "wssession", feature = "streaming"))]
 use reqwest::StatusCode;
 
+#[cfg(any(feature = "wssession", feature = "streaming"))]
 use crate::wssession::session::SessionType;
 
 /// A specialized `Result` type for the Tradier API client, using `Error` for errors.
@@ -16,7 +18,7 @@ pub type Result<T> = core::result::Result<T, Error>;
 ///   HTTP status, and response body for troubleshooting.
 /// - `JsonParsingError`: Raised when parsing JSON data into the expected session response structure fails.
 /// - `MissingAccessToken`: Indicates a missing access token, which is required for API authentication.
-/// - `SessionAlreadyExists`: Raised when attempting to create a duplicate session where one already exists.
+/// - `SessionAlreadyExists`: Raised when the session manager's limit for that session type is already reached.
 /// - `NetworkError`: Wraps network-related errors that occur during API requests, sourced from `reqwest`.
 /// - `WebSocketError`: Wraps WebSocket-related errors, sourced from the `tungstenite` crate.
 /// - `UnexpectedError`: Represents any other unexpected error with an accompanying error message.
@@ -42,6 +44,7 @@ pub enum Error {
     /// - `SessionType`: Type of session (e.g., `Account`, `Market`) attempted to create.
     /// - `StatusCode`: HTTP status code returned by the API.
     /// - `String`: Body of the API response.
+    #[cfg(any(feature = "wssession", feature = "streaming"))]
     #[error("Failed to create {0} session. Status: {1}. Body: {2}")]
     CreateSessionError(SessionType, StatusCode, String),
 
@@ -59,6 +62,13 @@ pub enum Error {
     #[error("Unsupported AccountSessionEvent: {0}")]
     UnsupportedAccountEvent(String),
 
+    /// Error if an order status string is not a known [`crate::types::OrderStatus`].
+    ///
+    /// # Parameters
+    /// - `String`: The offending status string from the wire.
+    #[error("Unsupported OrderStatus: {0}")]
+    UnsupportedOrderStatus(String),
+
     /// Error when JSON parsing fails while handling session response data.
     ///
     /// # Source
@@ -70,7 +80,8 @@ pub enum Error {
     #[error("Missing Access Token")]
     MissingAccessToken,
 
-    /// Error raised when attempting to create a session that already exists.
+    /// Error raised when creating a session would exceed the
+    /// `SessionManager` limit for its type.
     #[error("Session already exists")]
     SessionAlreadyExists,
 
@@ -96,9 +107,47 @@ pub enum Error {
     ///
     /// # Source
     /// Wraps `tungstenite::Error` for more detailed WebSocket connection error reporting.
+    #[cfg(feature = "wssession")]
     #[error("WebSocket error: {0}")]
     WebSocketError(#[from] Box<tungstenite::Error>),
 
+    /// Error raised when a supervised stream gives up reconnecting.
+    ///
+    /// # Parameters
+    /// - `u32`: The number of consecutive reconnect attempts made.
+    /// - `String`: The failure that triggered the last attempt.
+    #[cfg(feature = "wssession")]
+    #[error("Stream reconnect gave up after {0} attempts: {1}")]
+    ReconnectAttemptsExhausted(u32, String),
+
+    /// Error raised when a stream receives no frame within its configured
+    /// idle timeout. The stream ends after yielding it.
+    ///
+    /// # Parameters
+    /// - `Duration`: The idle timeout that elapsed.
+    #[cfg(any(feature = "wssession", feature = "streaming"))]
+    #[error("No stream data received for {0:?}")]
+    StreamIdle(std::time::Duration),
+
+    /// Error raised when a [`crate::wssession::HubReceiver`] falls too far
+    /// behind with [`crate::wssession::LagPolicy::Disconnect`]. The receiver
+    /// ends after yielding it.
+    ///
+    /// # Parameters
+    /// - `u64`: The number of events the receiver missed.
+    #[cfg(any(feature = "wssession", feature = "streaming"))]
+    #[error("Stream subscriber lagged behind and missed {0} events")]
+    SubscriberLagged(u64),
+
+    /// Error yielded by a [`crate::wssession::HubReceiver`] when the hub's
+    /// upstream stream ended with an error rather than closing normally.
+    ///
+    /// # Parameters
+    /// - `String`: The upstream error message.
+    #[cfg(any(feature = "wssession", feature = "streaming"))]
+    #[error("Upstream market stream failed: {0}")]
+    StreamFailed(String),
+
     /// Error raised when a streaming event payload cannot be decoded into a
     /// known [`crate::wssession::MarketEvent`] variant.
     ///
@@ -126,6 +175,32 @@ pub enum Error {
     #[error("Failed to parse {0} as u64: {1}")]
     ParseInt(String, String),
 
+    /// Error raised when a monetary value cannot be represented as an exact
+    /// [`rust_decimal::Decimal`].
+    ///
+    /// # Parameters
+    /// - `String`: The offending value.
+    /// - `String`: The underlying parse error message.
+    #[cfg(feature = "decimal")]
+    #[error("Failed to parse {0} as Decimal: {1}")]
+    ParseDecimal(String, String),
+
+    /// Error raised when an option cannot be priced, e.g. when no
+    /// volatility reproduces its market price.
+    ///
+    /// # Parameters
+    /// - `String`: Description of the failure.
+    #[error("Pricing error: {0}")]
+    PricingError(String),
+
+    /// Error raised when an order or option strategy cannot be assembled,
+    /// e.g. a leg is missing from the chain or a limit price is absent.
+    ///
+    /// # Parameters
+    /// - `String`: Description of the problem.
+    #[error("Invalid order: {0}")]
+    InvalidOrder(String),
+
     /// Represents any unexpected error, including a custom message for additional context.
     ///
     /// # Parameters
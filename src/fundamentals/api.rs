@@ -77,6 +77,7 @@ pub mod non_blocking {
     }
 }
 
+#[cfg(feature = "blocking")]
 pub mod blocking {
     use super::*;
 
@@ -8,14 +8,19 @@ mod config;
 mod constants;
 mod error;
 pub mod utils;
+#[cfg(any(feature = "wssession", feature = "streaming"))]
 pub mod wssession;
 pub use error::{Error, Result};
+#[cfg(feature = "decimal")]
+pub use rust_decimal::Decimal;
 
 mod accounts;
 mod client;
 pub mod common;
+#[cfg(feature = "fundamentals")]
 mod fundamentals;
 mod market_data;
+#[cfg(feature = "streaming")]
 pub mod streaming;
 mod trading;
 mod user;
@@ -23,7 +28,8 @@ mod watchlists;
 
 pub mod types {
     pub use crate::accounts::types::*;
-    pub use crate::common::SortOrder;
+    pub use crate::common::{OptionSymbol, OptionType, SortOrder};
+    #[cfg(feature = "fundamentals")]
     pub use crate::fundamentals::types::{
         AssetClassification, CashDividend, CompanyProfile, CompanyResponse, CompanyResult,
         CompanyTables, CorporateActionResponse, CorporateActionResult, CorporateActionTables,
@@ -35,14 +41,28 @@ pub mod types {
         StatisticsResponse, StatisticsResult, StatisticsTables, StockSplit, TrailingReturns,
         ValuationRatios,
     };
+    pub use crate::market_data::option_chain::{
+        ExpirationType, Moneyness, OptionChain, OptionContract, StrikeRow,
+    };
+    #[cfg(any(feature = "streaming", feature = "wssession"))]
+    pub use crate::market_data::option_surface::OptionSurface;
+    pub use crate::market_data::pricing::{
+        BlackScholes, OptionQuote, PriceSource, Valuation, years_to_expiry,
+    };
     pub use crate::market_data::types::*;
+    pub use crate::trading::strategy::{
+        LegSide, OptionStrategy, PayoffProfile, StrategyKind, StrategyLeg, StrikeSelector,
+    };
+    pub use crate::trading::types::{LegRequest, MultilegOrderRequest};
     pub use crate::user::types::*;
     pub use crate::utils::OneOrMany;
 }
+#[cfg(feature = "blocking")]
 pub mod blocking {
     pub use super::client::blocking::BlockingTradierRestClient as Client;
     pub mod operation {
         pub use crate::accounts::api::blocking::Accounts;
+        #[cfg(feature = "fundamentals")]
         pub use crate::fundamentals::api::blocking::Fundamentals;
         pub use crate::market_data::api::blocking::MarketData;
         pub use crate::user::api::blocking::User;
@@ -53,6 +73,7 @@ pub mod non_blocking {
     pub use super::client::non_blocking::TradierRestClient as Client;
     pub mod operation {
         pub use crate::accounts::api::non_blocking::Accounts;
+        #[cfg(feature = "fundamentals")]
         pub use crate::fundamentals::api::non_blocking::Fundamentals;
         pub use crate::market_data::api::non_blocking::MarketData;
         pub use crate::user::api::non_blocking::User;
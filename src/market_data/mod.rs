@@ -6,6 +6,11 @@
 //! Upstream docs: <https://documentation.tradier.com/brokerage-api/markets/>.
 
 pub mod api;
+pub mod option_chain;
+// Needs Tokio's timer, hence one of the `streaming` / `wssession` features.
+#[cfg(any(feature = "streaming", feature = "wssession"))]
+pub mod option_surface;
+pub mod pricing;
 #[cfg(test)]
 pub(crate) mod test_support;
 pub mod types;
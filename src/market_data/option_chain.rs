@@ -0,0 +1,565 @@
+//! Typed view over an option chain.
+//!
+//! [`GetOptionChainsResponse`] mirrors the wire format: a flat list of
+//! [`Quote`]s where the call/put flag, strike, expiration and greeks are
+//! loose optional fields. [`OptionChain`] validates those fields once and
+//! groups the contracts by strike into call/put pairs, sorted by strike,
+//! with lookups by strike, by distance to the underlying price, by delta
+//! and by moneyness.
+
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashSet};
+
+use chrono::NaiveDate;
+use tracing::debug;
+
+use super::types::{GetOptionChainsResponse, GreeksData, Quote};
+use crate::common::OptionType;
+use crate::{Error, Result};
+
+/// Expiration cycle of a contract, from the quote's `expiration_type`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ExpirationType {
+    /// Monthly (third Friday) expiration.
+    Standard,
+    Weeklys,
+    Quarterlys,
+    /// End-of-month expiration.
+    Eom,
+    /// A value this crate does not know yet, kept verbatim.
+    Other(String),
+}
+
+impl From<&str> for ExpirationType {
+    fn from(s: &str) -> Self {
+        match s {
+            "standard" => ExpirationType::Standard,
+            "weeklys" => ExpirationType::Weeklys,
+            "quarterlys" => ExpirationType::Quarterlys,
+            "eom" => ExpirationType::Eom,
+            other => ExpirationType::Other(other.to_owned()),
+        }
+    }
+}
+
+/// Where a strike sits relative to the underlying price.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Moneyness {
+    InTheMoney,
+    AtTheMoney,
+    OutOfTheMoney,
+}
+
+impl Moneyness {
+    /// Classifies an `option_type` contract struck at `strike` with the
+    /// underlying at `underlying_price`.
+    #[must_use]
+    pub fn of(option_type: OptionType, strike: f64, underlying_price: f64) -> Self {
+        match (strike.total_cmp(&underlying_price), option_type) {
+            (Ordering::Equal, _) => Moneyness::AtTheMoney,
+            (Ordering::Less, OptionType::Call) | (Ordering::Greater, OptionType::Put) => {
+                Moneyness::InTheMoney
+            }
+            _ => Moneyness::OutOfTheMoney,
+        }
+    }
+}
+
+/// One option contract of a chain.
+#[derive(Clone, Debug, PartialEq)]
+pub struct OptionContract {
+    quote: Quote,
+    option_type: OptionType,
+    expiration_type: Option<ExpirationType>,
+    expiration: NaiveDate,
+    strike: f64,
+}
+
+impl OptionContract {
+    fn from_quote(quote: Quote) -> Result<Self> {
+        let missing = |field: &str| {
+            Error::MarketDataParseError(format!(
+                "option chain quote {} has no {field}",
+                quote.symbol
+            ))
+        };
+        let option_type = quote
+            .option_type
+            .as_deref()
+            .ok_or_else(|| missing("option_type"))?
+            .parse()?;
+        let strike = quote.strike.ok_or_else(|| missing("strike"))?;
+        let expiration = quote
+            .expiration_date
+            .as_deref()
+            .ok_or_else(|| missing("expiration_date"))?;
+        let expiration = NaiveDate::parse_from_str(expiration, "%Y-%m-%d").map_err(|e| {
+            Error::MarketDataParseError(format!(
+                "option chain quote {} has expiration_date '{expiration}': {e}",
+                quote.symbol
+            ))
+        })?;
+        let expiration_type = quote.expiration_type.as_deref().map(ExpirationType::from);
+        Ok(Self {
+            quote,
+            option_type,
+            expiration_type,
+            expiration,
+            strike,
+        })
+    }
+
+    /// Returns the underlying quote with every upstream field.
+    #[must_use]
+    pub fn quote(&self) -> &Quote {
+        &self.quote
+    }
+
+    /// Returns the OCC option symbol, e.g. `SPY240119C00450000`.
+    #[must_use]
+    pub fn symbol(&self) -> &str {
+        &self.quote.symbol
+    }
+
+    /// Returns the option root, e.g. `SPXW`, falling back to the
+    /// underlying when Tradier sent no root.
+    #[must_use]
+    pub fn root(&self) -> &str {
+        self.quote
+            .root_symbol
+            .as_deref()
+            .or(self.quote.underlying.as_deref())
+            .unwrap_or_default()
+    }
+
+    #[must_use]
+    pub fn option_type(&self) -> OptionType {
+        self.option_type
+    }
+
+    /// Returns the expiration cycle, if Tradier sent one.
+    #[must_use]
+    pub fn expiration_type(&self) -> Option<&ExpirationType> {
+        self.expiration_type.as_ref()
+    }
+
+    #[must_use]
+    pub fn expiration(&self) -> NaiveDate {
+        self.expiration
+    }
+
+    #[must_use]
+    pub fn strike(&self) -> f64 {
+        self.strike
+    }
+
+    /// Returns the greeks, if the chain was requested with them.
+    #[must_use]
+    pub fn greeks(&self) -> Option<&GreeksData> {
+        self.quote.greeks.as_ref()
+    }
+
+    /// Returns the delta, if the chain was requested with greeks.
+    #[must_use]
+    pub fn delta(&self) -> Option<f64> {
+        self.greeks()?.delta
+    }
+
+    /// Returns the midpoint of bid and ask when both are quoted.
+    #[must_use]
+    pub fn mid(&self) -> Option<f64> {
+        Some((self.quote.bid? + self.quote.ask?) / 2.0)
+    }
+
+    /// Classifies the contract against `underlying_price`.
+    #[must_use]
+    pub fn moneyness(&self, underlying_price: f64) -> Moneyness {
+        Moneyness::of(self.option_type, self.strike, underlying_price)
+    }
+}
+
+/// The call and put listed at one strike.
+#[derive(Clone, Debug, PartialEq)]
+pub struct StrikeRow {
+    pub strike: f64,
+    pub call: Option<OptionContract>,
+    pub put: Option<OptionContract>,
+}
+
+impl StrikeRow {
+    /// Returns the contract of `option_type` at this strike.
+    #[must_use]
+    pub fn contract(&self, option_type: OptionType) -> Option<&OptionContract> {
+        match option_type {
+            OptionType::Call => self.call.as_ref(),
+            OptionType::Put => self.put.as_ref(),
+        }
+    }
+}
+
+/// An option chain grouped into [`StrikeRow`]s, sorted by strike.
+///
+/// A chain request covers one expiration, so every row normally holds at
+/// most one call and one put. Some underlyings list several roots on the
+/// same expiration (for example an SPX monthly next to an SPXW weekly),
+/// with a call and a put of each at every strike. The chain then keeps the
+/// root named like the underlying; [`OptionChain::split_by_root`] builds
+/// one chain per root for callers who want every root.
+///
+/// # Example
+/// ```no_run
+/// use tradier::non_blocking::{Client, operation::MarketData};
+/// use tradier::types::{Greeks, OptionChain, OptionType};
+/// use tradier::Config;
+///
+/// # async fn run() -> tradier::Result<()> {
+/// let client = Client::new(Config::new());
+/// let expiration = chrono::NaiveDate::from_ymd_opt(2024, 1, 19).unwrap();
+/// let response = client
+///     .get_option_chains(&"SPY".parse()?, &expiration, Some(Greeks::new(true)))
+///     .await?;
+/// let chain = OptionChain::try_from(response)?;
+///
+/// if let Some(row) = chain.nearest_strike(471.3) {
+///     println!("ATM {}: call {:?} put {:?}", row.strike, row.call, row.put);
+/// }
+/// if let Some(put) = chain.by_delta(OptionType::Put, -0.25) {
+///     println!("25-delta put: {} @ {:?}", put.symbol(), put.mid());
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct OptionChain {
+    rows: Vec<StrikeRow>,
+}
+
+impl OptionChain {
+    /// Builds a chain from option quotes.
+    ///
+    /// When two contracts share a strike and option type, only the
+    /// contracts whose root (see [`OptionContract::root`]) matches the
+    /// quotes' `underlying` are kept.
+    ///
+    /// # Errors
+    /// Returns [`Error::MarketDataParseError`] if a quote lacks its
+    /// option type, strike or expiration date, or one of them is
+    /// malformed, or if two contracts of the underlying's own root share a
+    /// strike and option type.
+    pub fn from_quotes(quotes: impl IntoIterator<Item = Quote>) -> Result<Self> {
+        let mut contracts = quotes
+            .into_iter()
+            .map(OptionContract::from_quote)
+            .collect::<Result<Vec<_>>>()?;
+        let mut listed = HashSet::new();
+        let collides = contracts
+            .iter()
+            .any(|c| !listed.insert((c.strike.to_bits(), c.option_type)));
+        let underlying = contracts.first().and_then(|c| c.quote.underlying.clone());
+        if let Some(underlying) = underlying
+            && collides
+            && contracts.iter().any(|c| c.root() == underlying)
+        {
+            let total = contracts.len();
+            contracts.retain(|c| c.root() == underlying);
+            debug!(
+                %underlying,
+                dropped = total - contracts.len(),
+                "option chain lists several roots, keeping the underlying's own"
+            );
+        }
+        Self::from_contracts(contracts)
+    }
+
+    /// Builds one chain per option root (see [`OptionContract::root`]),
+    /// for responses that list several roots at the same strikes.
+    ///
+    /// # Errors
+    /// Same as [`Self::from_quotes`], for each root.
+    pub fn split_by_root(
+        quotes: impl IntoIterator<Item = Quote>,
+    ) -> Result<BTreeMap<String, OptionChain>> {
+        let mut roots: BTreeMap<String, Vec<OptionContract>> = BTreeMap::new();
+        for quote in quotes {
+            let contract = OptionContract::from_quote(quote)?;
+            roots
+                .entry(contract.root().to_owned())
+                .or_default()
+                .push(contract);
+        }
+        roots
+            .into_iter()
+            .map(|(root, contracts)| Ok((root, Self::from_contracts(contracts)?)))
+            .collect()
+    }
+
+    fn from_contracts(mut contracts: Vec<OptionContract>) -> Result<Self> {
+        // Stable, so the first contract listed at a strike comes first.
+        contracts.sort_by(|a, b| a.strike.total_cmp(&b.strike));
+
+        let mut rows: Vec<StrikeRow> = Vec::new();
+        for contract in contracts {
+            let row = match rows.last_mut() {
+                Some(row) if row.strike == contract.strike => row,
+                _ => {
+                    rows.push(StrikeRow {
+                        strike: contract.strike,
+                        call: None,
+                        put: None,
+                    });
+                    rows.last_mut().expect("just pushed")
+                }
+            };
+            let slot = match contract.option_type {
+                OptionType::Call => &mut row.call,
+                OptionType::Put => &mut row.put,
+            };
+            if let Some(listed) = slot {
+                return Err(Error::MarketDataParseError(format!(
+                    "option chain lists {} and {} as the {} at strike {}; use OptionChain::split_by_root",
+                    listed.symbol(),
+                    contract.symbol(),
+                    contract.option_type,
+                    contract.strike
+                )));
+            }
+            *slot = Some(contract);
+        }
+        Ok(Self { rows })
+    }
+
+    /// Returns the rows, sorted by ascending strike.
+    #[must_use]
+    pub fn strikes(&self) -> &[StrikeRow] {
+        &self.rows
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+
+    /// Returns every call, by ascending strike.
+    pub fn calls(&self) -> impl Iterator<Item = &OptionContract> {
+        self.rows.iter().filter_map(|row| row.call.as_ref())
+    }
+
+    /// Returns every put, by ascending strike.
+    pub fn puts(&self) -> impl Iterator<Item = &OptionContract> {
+        self.rows.iter().filter_map(|row| row.put.as_ref())
+    }
+
+    /// Returns every contract of `option_type`, by ascending strike.
+    pub fn contracts(&self, option_type: OptionType) -> impl Iterator<Item = &OptionContract> {
+        self.rows
+            .iter()
+            .filter_map(move |row| row.contract(option_type))
+    }
+
+    /// Returns the row listed at `strike`. Strikes are compared in
+    /// thousandths, so a computed strike such as `12.2 + 0.1` finds the
+    /// row listed at `12.3`.
+    #[must_use]
+    pub fn strike(&self, strike: f64) -> Option<&StrikeRow> {
+        let key = strike_key(strike);
+        self.rows
+            .binary_search_by(|row| strike_key(row.strike).total_cmp(&key))
+            .ok()
+            .map(|i| &self.rows[i])
+    }
+
+    /// Returns the row whose strike is closest to `price`, i.e. the
+    /// at-the-money strike. Ties go to the lower strike.
+    #[must_use]
+    pub fn nearest_strike(&self, price: f64) -> Option<&StrikeRow> {
+        self.rows.iter().min_by(|a, b| {
+            (a.strike - price)
+                .abs()
+                .total_cmp(&(b.strike - price).abs())
+        })
+    }
+
+    /// Returns the `option_type` contract whose delta is closest to
+    /// `target`, e.g. `0.30` for a 30-delta call or `-0.25` for a
+    /// 25-delta put. Contracts without greeks are skipped.
+    #[must_use]
+    pub fn by_delta(&self, option_type: OptionType, target: f64) -> Option<&OptionContract> {
+        self.contracts(option_type)
+            .filter_map(|contract| Some((contract, (contract.delta()? - target).abs())))
+            .min_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map(|(contract, _)| contract)
+    }
+
+    /// Returns the `option_type` contracts with the given moneyness
+    /// against `underlying_price`, by ascending strike.
+    pub fn by_moneyness(
+        &self,
+        option_type: OptionType,
+        underlying_price: f64,
+        moneyness: Moneyness,
+    ) -> impl Iterator<Item = &OptionContract> {
+        self.contracts(option_type)
+            .filter(move |contract| contract.moneyness(underlying_price) == moneyness)
+    }
+
+    /// Returns the expiration shared by every contract, or `None` if the
+    /// chain is empty or mixes expirations.
+    #[must_use]
+    pub fn expiration(&self) -> Option<NaiveDate> {
+        let mut expirations = self
+            .rows
+            .iter()
+            .flat_map(|row| row.call.iter().chain(&row.put))
+            .map(OptionContract::expiration);
+        let first = expirations.next()?;
+        expirations.all(|e| e == first).then_some(first)
+    }
+}
+
+/// `strike` rounded to thousandths, the precision of OCC strike prices.
+fn strike_key(strike: f64) -> f64 {
+    (strike * 1000.0).round()
+}
+
+impl TryFrom<GetOptionChainsResponse> for OptionChain {
+    type Error = Error;
+
+    /// Builds the chain from a chains response; an empty response (no
+    /// `options`) gives an empty chain. Fails like
+    /// [`OptionChain::from_quotes`].
+    fn try_from(response: GetOptionChainsResponse) -> Result<Self> {
+        match response.options {
+            Some(payload) => Self::from_quotes(payload.option.into_vec()),
+            None => Ok(Self::default()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::market_data::test_support::option_quote_json;
+
+    fn chain() -> OptionChain {
+        let quote = |symbol: &str, option_type: &str, strike: f64, delta: f64| {
+            let mut quote = option_quote_json(symbol, option_type, strike, "2024-01-19");
+            quote["expiration_type"] = "standard".into();
+            quote["greeks"] = serde_json::json!({ "delta": delta });
+            quote
+        };
+        let response = serde_json::json!({ "options": { "option": [
+            quote("SPY240119P00475000", "put", 475.0, -0.62),
+            quote("SPY240119C00465000", "call", 465.0, 0.71),
+            quote("SPY240119C00470000", "call", 470.0, 0.55),
+            quote("SPY240119P00470000", "put", 470.0, -0.45),
+            quote("SPY240119C00475000", "call", 475.0, 0.33),
+            quote("SPY240119P00465000", "put", 465.0, -0.29),
+        ]}});
+        let response: GetOptionChainsResponse = serde_json::from_value(response).unwrap();
+        OptionChain::try_from(response).unwrap()
+    }
+
+    #[test]
+    fn test_groups_contracts_into_sorted_strike_rows() {
+        let chain = chain();
+        let strikes: Vec<f64> = chain.strikes().iter().map(|row| row.strike).collect();
+        assert_eq!(strikes, [465.0, 470.0, 475.0]);
+        let row = chain.strike(470.0).unwrap();
+        assert_eq!(row.call.as_ref().unwrap().symbol(), "SPY240119C00470000");
+        assert_eq!(row.put.as_ref().unwrap().option_type(), OptionType::Put);
+        assert!(chain.strike(471.0).is_none());
+
+        let call = row.contract(OptionType::Call).unwrap();
+        assert_eq!(call.expiration_type(), Some(&ExpirationType::Standard));
+        assert_eq!(call.mid(), Some(1.1));
+        assert_eq!(chain.expiration(), NaiveDate::from_ymd_opt(2024, 1, 19));
+        assert_eq!(chain.calls().count(), 3);
+    }
+
+    #[test]
+    fn test_lookups_by_price_delta_and_moneyness() {
+        let chain = chain();
+        assert_eq!(chain.nearest_strike(471.9).unwrap().strike, 470.0);
+        assert_eq!(chain.nearest_strike(1000.0).unwrap().strike, 475.0);
+        assert_eq!(
+            chain.by_delta(OptionType::Call, 0.30).unwrap().strike(),
+            475.0
+        );
+        assert_eq!(
+            chain.by_delta(OptionType::Put, -0.25).unwrap().strike(),
+            465.0
+        );
+
+        let itm_puts: Vec<f64> = chain
+            .by_moneyness(OptionType::Put, 470.0, Moneyness::InTheMoney)
+            .map(OptionContract::strike)
+            .collect();
+        assert_eq!(itm_puts, [475.0]);
+        let atm = chain
+            .by_moneyness(OptionType::Call, 470.0, Moneyness::AtTheMoney)
+            .count();
+        assert_eq!(atm, 1);
+        assert_eq!(
+            Moneyness::of(OptionType::Call, 465.0, 470.0),
+            Moneyness::InTheMoney
+        );
+    }
+
+    #[test]
+    fn test_colliding_roots_keep_the_underlyings_own() {
+        let quote = |symbol: &str, root: &str, option_type: &str| {
+            let mut quote = option_quote_json(symbol, option_type, 4700.0, "2024-01-19");
+            quote["underlying"] = "SPX".into();
+            quote["root_symbol"] = root.into();
+            quote
+        };
+        let quotes = vec![
+            quote("SPX240119C04700000", "SPX", "call"),
+            quote("SPXW240119C04700000", "SPXW", "call"),
+            quote("SPXW240119P04700000", "SPXW", "put"),
+        ];
+        let response: GetOptionChainsResponse =
+            serde_json::from_value(serde_json::json!({ "options": { "option": quotes } })).unwrap();
+        let chain = OptionChain::try_from(response).unwrap();
+        let row = chain.strike(4700.0).unwrap();
+        assert_eq!(row.call.as_ref().unwrap().symbol(), "SPX240119C04700000");
+        assert!(row.put.is_none());
+
+        let quotes: Vec<Quote> = quotes
+            .into_iter()
+            .map(|quote| serde_json::from_value(quote).unwrap())
+            .collect();
+        let roots = OptionChain::split_by_root(quotes.clone()).unwrap();
+        assert_eq!(roots.keys().collect::<Vec<_>>(), ["SPX", "SPXW"]);
+        let weekly = roots["SPXW"].strike(4700.0).unwrap();
+        assert_eq!(weekly.call.as_ref().unwrap().root(), "SPXW");
+        assert!(weekly.put.is_some());
+
+        // Without the underlying's own root the collision is an error.
+        let Err(Error::MarketDataParseError(message)) =
+            OptionChain::from_quotes(quotes[1..].iter().cloned().chain([Quote {
+                symbol: "SPXW240119C04700001".to_owned(),
+                ..quotes[1].clone()
+            }]))
+        else {
+            panic!("colliding contracts must not be dropped");
+        };
+        assert!(message.contains("SPXW240119C04700001"), "{message}");
+    }
+
+    #[test]
+    fn test_rejects_quotes_missing_option_fields() {
+        let stock: Quote = serde_json::from_value(serde_json::json!({
+            "symbol": "SPY", "description": "SPDR", "exch": "P", "type": "etf"
+        }))
+        .unwrap();
+        assert!(matches!(
+            OptionChain::from_quotes([stock]),
+            Err(Error::MarketDataParseError(_))
+        ));
+        let empty = OptionChain::try_from(GetOptionChainsResponse { options: None }).unwrap();
+        assert!(empty.is_empty());
+        assert_eq!(empty.expiration(), None);
+    }
+}
@@ -0,0 +1,469 @@
+//! Option chains across expirations.
+//!
+//! [`OptionSurface`] fetches every listed expiration of an underlying, or
+//! those within a date range, and keeps one [`OptionChain`] per expiration:
+//! an expiry x strike grid for volatility surfaces and term-structure work.
+//!
+//! The chains are requested concurrently, at most `concurrency` at a time.
+//! Requests share Tradier's rate-limit headers: once `X-Ratelimit-Available`
+//! reaches zero, or a request is rejected with `429 Too Many Requests`, every
+//! in-flight fetch holds off until `X-Ratelimit-Expiry`. Rejected requests
+//! are retried up to `max_retries` times.
+//!
+//! The module needs Tokio's timer, so it is only built with the
+//! `streaming` or `wssession` feature (both on by default). REST-only
+//! builds enable one of them to use it.
+//!
+//! When an expiration lists several option roots at the same strikes (an
+//! SPX monthly next to the SPXW weekly), each chain keeps the root named
+//! like the underlying, as [`OptionChain`] does.
+
+use std::collections::BTreeMap;
+use std::sync::{Mutex, PoisonError};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use chrono::NaiveDate;
+use futures_util::stream::{self, StreamExt, TryStreamExt};
+use reqwest::StatusCode;
+use reqwest::header::HeaderMap;
+use tokio::time::Instant;
+use tracing::warn;
+
+use super::api::non_blocking::MarketData;
+use super::option_chain::{OptionChain, StrikeRow};
+use super::types::{GetOptionChainsResponse, GetOptionExpirationsResponse, Greeks};
+use crate::client::non_blocking::TradierRestClient;
+use crate::common::Symbol;
+use crate::{Error, Result};
+
+/// Longest pause taken on account of the rate-limit headers.
+const MAX_RATE_LIMIT_WAIT: Duration = Duration::from_secs(60);
+
+/// Delay before the first retry of a `429` that carries no
+/// `X-Ratelimit-Expiry`; doubled on each further retry.
+const INITIAL_RETRY_DELAY: Duration = Duration::from_secs(1);
+
+/// Option chains of one underlying, keyed by expiration.
+///
+/// # Example
+/// ```no_run
+/// use tradier::non_blocking::Client;
+/// use tradier::types::{Greeks, OptionSurface, OptionType};
+/// use tradier::Config;
+///
+/// # async fn run() -> tradier::Result<()> {
+/// let client = Client::new(Config::new());
+/// let surface = OptionSurface::builder()
+///     .client(&client)
+///     .symbol(&"SPY".parse()?)
+///     .to(chrono::NaiveDate::from_ymd_opt(2024, 6, 30).unwrap())
+///     .greeks(Greeks::new(true))
+///     .fetch()
+///     .await?;
+///
+/// for (expiration, chain) in surface.chains() {
+///     let iv = chain
+///         .nearest_strike(471.3)
+///         .and_then(|row| row.contract(OptionType::Call))
+///         .and_then(|call| call.greeks())
+///         .and_then(|greeks| greeks.mid_iv);
+///     println!("{expiration}: ATM call IV {iv:?}");
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct OptionSurface {
+    underlying: Symbol,
+    chains: BTreeMap<NaiveDate, OptionChain>,
+}
+
+#[bon::bon]
+impl OptionSurface {
+    /// Fetches the chains of `symbol` for every expiration in range.
+    ///
+    /// # Arguments
+    /// - `client`: REST client used for every request.
+    /// - `symbol`: Underlying symbol.
+    /// - `from`, `to`: Inclusive expiration range. Both default to open.
+    /// - `greeks`: Whether chains include greeks and implied volatility.
+    /// - `concurrency`: Chains requested at once. Defaults to 4.
+    /// - `max_retries`: Retries per chain after a `429`. Defaults to 3.
+    ///
+    /// # Errors
+    /// Fails on the first chain that cannot be fetched: a transport error,
+    /// an error status (including a `429` past `max_retries`), or a quote
+    /// that cannot be grouped into an [`OptionChain`].
+    #[builder(builder_type(vis = "pub"), finish_fn = fetch)]
+    async fn new(
+        client: &TradierRestClient,
+        symbol: &Symbol,
+        from: Option<NaiveDate>,
+        to: Option<NaiveDate>,
+        greeks: Option<Greeks>,
+        #[builder(default = 4)] concurrency: usize,
+        #[builder(default = 3)] max_retries: u32,
+    ) -> Result<Self> {
+        let expirations = client.get_option_expirations(symbol, None, None).await?;
+        let dates = expiration_dates(&expirations)?
+            .into_iter()
+            .filter(|date| from.is_none_or(|from| *date >= from))
+            .filter(|date| to.is_none_or(|to| *date <= to));
+
+        let pacer = Pacer::default();
+        let chains = stream::iter(dates)
+            .map(|expiration| {
+                let pacer = &pacer;
+                async move {
+                    let chain =
+                        fetch_chain(client, symbol, expiration, greeks, max_retries, pacer).await?;
+                    Ok::<_, Error>((expiration, chain))
+                }
+            })
+            .buffer_unordered(concurrency.max(1))
+            .try_collect()
+            .await?;
+        Ok(Self {
+            underlying: symbol.clone(),
+            chains,
+        })
+    }
+
+    /// Builds a surface from chains already at hand.
+    pub fn from_chains(
+        underlying: Symbol,
+        chains: impl IntoIterator<Item = (NaiveDate, OptionChain)>,
+    ) -> Self {
+        Self {
+            underlying,
+            chains: chains.into_iter().collect(),
+        }
+    }
+
+    /// Returns the underlying symbol.
+    pub fn underlying(&self) -> &Symbol {
+        &self.underlying
+    }
+
+    /// Returns the number of expirations.
+    pub fn len(&self) -> usize {
+        self.chains.len()
+    }
+
+    /// Returns `true` if the surface has no expirations.
+    pub fn is_empty(&self) -> bool {
+        self.chains.is_empty()
+    }
+
+    /// Returns the expirations, in ascending order.
+    pub fn expirations(&self) -> impl Iterator<Item = NaiveDate> + '_ {
+        self.chains.keys().copied()
+    }
+
+    /// Returns each expiration with its chain, in ascending order.
+    pub fn chains(&self) -> impl Iterator<Item = (NaiveDate, &OptionChain)> {
+        self.chains.iter().map(|(date, chain)| (*date, chain))
+    }
+
+    /// Returns the chain expiring on `expiration`.
+    pub fn chain(&self, expiration: NaiveDate) -> Option<&OptionChain> {
+        self.chains.get(&expiration)
+    }
+
+    /// Returns every strike listed in any expiration, ascending and
+    /// without duplicates.
+    pub fn strikes(&self) -> Vec<f64> {
+        let mut strikes: Vec<f64> = self
+            .chains
+            .values()
+            .flat_map(|chain| chain.strikes().iter().map(|row| row.strike))
+            .collect();
+        strikes.sort_by(f64::total_cmp);
+        strikes.dedup();
+        strikes
+    }
+
+    /// Returns the call/put pair at `strike` for `expiration`.
+    pub fn get(&self, expiration: NaiveDate, strike: f64) -> Option<&StrikeRow> {
+        self.chain(expiration)?.strike(strike)
+    }
+}
+
+/// Parses the dates of an expirations response, whichever shape it came in.
+fn expiration_dates(response: &GetOptionExpirationsResponse) -> Result<Vec<NaiveDate>> {
+    let payload = &response.expirations;
+    let dates: Vec<&str> = match (&payload.date, &payload.expiration) {
+        (Some(dates), _) => dates.as_slice().iter().map(String::as_str).collect(),
+        (None, Some(entries)) => entries.as_slice().iter().map(|e| e.date.as_str()).collect(),
+        (None, None) => Vec::new(),
+    };
+    dates
+        .into_iter()
+        .map(|date| {
+            NaiveDate::parse_from_str(date, "%Y-%m-%d").map_err(|e| {
+                Error::MarketDataParseError(format!("invalid expiration date {date:?}: {e}"))
+            })
+        })
+        .collect()
+}
+
+/// Requests one chain, waiting out and retrying rate-limit rejections.
+async fn fetch_chain(
+    client: &TradierRestClient,
+    symbol: &Symbol,
+    expiration: NaiveDate,
+    greeks: Option<Greeks>,
+    max_retries: u32,
+    pacer: &Pacer,
+) -> Result<OptionChain> {
+    let mut retries = 0;
+    loop {
+        pacer.ready().await;
+        let response = client
+            .option_chains_response(symbol, &expiration, greeks)
+            .await?;
+        let reset = rate_limit_reset(response.headers());
+
+        if response.status() == StatusCode::TOO_MANY_REQUESTS && retries < max_retries {
+            retries += 1;
+            let delay = reset.unwrap_or(INITIAL_RETRY_DELAY * 2u32.pow(retries - 1));
+            let delay = delay.min(MAX_RATE_LIMIT_WAIT);
+            warn!(%symbol, %expiration, retries, ?delay, "option chain request rate limited, retrying");
+            pacer.pause_for(delay);
+            continue;
+        }
+        if rate_limit_available(response.headers()) == Some(0)
+            && let Some(reset) = reset
+        {
+            pacer.pause_for(reset);
+        }
+
+        let chain = response
+            .error_for_status()?
+            .json::<GetOptionChainsResponse>()
+            .await?;
+        return OptionChain::try_from(chain);
+    }
+}
+
+/// Reads `X-Ratelimit-Available`.
+fn rate_limit_available(headers: &HeaderMap) -> Option<u64> {
+    headers
+        .get("x-ratelimit-available")?
+        .to_str()
+        .ok()?
+        .parse()
+        .ok()
+}
+
+/// Returns the time left until `X-Ratelimit-Expiry` (epoch milliseconds),
+/// capped at [`MAX_RATE_LIMIT_WAIT`].
+fn rate_limit_reset(headers: &HeaderMap) -> Option<Duration> {
+    let expiry: u64 = headers
+        .get("x-ratelimit-expiry")?
+        .to_str()
+        .ok()?
+        .parse()
+        .ok()?;
+    let now = u64::try_from(
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .ok()?
+            .as_millis(),
+    )
+    .ok()?;
+    Some(Duration::from_millis(expiry.saturating_sub(now)).min(MAX_RATE_LIMIT_WAIT))
+}
+
+/// Shared hold-off point for the concurrent chain requests.
+#[derive(Debug, Default)]
+struct Pacer {
+    resume_at: Mutex<Option<Instant>>,
+}
+
+impl Pacer {
+    /// Waits until any pause set by another request has passed.
+    async fn ready(&self) {
+        let resume_at = *self
+            .resume_at
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        if let Some(resume_at) = resume_at {
+            tokio::time::sleep_until(resume_at).await;
+        }
+    }
+
+    /// Holds off further requests for `delay`, unless a later pause is
+    /// already set.
+    fn pause_for(&self, delay: Duration) {
+        let until = Instant::now() + delay;
+        let mut resume_at = self
+            .resume_at
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        *resume_at = Some(resume_at.map_or(until, |current| current.max(until)));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mockito::{Matcher, Server};
+
+    use super::*;
+    use crate::market_data::test_support::option_quote_json;
+    use crate::utils::tests::create_test_config;
+
+    fn chain_body(expiration: &str, strikes: &[f64]) -> String {
+        let quotes: Vec<_> = strikes
+            .iter()
+            .map(|strike| {
+                option_quote_json(
+                    &format!("SPY-{expiration}-C{strike}"),
+                    "call",
+                    *strike,
+                    expiration,
+                )
+            })
+            .collect();
+        serde_json::json!({ "options": { "option": quotes } }).to_string()
+    }
+
+    fn date(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_fetches_chains_within_range() {
+        let mut server = Server::new_async().await;
+        let _expirations = server
+            .mock("GET", "/v1/markets/options/expirations")
+            .match_query(Matcher::Any)
+            .with_status(200)
+            .with_body(
+                r#"{"expirations":{"date":["2024-01-12","2024-01-19","2024-02-16","2024-03-15"]}}"#,
+            )
+            .create_async()
+            .await;
+        let mut chains = Vec::new();
+        for (expiration, strikes) in [
+            ("2024-01-19", &[470.0, 475.0][..]),
+            ("2024-02-16", &[465.0, 470.0][..]),
+        ] {
+            let mock = server
+                .mock("GET", "/v1/markets/options/chains")
+                .match_query(Matcher::UrlEncoded("expiration".into(), expiration.into()))
+                .with_status(200)
+                .with_body(chain_body(expiration, strikes))
+                .expect(1)
+                .create_async()
+                .await;
+            chains.push(mock);
+        }
+
+        let client =
+            TradierRestClient::new(create_test_config().server_url(&server.url()).finish());
+        let surface = OptionSurface::builder()
+            .client(&client)
+            .symbol(&"SPY".parse().unwrap())
+            .from(date("2024-01-13"))
+            .to(date("2024-03-01"))
+            .concurrency(2)
+            .fetch()
+            .await
+            .unwrap();
+
+        for mock in &chains {
+            mock.assert_async().await;
+        }
+        assert_eq!(surface.underlying().as_str(), "SPY");
+        let expirations: Vec<_> = surface.expirations().collect();
+        assert_eq!(expirations, [date("2024-01-19"), date("2024-02-16")]);
+        assert_eq!(surface.strikes(), [465.0, 470.0, 475.0]);
+        assert!(
+            surface
+                .get(date("2024-02-16"), 470.0)
+                .unwrap()
+                .call
+                .is_some()
+        );
+        assert!(surface.get(date("2024-02-16"), 475.0).is_none());
+        assert!(surface.chain(date("2024-01-12")).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_retries_rate_limited_chain_request() {
+        let mut server = Server::new_async().await;
+        let _expirations = server
+            .mock("GET", "/v1/markets/options/expirations")
+            .match_query(Matcher::Any)
+            .with_status(200)
+            .with_body(r#"{"expirations":{"date":"2024-01-19"}}"#)
+            .create_async()
+            .await;
+        let expiry = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_millis()
+            + 50;
+        let limited = server
+            .mock("GET", "/v1/markets/options/chains")
+            .match_query(Matcher::Any)
+            .with_status(429)
+            .with_header("x-ratelimit-available", "0")
+            .with_header("x-ratelimit-expiry", &expiry.to_string())
+            .expect(1)
+            .create_async()
+            .await;
+        let ok = server
+            .mock("GET", "/v1/markets/options/chains")
+            .match_query(Matcher::Any)
+            .with_status(200)
+            .with_body(chain_body("2024-01-19", &[470.0]))
+            .expect(1)
+            .create_async()
+            .await;
+
+        let client =
+            TradierRestClient::new(create_test_config().server_url(&server.url()).finish());
+        let surface = OptionSurface::builder()
+            .client(&client)
+            .symbol(&"SPY".parse().unwrap())
+            .fetch()
+            .await
+            .unwrap();
+
+        limited.assert_async().await;
+        ok.assert_async().await;
+        assert_eq!(surface.len(), 1);
+        assert_eq!(surface.strikes(), [470.0]);
+    }
+
+    #[tokio::test]
+    async fn test_gives_up_after_max_retries() {
+        let mut server = Server::new_async().await;
+        let _expirations = server
+            .mock("GET", "/v1/markets/options/expirations")
+            .match_query(Matcher::Any)
+            .with_status(200)
+            .with_body(r#"{"expirations":{"expiration":[{"date":"2024-01-19"}]}}"#)
+            .create_async()
+            .await;
+        let _limited = server
+            .mock("GET", "/v1/markets/options/chains")
+            .match_query(Matcher::Any)
+            .with_status(429)
+            .with_header("x-ratelimit-expiry", "0")
+            .create_async()
+            .await;
+
+        let client =
+            TradierRestClient::new(create_test_config().server_url(&server.url()).finish());
+        let result = OptionSurface::builder()
+            .client(&client)
+            .symbol(&"SPY".parse().unwrap())
+            .max_retries(1)
+            .fetch()
+            .await;
+        assert!(matches!(result, Err(Error::NetworkError(_))));
+    }
+}
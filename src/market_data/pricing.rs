@@ -0,0 +1,549 @@
+//! Local Black-Scholes pricing, greeks and implied volatility.
+//!
+//! Tradier's [`GreeksData`](super::types::GreeksData) is refreshed about once
+//! an hour and is absent for some contracts. [`BlackScholes`] prices
+//! European options from the live quote instead: theoretical value, the
+//! first-order greeks plus gamma, and implied volatility solved from the
+//! bid, ask or mid.
+//!
+//! Greeks follow Tradier's units so the two can be compared side by side:
+//! theta is per calendar day, vega and rho per percentage point.
+//!
+//! Any quote implementing [`OptionQuote`] can be priced directly: REST
+//! [`Quote`]s, chain [`OptionContract`]s and, with `streaming` or
+//! `wssession`, streaming [`crate::wssession::Quote`]s.
+
+use std::f64::consts::PI;
+
+use chrono::{DateTime, Datelike, NaiveDate, Utc, Weekday};
+
+use super::option_chain::OptionContract;
+use super::types::Quote;
+use crate::common::{OptionSymbol, OptionType};
+use crate::{Error, Result};
+
+/// Seconds in the 365-day year used for time to expiry.
+const SECONDS_PER_YEAR: f64 = 365.0 * 24.0 * 60.0 * 60.0;
+
+/// Volatility bracket searched by [`BlackScholes::implied_volatility`].
+const MIN_VOLATILITY: f64 = 1e-6;
+const MAX_VOLATILITY: f64 = 10.0;
+
+/// Price tolerance at which the implied volatility solve stops.
+const PRICE_TOLERANCE: f64 = 1e-10;
+const MAX_ITERATIONS: usize = 100;
+
+/// Which side of the market implied volatility is solved from.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum PriceSource {
+    Bid,
+    Ask,
+    /// Midpoint of bid and ask.
+    #[default]
+    Mid,
+}
+
+/// Theoretical value and greeks of one option.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Valuation {
+    /// Annualized volatility the option was priced at.
+    pub volatility: f64,
+    /// Theoretical value per share.
+    pub price: f64,
+    pub delta: f64,
+    pub gamma: f64,
+    /// Value change per calendar day.
+    pub theta: f64,
+    /// Value change per percentage point of volatility.
+    pub vega: f64,
+    /// Value change per percentage point of the risk-free rate.
+    pub rho: f64,
+}
+
+/// A quote that identifies an option contract and carries its market.
+pub trait OptionQuote {
+    /// Returns the contract's type, strike and expiration.
+    ///
+    /// # Errors
+    /// Returns [`Error::MarketDataParseError`] if the quote is not an
+    /// option quote.
+    fn contract(&self) -> Result<OptionSymbol>;
+
+    /// Returns the bid, if any.
+    fn bid(&self) -> Option<f64>;
+
+    /// Returns the ask, if any.
+    fn ask(&self) -> Option<f64>;
+
+    /// Returns the quoted price on `source`, ignoring non-positive prices.
+    fn market_price(&self, source: PriceSource) -> Option<f64> {
+        let positive = |price: Option<f64>| price.filter(|p| *p > 0.0);
+        match source {
+            PriceSource::Bid => positive(self.bid()),
+            PriceSource::Ask => positive(self.ask()),
+            PriceSource::Mid => Some((positive(self.bid())? + positive(self.ask())?) / 2.0),
+        }
+    }
+}
+
+impl OptionQuote for Quote {
+    fn contract(&self) -> Result<OptionSymbol> {
+        self.symbol.parse()
+    }
+
+    fn bid(&self) -> Option<f64> {
+        self.bid
+    }
+
+    fn ask(&self) -> Option<f64> {
+        self.ask
+    }
+}
+
+impl OptionQuote for OptionContract {
+    fn contract(&self) -> Result<OptionSymbol> {
+        self.quote().contract()
+    }
+
+    fn bid(&self) -> Option<f64> {
+        self.quote().bid
+    }
+
+    fn ask(&self) -> Option<f64> {
+        self.quote().ask
+    }
+}
+
+#[cfg(any(feature = "streaming", feature = "wssession"))]
+impl OptionQuote for crate::wssession::Quote {
+    fn contract(&self) -> Result<OptionSymbol> {
+        self.symbol.parse()
+    }
+
+    fn bid(&self) -> Option<f64> {
+        Some(self.bid)
+    }
+
+    fn ask(&self) -> Option<f64> {
+        Some(self.ask)
+    }
+}
+
+/// Black-Scholes-Merton model for European options on an underlying with a
+/// continuous dividend yield.
+///
+/// # Example
+/// ```
+/// use tradier::types::{BlackScholes, OptionType};
+///
+/// let model = BlackScholes::builder().rate(0.05).build();
+/// let call = model.valuation(OptionType::Call, 100.0, 100.0, 1.0, 0.2);
+/// assert!((call.price - 10.4506).abs() < 1e-4);
+///
+/// let iv = model
+///     .implied_volatility(OptionType::Call, 100.0, 100.0, 1.0, call.price)
+///     .unwrap();
+/// assert!((iv - 0.2).abs() < 1e-8);
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct BlackScholes {
+    rate: f64,
+    dividend_yield: f64,
+}
+
+#[bon::bon]
+impl BlackScholes {
+    /// Constructs a new `BlackScholes` model.
+    ///
+    /// # Arguments
+    /// - `rate`: Continuously compounded risk-free rate, e.g. `0.05`.
+    /// - `dividend_yield`: Continuous dividend yield. Defaults to `0.0`.
+    #[builder(builder_type(vis = "pub"))]
+    fn new(rate: f64, #[builder(default)] dividend_yield: f64) -> Self {
+        BlackScholes {
+            rate,
+            dividend_yield,
+        }
+    }
+
+    /// Returns the risk-free rate.
+    pub fn rate(&self) -> f64 {
+        self.rate
+    }
+
+    /// Returns the dividend yield.
+    pub fn dividend_yield(&self) -> f64 {
+        self.dividend_yield
+    }
+
+    /// Returns the theoretical value of an option.
+    ///
+    /// `years` is the time to expiry and `volatility` the annualized
+    /// volatility, e.g. `0.2`.
+    pub fn price(
+        &self,
+        option_type: OptionType,
+        spot: f64,
+        strike: f64,
+        years: f64,
+        volatility: f64,
+    ) -> f64 {
+        self.valuation(option_type, spot, strike, years, volatility)
+            .price
+    }
+
+    /// Returns the theoretical value and greeks of an option.
+    ///
+    /// At or past expiry, or at zero volatility, the option is worth its
+    /// discounted intrinsic value and only delta is non-zero.
+    pub fn valuation(
+        &self,
+        option_type: OptionType,
+        spot: f64,
+        strike: f64,
+        years: f64,
+        volatility: f64,
+    ) -> Valuation {
+        let years = years.max(0.0);
+        let dividend_discount = (-self.dividend_yield * years).exp();
+        let rate_discount = (-self.rate * years).exp();
+        let forward = spot * dividend_discount;
+        let present_strike = strike * rate_discount;
+
+        if years == 0.0 || volatility <= 0.0 {
+            let (price, delta) = match option_type {
+                OptionType::Call if forward > present_strike => {
+                    (forward - present_strike, dividend_discount)
+                }
+                OptionType::Put if forward < present_strike => {
+                    (present_strike - forward, -dividend_discount)
+                }
+                _ => (0.0, 0.0),
+            };
+            return Valuation {
+                volatility: volatility.max(0.0),
+                price,
+                delta,
+                gamma: 0.0,
+                theta: 0.0,
+                vega: 0.0,
+                rho: 0.0,
+            };
+        }
+
+        let sqrt_years = years.sqrt();
+        let d1 = ((spot / strike).ln()
+            + (self.rate - self.dividend_yield + volatility * volatility / 2.0) * years)
+            / (volatility * sqrt_years);
+        let d2 = d1 - volatility * sqrt_years;
+        let density = norm_pdf(d1);
+        let decay = -forward * density * volatility / (2.0 * sqrt_years);
+
+        let (price, delta, theta, rho) = match option_type {
+            OptionType::Call => (
+                forward * norm_cdf(d1) - present_strike * norm_cdf(d2),
+                dividend_discount * norm_cdf(d1),
+                decay - self.rate * present_strike * norm_cdf(d2)
+                    + self.dividend_yield * forward * norm_cdf(d1),
+                years * present_strike * norm_cdf(d2),
+            ),
+            OptionType::Put => (
+                present_strike * norm_cdf(-d2) - forward * norm_cdf(-d1),
+                -dividend_discount * norm_cdf(-d1),
+                decay + self.rate * present_strike * norm_cdf(-d2)
+                    - self.dividend_yield * forward * norm_cdf(-d1),
+                -years * present_strike * norm_cdf(-d2),
+            ),
+        };
+        Valuation {
+            volatility,
+            price,
+            delta,
+            gamma: dividend_discount * density / (spot * volatility * sqrt_years),
+            theta: theta / 365.0,
+            vega: forward * density * sqrt_years / 100.0,
+            rho: rho / 100.0,
+        }
+    }
+
+    /// Solves the volatility at which the model reproduces `price`.
+    ///
+    /// # Errors
+    /// Returns [`Error::PricingError`] if the option has expired, or if
+    /// `price` lies outside the no-arbitrage bounds so no volatility
+    /// reproduces it.
+    pub fn implied_volatility(
+        &self,
+        option_type: OptionType,
+        spot: f64,
+        strike: f64,
+        years: f64,
+        price: f64,
+    ) -> Result<f64> {
+        if years <= 0.0 {
+            return Err(Error::PricingError(
+                "cannot solve implied volatility at or past expiry".to_owned(),
+            ));
+        }
+        let value = |volatility| self.price(option_type, spot, strike, years, volatility);
+        let (mut low, mut high) = (MIN_VOLATILITY, MAX_VOLATILITY);
+        if !(value(low) - PRICE_TOLERANCE..=value(high) + PRICE_TOLERANCE).contains(&price) {
+            return Err(Error::PricingError(format!(
+                "price {price} is outside the no-arbitrage bounds for {option_type} \
+                 strike {strike} on spot {spot}"
+            )));
+        }
+
+        // Newton steps, falling back to bisection whenever a step leaves the
+        // bracket; the price is increasing in volatility.
+        let mut volatility = 0.3;
+        for _ in 0..MAX_ITERATIONS {
+            let valuation = self.valuation(option_type, spot, strike, years, volatility);
+            let diff = valuation.price - price;
+            if diff.abs() < PRICE_TOLERANCE {
+                break;
+            }
+            if diff > 0.0 {
+                high = volatility;
+            } else {
+                low = volatility;
+            }
+            let step = volatility - diff / (valuation.vega * 100.0);
+            volatility = if step > low && step < high {
+                step
+            } else {
+                (low + high) / 2.0
+            };
+        }
+        Ok(volatility)
+    }
+
+    /// Prices an option quote: solves its implied volatility from the
+    /// `source` side of the market, then computes its greeks.
+    ///
+    /// # Errors
+    /// - [`Error::MarketDataParseError`] if the quote is not an option.
+    /// - [`Error::PricingError`] if the quote has no positive price on
+    ///   `source`, or no volatility reproduces it.
+    pub fn quote_valuation(
+        &self,
+        quote: &impl OptionQuote,
+        spot: f64,
+        as_of: DateTime<Utc>,
+        source: PriceSource,
+    ) -> Result<Valuation> {
+        let contract = quote.contract()?;
+        let price = quote.market_price(source).ok_or_else(|| {
+            Error::PricingError(format!("{contract} has no {source:?} price to solve from"))
+        })?;
+        let years = years_to_expiry(as_of, contract.expiration());
+        let strike = contract.strike();
+        let volatility =
+            self.implied_volatility(contract.option_type(), spot, strike, years, price)?;
+        Ok(self.valuation(contract.option_type(), spot, strike, years, volatility))
+    }
+}
+
+/// Returns the time from `as_of` to the close on `expiration`, in years.
+///
+/// The close is 16:00 New York time: 20:00 UTC while US daylight saving
+/// time is in effect (second Sunday of March to first Sunday of November)
+/// and 21:00 UTC otherwise. Never negative.
+pub fn years_to_expiry(as_of: DateTime<Utc>, expiration: NaiveDate) -> f64 {
+    let seconds = (new_york_close(expiration) - as_of).num_seconds().max(0);
+    seconds as f64 / SECONDS_PER_YEAR
+}
+
+/// Returns 16:00 New York time on `date`, in UTC, under the US
+/// daylight-saving rule in force since 2007.
+fn new_york_close(date: NaiveDate) -> DateTime<Utc> {
+    let sunday =
+        |month, n| NaiveDate::from_weekday_of_month_opt(date.year(), month, Weekday::Sun, n);
+    let daylight_saving = match (sunday(3, 2), sunday(11, 1)) {
+        (Some(start), Some(end)) => start <= date && date < end,
+        _ => false,
+    };
+    let hour = if daylight_saving { 20 } else { 21 };
+    date.and_hms_opt(hour, 0, 0)
+        .expect("the close is a valid time")
+        .and_utc()
+}
+
+/// Standard normal density.
+fn norm_pdf(x: f64) -> f64 {
+    (-x * x / 2.0).exp() / (2.0 * PI).sqrt()
+}
+
+/// Standard normal distribution function, by Hart's double-precision
+/// rational approximation (as given by West, 2005).
+fn norm_cdf(x: f64) -> f64 {
+    let z = x.abs();
+    let tail = if z > 37.0 {
+        0.0
+    } else if z < 7.071_067_811_865_47 {
+        let numerator = [
+            3.526_249_659_989_11e-2,
+            0.700_383_064_443_688,
+            6.373_962_203_531_65,
+            33.912_866_078_383,
+            112.079_291_497_871,
+            221.213_596_169_931,
+            220.206_867_912_376,
+        ]
+        .into_iter()
+        .reduce(|acc, c| acc * z + c)
+        .unwrap_or_default();
+        let denominator = [
+            8.838_834_764_831_84e-2,
+            1.755_667_163_182_64,
+            16.064_177_579_207,
+            86.780_732_202_946_1,
+            296.564_248_779_674,
+            637.333_633_378_831,
+            793.826_512_519_948,
+            440.413_735_824_752,
+        ]
+        .into_iter()
+        .reduce(|acc, c| acc * z + c)
+        .unwrap_or_default();
+        (-z * z / 2.0).exp() * numerator / denominator
+    } else {
+        let fraction = [1.0, 2.0, 3.0, 4.0]
+            .into_iter()
+            .fold(z + 0.65, |acc, c| z + (5.0 - c) / acc);
+        (-z * z / 2.0).exp() / fraction / (2.0 * PI).sqrt()
+    };
+    if x > 0.0 { 1.0 - tail } else { tail }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::market_data::test_support::option_quote_json;
+
+    fn close(a: f64, b: f64, tolerance: f64) -> bool {
+        (a - b).abs() < tolerance
+    }
+
+    #[test]
+    fn test_matches_reference_values_and_parity() {
+        let model = BlackScholes::builder().rate(0.05).build();
+        let call = model.valuation(OptionType::Call, 100.0, 100.0, 1.0, 0.2);
+        let put = model.valuation(OptionType::Put, 100.0, 100.0, 1.0, 0.2);
+        assert!(close(call.price, 10.450_583_572_185_565, 1e-9));
+        assert!(close(put.price, 5.573_526_022_256_971, 1e-9));
+        assert!(close(call.delta, 0.636_830_651_175_619, 1e-9));
+        assert!(close(put.delta, call.delta - 1.0, 1e-12));
+        assert!(close(call.gamma, put.gamma, 1e-12));
+        assert!(close(call.vega, 0.375_240_346_916_938, 1e-9));
+        assert!(close(call.theta, -6.414_027_546_438_197 / 365.0, 1e-9));
+        assert!(close(call.rho, 0.532_324_815_453_763, 1e-9));
+
+        let model = BlackScholes::builder()
+            .rate(0.03)
+            .dividend_yield(0.02)
+            .build();
+        let call = model.price(OptionType::Call, 105.0, 100.0, 0.5, 0.3);
+        let put = model.price(OptionType::Put, 105.0, 100.0, 0.5, 0.3);
+        let parity = 105.0 * (-0.02f64 * 0.5).exp() - 100.0 * (-0.03f64 * 0.5).exp();
+        assert!(close(call - put, parity, 1e-9));
+        assert!(close(norm_cdf(0.0), 0.5, 1e-15));
+        assert!(close(norm_cdf(-8.0), 6.220_960_574_271_78e-16, 1e-20));
+    }
+
+    #[test]
+    fn test_solves_implied_volatility() {
+        let model = BlackScholes::builder()
+            .rate(0.04)
+            .dividend_yield(0.01)
+            .build();
+        for (option_type, strike, volatility) in [
+            (OptionType::Call, 80.0, 0.15),
+            (OptionType::Call, 120.0, 0.65),
+            (OptionType::Put, 95.0, 0.05),
+            (OptionType::Put, 150.0, 1.8),
+        ] {
+            let price = model.price(option_type, 100.0, strike, 0.25, volatility);
+            let iv = model
+                .implied_volatility(option_type, 100.0, strike, 0.25, price)
+                .unwrap();
+            assert!(close(iv, volatility, 1e-6), "{option_type} {strike}: {iv}");
+        }
+        assert!(matches!(
+            model.implied_volatility(OptionType::Call, 100.0, 80.0, 0.25, 1.0),
+            Err(Error::PricingError(_))
+        ));
+        assert!(matches!(
+            model.implied_volatility(OptionType::Put, 100.0, 80.0, 0.0, 1.0),
+            Err(Error::PricingError(_))
+        ));
+    }
+
+    #[test]
+    fn test_years_to_expiry_follows_daylight_saving() {
+        let at = |y, m, d, h| {
+            NaiveDate::from_ymd_opt(y, m, d)
+                .unwrap()
+                .and_hms_opt(h, 0, 0)
+                .unwrap()
+                .and_utc()
+        };
+        let day = |y, m, d| NaiveDate::from_ymd_opt(y, m, d).unwrap();
+        let hour = 3600.0 / SECONDS_PER_YEAR;
+        // Summer: the close is 20:00 UTC.
+        assert_eq!(years_to_expiry(at(2024, 7, 19, 20), day(2024, 7, 19)), 0.0);
+        assert!(close(
+            years_to_expiry(at(2024, 7, 19, 18), day(2024, 7, 19)),
+            2.0 * hour,
+            1e-15
+        ));
+        // Around the switches: 2024-03-10 and 2024-11-03.
+        assert!(close(
+            years_to_expiry(at(2024, 3, 8, 20), day(2024, 3, 8)),
+            hour,
+            1e-15
+        ));
+        assert_eq!(years_to_expiry(at(2024, 3, 11, 20), day(2024, 3, 11)), 0.0);
+        assert_eq!(years_to_expiry(at(2024, 11, 1, 20), day(2024, 11, 1)), 0.0);
+        assert!(close(
+            years_to_expiry(at(2024, 11, 4, 20), day(2024, 11, 4)),
+            hour,
+            1e-15
+        ));
+    }
+
+    #[test]
+    fn test_prices_quotes() {
+        let mut quote = option_quote_json("SPY240119C00470000", "call", 470.0, "2024-01-19");
+        quote["bid"] = 4.9.into();
+        quote["ask"] = 5.1.into();
+        let quote: Quote = serde_json::from_value(quote).unwrap();
+        let model = BlackScholes::builder().rate(0.05).build();
+        let as_of = NaiveDate::from_ymd_opt(2024, 1, 5)
+            .unwrap()
+            .and_hms_opt(21, 0, 0)
+            .unwrap()
+            .and_utc();
+        assert!(close(
+            years_to_expiry(as_of, NaiveDate::from_ymd_opt(2024, 1, 19).unwrap()),
+            14.0 / 365.0,
+            1e-12
+        ));
+
+        let valuation = model
+            .quote_valuation(&quote, 468.0, as_of, PriceSource::Mid)
+            .unwrap();
+        assert!(close(valuation.price, 5.0, 1e-8));
+        assert!(valuation.delta > 0.0 && valuation.delta < 0.5);
+        let bid = model
+            .quote_valuation(&quote, 468.0, as_of, PriceSource::Bid)
+            .unwrap();
+        assert!(bid.volatility < valuation.volatility);
+
+        let no_bid = Quote { bid: None, ..quote };
+        assert!(matches!(
+            model.quote_valuation(&no_bid, 468.0, as_of, PriceSource::Mid),
+            Err(Error::PricingError(_))
+        ));
+    }
+}
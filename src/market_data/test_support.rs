@@ -9,6 +9,28 @@ use chrono::NaiveDate;
 use proptest::{prelude::Strategy, strategy::Just};
 use serde::Serialize;
 
+/// JSON for one option-chain [`crate::types::Quote`] on SPY, quoted 1.00 x
+/// 1.20; tests add or override fields such as `greeks` as needed.
+pub fn option_quote_json(
+    symbol: &str,
+    option_type: &str,
+    strike: f64,
+    expiration_date: &str,
+) -> serde_json::Value {
+    serde_json::json!({
+        "symbol": symbol,
+        "description": symbol,
+        "exch": "Z",
+        "type": "option",
+        "bid": 1.0,
+        "ask": 1.2,
+        "underlying": "SPY",
+        "strike": strike,
+        "expiration_date": expiration_date,
+        "option_type": option_type,
+    })
+}
+
 /// A [`NaiveDate`] strategy that yields only valid wall-clock dates.
 fn arb_naive_date() -> impl Strategy<Value = NaiveDate> {
     (1970i32..=2100, 1u32..=12, 1u32..=28)
@@ -201,6 +201,22 @@ pub struct Quote {
     pub greeks: Option<GreeksData>,
 }
 
+#[cfg(feature = "decimal")]
+crate::utils::decimal::decimal_accessors!(Quote {
+    option last => last_decimal,
+    option change => change_decimal,
+    option open => open_decimal,
+    option high => high_decimal,
+    option low => low_decimal,
+    option close => close_decimal,
+    option bid => bid_decimal,
+    option ask => ask_decimal,
+    option prevclose => prevclose_decimal,
+    option week_52_high => week_52_high_decimal,
+    option week_52_low => week_52_low_decimal,
+    option strike => strike_decimal,
+});
+
 /// Greeks block attached to an option quote when requested.
 #[derive(Clone, Debug, Deserialize, PartialEq)]
 pub struct GreeksData {
@@ -23,14 +23,19 @@
 //! - Per-line decode failures surface as
 //!   `Err(Error::StreamDecodeError(_, _))` items — consistent with the
 //!   WebSocket decoder. Decode failures do NOT abort the stream.
+//! - If [`crate::config::StreamingConfig::idle_timeout`] is set and no
+//!   chunk arrives within it, the stream yields
+//!   `Err(Error::StreamIdle(_))` and ends.
 
 use std::collections::VecDeque;
+use std::time::Duration;
 
 use futures_util::stream::{Stream, StreamExt};
 use serde::Serialize;
 use tracing::{debug, info, warn};
 
 use crate::client::non_blocking::TradierRestClient;
+use crate::utils::telemetry;
 use crate::wssession::account_events::AccountEvent;
 use crate::wssession::events::MarketEvent;
 use crate::{Error, Result};
@@ -75,7 +80,7 @@ pub async fn market_events(
     linebreak: Option<bool>,
     valid_only: Option<bool>,
     advanced_details: Option<bool>,
-) -> Result<impl Stream<Item = Result<MarketEvent>>> {
+) -> Result<impl Stream<Item = Result<MarketEvent>> + use<>> {
     let config = client.http_client_config();
     let base = &config.streaming.http_base_url;
     let url = format!("{base}/v1/markets/events");
@@ -115,9 +120,11 @@ pub async fn market_events(
 
     let response = error_for_non_success(response).await?;
     debug!("HTTP market stream accepted, decoding body");
+    telemetry::record_stream_connect(telemetry::TRANSPORT_HTTP);
 
     Ok(ndjson_event_stream(
         response.bytes_stream(),
+        config.streaming.idle_timeout(),
         MarketEvent::from_json,
     ))
 }
@@ -146,7 +153,7 @@ pub async fn account_events(
     session_id: &str,
     events: Option<&[crate::wssession::AccountSessionEvent]>,
     exclude_accounts: Option<&[String]>,
-) -> Result<impl Stream<Item = Result<AccountEvent>>> {
+) -> Result<impl Stream<Item = Result<AccountEvent>> + use<>> {
     let config = client.http_client_config();
     let base = &config.streaming.http_base_url;
     let url = format!("{base}/v1/accounts/events");
@@ -178,9 +185,11 @@ pub async fn account_events(
 
     let response = error_for_non_success(response).await?;
     debug!("HTTP account stream accepted, decoding body");
+    telemetry::record_stream_connect(telemetry::TRANSPORT_HTTP);
 
     Ok(ndjson_event_stream(
         response.bytes_stream(),
+        config.streaming.idle_timeout(),
         AccountEvent::from_json,
     ))
 }
@@ -221,6 +230,7 @@ struct NdjsonState<S, T, F> {
     pending: VecDeque<Result<T>>,
     decode: F,
     finished: bool,
+    idle_timeout: Option<Duration>,
 }
 
 /// Wraps the body byte stream from a `reqwest::Response` in a
@@ -231,8 +241,14 @@ struct NdjsonState<S, T, F> {
 /// - Decode errors become `Err(Error::StreamDecodeError)` and are
 ///   yielded without terminating the stream.
 /// - End-of-body flushes any remaining non-empty line.
+/// - A gap longer than `idle_timeout` between chunks becomes
+///   `Err(Error::StreamIdle)` and terminates the stream.
 #[inline]
-fn ndjson_event_stream<S, B, T, F>(body: S, decode: F) -> impl Stream<Item = Result<T>>
+fn ndjson_event_stream<S, B, T, F>(
+    body: S,
+    idle_timeout: Option<Duration>,
+    decode: F,
+) -> impl Stream<Item = Result<T>>
 where
     S: Stream<Item = reqwest::Result<B>> + Unpin,
     B: AsRef<[u8]>,
@@ -244,17 +260,34 @@ where
         pending: VecDeque::new(),
         decode,
         finished: false,
+        idle_timeout,
     };
 
     futures_util::stream::unfold(state, |mut state| async move {
         loop {
             if let Some(item) = state.pending.pop_front() {
+                telemetry::record_stream_item(telemetry::TRANSPORT_HTTP, &item);
                 return Some((item, state));
             }
             if state.finished {
                 return None;
             }
-            match state.body.next().await {
+            let next = match state.idle_timeout {
+                Some(timeout) => match tokio::time::timeout(timeout, state.body.next()).await {
+                    Ok(next) => next,
+                    Err(_) => {
+                        warn!(
+                            timeout_ms = timeout.as_millis() as u64,
+                            "HTTP stream idle, terminating stream"
+                        );
+                        state.finished = true;
+                        state.pending.push_back(Err(Error::StreamIdle(timeout)));
+                        continue;
+                    }
+                },
+                None => state.body.next().await,
+            };
+            match next {
                 Some(Ok(chunk)) => {
                     state.buffer.extend_from_slice(chunk.as_ref());
                     drain_complete_lines(&mut state.buffer, &mut state.pending, &state.decode);
@@ -356,6 +389,9 @@ mod tests {
                 ws_base_url: String::new(),
                 events_path: String::new(),
                 reconnect_interval: 5,
+                session_timeout: 300,
+                idle_timeout: 0,
+                ping_interval: 0,
             },
         }
     }
@@ -512,4 +548,20 @@ mod tests {
         let result = account_events(&client, "sid", None, None).await;
         assert!(matches!(result, Err(Error::NetworkError(_))));
     }
+
+    #[tokio::test]
+    async fn test_ndjson_idle_timeout_yields_stream_idle_and_ends() {
+        let body = futures_util::stream::iter([Ok::<_, reqwest::Error>("a\nb")])
+            .chain(futures_util::stream::pending());
+        let timeout = Duration::from_millis(50);
+        let items: Vec<Result<String>> =
+            ndjson_event_stream(body, Some(timeout), |line| Ok(line.to_owned()))
+                .collect()
+                .await;
+        // The complete line surfaces, the partial one is dropped when the
+        // feed goes idle.
+        assert_eq!(items.len(), 2, "unexpected items: {items:?}");
+        assert_eq!(items[0].as_deref().unwrap(), "a");
+        assert!(matches!(items[1], Err(Error::StreamIdle(d)) if d == timeout));
+    }
 }
@@ -0,0 +1,265 @@
+//! Transport-independent market event stream.
+//!
+//! [`MarketStream`] opens the same subscription over either the WebSocket
+//! API or the HTTP chunked-transfer API and yields the same
+//! [`MarketEvent`] items either way, so strategy code does not need to
+//! know which transport is in use.
+
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use futures_util::stream::{BoxStream, Stream, StreamExt};
+use tracing::{info, warn};
+
+use super::http_stream;
+use crate::Result;
+use crate::client::non_blocking::TradierRestClient;
+use crate::wssession::{MarketEvent, MarketSession, MarketSessionPayload};
+
+/// Transport used by a [`MarketStream`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MarketTransport {
+    /// Tradier's WebSocket streaming API.
+    WebSocket,
+    /// Tradier's HTTP chunked-transfer streaming API.
+    Http,
+    /// Try the WebSocket first and fall back to HTTP if the WebSocket
+    /// cannot be opened (for example when a firewall blocks the upgrade).
+    #[default]
+    Auto,
+}
+
+/// A stream of [`MarketEvent`] items over the transport picked with
+/// [`MarketTransport`].
+///
+/// Built with [`MarketStream::builder`]; `connect()` opens the stream.
+/// Items, decode errors and end-of-stream behave the same on both
+/// transports: decode failures are yielded as
+/// `Err(Error::StreamDecodeError(...))` without ending the stream, and
+/// transport failures end it.
+///
+/// # Example
+/// ```no_run
+/// use futures_util::StreamExt;
+/// use tradier::Config;
+/// use tradier::non_blocking::Client;
+/// use tradier::streaming::{MarketStream, MarketTransport};
+/// use tradier::wssession::{MarketSession, MarketSessionPayload};
+///
+/// # async fn run() -> tradier::Result<()> {
+/// let config = Config::new();
+/// let client = Client::new(config.clone());
+/// let session = MarketSession::new(&config).await?;
+/// let symbols = vec!["SPY".to_string()];
+/// let payload = MarketSessionPayload::recommended(&symbols, session.get_session_id());
+///
+/// let mut stream = MarketStream::builder()
+///     .session(&session)
+///     .client(&client)
+///     .payload(payload)
+///     .transport(MarketTransport::Auto)
+///     .connect()
+///     .await?;
+/// println!("streaming over {:?}", stream.transport());
+/// while let Some(event) = stream.next().await {
+///     println!("{:?}", event?);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub struct MarketStream {
+    transport: MarketTransport,
+    events: BoxStream<'static, Result<MarketEvent>>,
+}
+
+#[bon::bon]
+impl MarketStream {
+    /// Opens a market event stream.
+    ///
+    /// # Arguments
+    /// - `session`: The market session whose WebSocket URL is used.
+    /// - `client`: REST client used for the HTTP transport; its pooled
+    ///   `reqwest::Client` and configuration are reused.
+    /// - `payload`: Symbols, filters and session id to subscribe with.
+    /// - `transport`: Which transport to use. Defaults to
+    ///   [`MarketTransport::Auto`].
+    ///
+    /// # Errors
+    /// - With [`MarketTransport::WebSocket`], the errors of
+    ///   [`MarketSession::event_stream`].
+    /// - With [`MarketTransport::Http`], the errors of
+    ///   [`http_stream::market_events`].
+    /// - With [`MarketTransport::Auto`], the HTTP error if both transports
+    ///   fail.
+    #[builder(builder_type(vis = "pub"), finish_fn = connect)]
+    async fn new(
+        session: &MarketSession<'_>,
+        client: &TradierRestClient,
+        payload: MarketSessionPayload<'_>,
+        #[builder(default)] transport: MarketTransport,
+    ) -> Result<Self> {
+        match transport {
+            MarketTransport::WebSocket => Self::websocket(session, &payload).await,
+            MarketTransport::Http => Self::http(client, &payload).await,
+            MarketTransport::Auto => match Self::websocket(session, &payload).await {
+                Ok(stream) => Ok(stream),
+                Err(e) => {
+                    warn!(error = %e, "market websocket unavailable, falling back to HTTP stream");
+                    Self::http(client, &payload).await
+                }
+            },
+        }
+    }
+
+    async fn websocket(
+        session: &MarketSession<'_>,
+        payload: &MarketSessionPayload<'_>,
+    ) -> Result<Self> {
+        let events = session.event_stream(payload.clone().into_owned()).await?;
+        info!("market stream opened over websocket");
+        Ok(MarketStream {
+            transport: MarketTransport::WebSocket,
+            events: events.boxed(),
+        })
+    }
+
+    async fn http(client: &TradierRestClient, payload: &MarketSessionPayload<'_>) -> Result<Self> {
+        let events = http_stream::market_events(
+            client,
+            &payload.session_id,
+            &payload.symbols,
+            payload.filters.as_deref(),
+            payload.linebreak,
+            payload.valid_only,
+            payload.advanced_details,
+        )
+        .await?;
+        info!("market stream opened over HTTP");
+        Ok(MarketStream {
+            transport: MarketTransport::Http,
+            events: events.boxed(),
+        })
+    }
+
+    /// Returns the transport the stream is using; never
+    /// [`MarketTransport::Auto`].
+    pub fn transport(&self) -> MarketTransport {
+        self.transport
+    }
+}
+
+impl Stream for MarketStream {
+    type Item = Result<MarketEvent>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.events.poll_next_unpin(cx)
+    }
+}
+
+impl std::fmt::Debug for MarketStream {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MarketStream")
+            .field("transport", &self.transport)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::tests::{
+        ScriptedWsAction, create_test_config, free_tcp_port, scripted_websocket_server,
+    };
+    use crate::wssession::SessionManager;
+    use mockito::{Server, ServerGuard};
+
+    const QUOTE_FRAME: &str = r#"{"type":"quote","symbol":"C","bid":281.84,"bidsz":60,"bidexch":"M","biddate":"1","ask":281.85,"asksz":6,"askexch":"Z","askdate":"2"}"#;
+
+    /// Mocks the session endpoint (pointing the WebSocket at `ws_port`)
+    /// and the HTTP market stream on the same server.
+    async fn mock_server(ws_port: u16) -> ServerGuard {
+        let mut server = Server::new_async().await;
+        server
+            .mock("POST", "/v1/markets/events/session")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(format!(
+                r#"{{"stream":{{"url":"ws://127.0.0.1:{ws_port}/v1/markets/events","sessionid":"sid"}}}}"#
+            ))
+            .create_async()
+            .await;
+        server
+            .mock("GET", "/v1/markets/events")
+            .match_query(mockito::Matcher::UrlEncoded(
+                "sessionid".into(),
+                "sid".into(),
+            ))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(format!("{QUOTE_FRAME}\n"))
+            .create_async()
+            .await;
+        server
+    }
+
+    async fn collect_over(transport: MarketTransport, ws_up: bool) -> MarketTransport {
+        let port = free_tcp_port();
+        let server = mock_server(port).await;
+        if ws_up {
+            scripted_websocket_server(
+                ("127.0.0.1", port),
+                vec![
+                    ScriptedWsAction::SendText(QUOTE_FRAME),
+                    ScriptedWsAction::SendClose,
+                ],
+                |_| {},
+            )
+            .await;
+        }
+        let mut config = create_test_config().server_url(&server.url()).finish();
+        config.streaming.http_base_url = server.url();
+        let client = TradierRestClient::new(config.clone());
+        let manager = SessionManager::default();
+        let session = manager.market_session(&config).await.expect("session");
+        let symbols = ["C".to_string()];
+        let payload = MarketSessionPayload::recommended(&symbols, session.get_session_id());
+
+        let stream = MarketStream::builder()
+            .session(&session)
+            .client(&client)
+            .payload(payload)
+            .transport(transport)
+            .connect()
+            .await
+            .expect("connect");
+        let used = stream.transport();
+        let collected: Vec<Result<MarketEvent>> = stream.collect().await;
+        assert_eq!(collected.len(), 1, "unexpected items: {collected:?}");
+        assert!(matches!(collected[0], Ok(MarketEvent::Quote(_))));
+        used
+    }
+
+    #[tokio::test]
+    async fn test_both_transports_yield_the_same_events() {
+        assert_eq!(
+            collect_over(MarketTransport::WebSocket, true).await,
+            MarketTransport::WebSocket
+        );
+        assert_eq!(
+            collect_over(MarketTransport::Http, false).await,
+            MarketTransport::Http
+        );
+    }
+
+    #[tokio::test]
+    async fn test_auto_prefers_websocket_and_falls_back_to_http() {
+        assert_eq!(
+            collect_over(MarketTransport::Auto, true).await,
+            MarketTransport::WebSocket
+        );
+        assert_eq!(
+            collect_over(MarketTransport::Auto, false).await,
+            MarketTransport::Http
+        );
+    }
+}
@@ -8,9 +8,17 @@
 //!   `reqwest::Client` on a
 //!   [`crate::client::non_blocking::TradierRestClient`]. These are the
 //!   HTTP fallback when WebSockets are not reachable.
+//! - [`MarketStream`] opens one market subscription over either
+//!   transport (or tries the WebSocket first and falls back to HTTP)
+//!   and yields the same event stream either way.
 //!
 //! The REST endpoint that mints the session id itself lives under
 //! [`crate::wssession::session`] (it is shared between the WebSocket
 //! and HTTP streaming flavors).
 
 pub mod http_stream;
+#[cfg(feature = "wssession")]
+mod market_stream;
+
+#[cfg(feature = "wssession")]
+pub use market_stream::{MarketStream, MarketTransport};
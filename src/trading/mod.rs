@@ -1 +1,8 @@
+//! Order placement.
+//!
+//! [`types::MultilegOrderRequest`] is the body of a multileg order, and
+//! [`strategy::OptionStrategy`] assembles common option strategies from
+//! chains into one.
 
+pub mod strategy;
+pub mod types;
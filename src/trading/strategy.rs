@@ -0,0 +1,768 @@
+//! Multileg option strategies assembled from option chains.
+//!
+//! Each [`OptionStrategy`] constructor picks its legs from an
+//! [`OptionChain`], either at a given strike, nearest to a price, or
+//! nearest to a delta ([`StrikeSelector`]), with wings placed a fixed width
+//! away. A strategy reports its net debit from the quotes, its payoff at
+//! expiration (max profit, max loss, breakevens) and turns into a
+//! [`MultilegOrderRequest`] that opens it.
+//!
+//! Prices and payoffs are per share, in the same units as the quotes;
+//! multiply by the contract size for dollars per strategy.
+
+use std::fmt;
+
+use crate::common::{OptionSymbol, OptionType, Symbol};
+use crate::market_data::option_chain::{OptionChain, OptionContract};
+use crate::market_data::pricing::OptionQuote;
+use crate::trading::types::{LegRequest, MultilegOrderRequest};
+use crate::types::{OrderDuration, OrderSide, OrderType};
+use crate::{Error, Result};
+
+/// How a leg's strike is picked from a chain.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum StrikeSelector {
+    /// Exactly this strike.
+    Strike(f64),
+    /// The listed strike closest to this price.
+    NearestTo(f64),
+    /// The contract whose delta is closest to this value; use negative
+    /// deltas for puts.
+    Delta(f64),
+}
+
+/// Whether a leg is bought or sold.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum LegSide {
+    Buy,
+    Sell,
+}
+
+impl LegSide {
+    fn sign(self) -> f64 {
+        match self {
+            LegSide::Buy => 1.0,
+            LegSide::Sell => -1.0,
+        }
+    }
+
+    fn opposite(self) -> Self {
+        match self {
+            LegSide::Buy => LegSide::Sell,
+            LegSide::Sell => LegSide::Buy,
+        }
+    }
+
+    fn opening_side(self) -> OrderSide {
+        match self {
+            LegSide::Buy => OrderSide::BuyToOpen,
+            LegSide::Sell => OrderSide::SellToOpen,
+        }
+    }
+}
+
+/// Shape of an [`OptionStrategy`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum StrategyKind {
+    Vertical,
+    Straddle,
+    Strangle,
+    IronCondor,
+    Butterfly,
+    Calendar,
+}
+
+impl fmt::Display for StrategyKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let value = match self {
+            StrategyKind::Vertical => "vertical",
+            StrategyKind::Straddle => "straddle",
+            StrategyKind::Strangle => "strangle",
+            StrategyKind::IronCondor => "iron condor",
+            StrategyKind::Butterfly => "butterfly",
+            StrategyKind::Calendar => "calendar",
+        };
+        f.write_str(value)
+    }
+}
+
+/// One leg of an [`OptionStrategy`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct StrategyLeg {
+    pub contract: OptionContract,
+    pub side: LegSide,
+    /// Contracts of this leg per unit of the strategy.
+    pub ratio: u32,
+}
+
+impl StrategyLeg {
+    fn new(contract: &OptionContract, side: LegSide, ratio: u32) -> Self {
+        Self {
+            contract: contract.clone(),
+            side,
+            ratio,
+        }
+    }
+
+    /// Value of the leg at expiration with the underlying at `price`,
+    /// positive when held long.
+    fn payoff(&self, price: f64) -> f64 {
+        let strike = self.contract.strike();
+        let intrinsic = match self.contract.option_type() {
+            OptionType::Call => (price - strike).max(0.0),
+            OptionType::Put => (strike - price).max(0.0),
+        };
+        self.side.sign() * f64::from(self.ratio) * intrinsic
+    }
+}
+
+/// Outcome of a strategy held to expiration, per share.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PayoffProfile {
+    /// Largest gain, or `None` if unlimited.
+    pub max_profit: Option<f64>,
+    /// Largest loss as a positive amount, or `None` if unlimited.
+    pub max_loss: Option<f64>,
+    /// Underlying prices at which the strategy breaks even, ascending.
+    pub breakevens: Vec<f64>,
+}
+
+/// A multileg option position.
+///
+/// # Example
+/// ```no_run
+/// use tradier::non_blocking::{Client, operation::MarketData};
+/// use tradier::types::{
+///     Greeks, OptionChain, OptionStrategy, OptionType, OrderDuration, StrikeSelector,
+/// };
+/// use tradier::Config;
+///
+/// # async fn run() -> tradier::Result<()> {
+/// let client = Client::new(Config::new());
+/// let expiration = chrono::NaiveDate::from_ymd_opt(2024, 1, 19).unwrap();
+/// let response = client
+///     .get_option_chains(&"SPY".parse()?, &expiration, Some(Greeks::new(true)))
+///     .await?;
+/// let chain = OptionChain::try_from(response)?;
+///
+/// // Sell the 16-delta put and call, with wings $5 further out.
+/// let condor = OptionStrategy::iron_condor(
+///     &chain,
+///     StrikeSelector::Delta(-0.16),
+///     StrikeSelector::Delta(0.16),
+///     5.0,
+/// )?;
+/// let net = condor.net_debit().unwrap_or_default();
+/// if let Some(profile) = condor.payoff_profile(net) {
+///     println!("credit {:.2}, max loss {:?}", -net, profile.max_loss);
+///     println!("breakevens {:?}", profile.breakevens);
+/// }
+/// let order = condor.order(1, net, OrderDuration::Day)?;
+/// println!("{:?}", order.form());
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct OptionStrategy {
+    kind: StrategyKind,
+    legs: Vec<StrategyLeg>,
+}
+
+impl OptionStrategy {
+    /// A vertical spread: buys the `long` strike and sells the `short`
+    /// strike of one option type and expiration.
+    ///
+    /// # Errors
+    /// Returns [`Error::InvalidOrder`] if a leg is not in the chain or both
+    /// resolve to the same strike.
+    pub fn vertical(
+        chain: &OptionChain,
+        option_type: OptionType,
+        long: StrikeSelector,
+        short: StrikeSelector,
+    ) -> Result<Self> {
+        let long = select(chain, option_type, long)?;
+        let short = select(chain, option_type, short)?;
+        distinct_strikes(&[long, short])?;
+        Ok(Self {
+            kind: StrategyKind::Vertical,
+            legs: vec![
+                StrategyLeg::new(long, LegSide::Buy, 1),
+                StrategyLeg::new(short, LegSide::Sell, 1),
+            ],
+        })
+    }
+
+    /// A straddle: a call and a put at the same strike, both bought or
+    /// both sold. A [`StrikeSelector::Delta`] selects by the call's delta.
+    ///
+    /// # Errors
+    /// Returns [`Error::InvalidOrder`] if the strike lacks a call or a put.
+    pub fn straddle(chain: &OptionChain, strike: StrikeSelector, side: LegSide) -> Result<Self> {
+        let call = select(chain, OptionType::Call, strike)?;
+        let put = at_strike(chain, OptionType::Put, call.strike())?;
+        Ok(Self {
+            kind: StrategyKind::Straddle,
+            legs: vec![
+                StrategyLeg::new(put, side, 1),
+                StrategyLeg::new(call, side, 1),
+            ],
+        })
+    }
+
+    /// A strangle: a put and a higher-strike call, both bought or both
+    /// sold.
+    ///
+    /// # Errors
+    /// Returns [`Error::InvalidOrder`] if a leg is not in the chain or the
+    /// put strike is not below the call strike.
+    pub fn strangle(
+        chain: &OptionChain,
+        put: StrikeSelector,
+        call: StrikeSelector,
+        side: LegSide,
+    ) -> Result<Self> {
+        let put = select(chain, OptionType::Put, put)?;
+        let call = select(chain, OptionType::Call, call)?;
+        ascending_strikes(&[put, call])?;
+        Ok(Self {
+            kind: StrategyKind::Strangle,
+            legs: vec![
+                StrategyLeg::new(put, side, 1),
+                StrategyLeg::new(call, side, 1),
+            ],
+        })
+    }
+
+    /// A short iron condor: sells a put and a higher-strike call, and buys
+    /// wings `width` below the put and `width` above the call.
+    ///
+    /// # Errors
+    /// Returns [`Error::InvalidOrder`] if a leg is not in the chain or the
+    /// short put strike is not below the short call strike.
+    pub fn iron_condor(
+        chain: &OptionChain,
+        short_put: StrikeSelector,
+        short_call: StrikeSelector,
+        width: f64,
+    ) -> Result<Self> {
+        let short_put = select(chain, OptionType::Put, short_put)?;
+        let short_call = select(chain, OptionType::Call, short_call)?;
+        let long_put = at_strike(chain, OptionType::Put, short_put.strike() - width)?;
+        let long_call = at_strike(chain, OptionType::Call, short_call.strike() + width)?;
+        ascending_strikes(&[long_put, short_put, short_call, long_call])?;
+        Ok(Self {
+            kind: StrategyKind::IronCondor,
+            legs: vec![
+                StrategyLeg::new(long_put, LegSide::Buy, 1),
+                StrategyLeg::new(short_put, LegSide::Sell, 1),
+                StrategyLeg::new(short_call, LegSide::Sell, 1),
+                StrategyLeg::new(long_call, LegSide::Buy, 1),
+            ],
+        })
+    }
+
+    /// A long butterfly: sells two contracts at the `center` strike and
+    /// buys one `width` below and one `width` above.
+    ///
+    /// # Errors
+    /// Returns [`Error::InvalidOrder`] if a leg is not in the chain or
+    /// `width` is not positive.
+    pub fn butterfly(
+        chain: &OptionChain,
+        option_type: OptionType,
+        center: StrikeSelector,
+        width: f64,
+    ) -> Result<Self> {
+        let center = select(chain, option_type, center)?;
+        let lower = at_strike(chain, option_type, center.strike() - width)?;
+        let upper = at_strike(chain, option_type, center.strike() + width)?;
+        ascending_strikes(&[lower, center, upper])?;
+        Ok(Self {
+            kind: StrategyKind::Butterfly,
+            legs: vec![
+                StrategyLeg::new(lower, LegSide::Buy, 1),
+                StrategyLeg::new(center, LegSide::Sell, 2),
+                StrategyLeg::new(upper, LegSide::Buy, 1),
+            ],
+        })
+    }
+
+    /// A long calendar spread: sells the `near` expiration and buys the
+    /// `far` expiration at the same strike. A [`StrikeSelector::Delta`]
+    /// selects by the near contract's delta.
+    ///
+    /// # Errors
+    /// Returns [`Error::InvalidOrder`] if a leg is not in its chain or the
+    /// far chain does not expire after the near one.
+    pub fn calendar(
+        near: &OptionChain,
+        far: &OptionChain,
+        option_type: OptionType,
+        strike: StrikeSelector,
+    ) -> Result<Self> {
+        let short = select(near, option_type, strike)?;
+        let long = at_strike(far, option_type, short.strike())?;
+        if long.expiration() <= short.expiration() {
+            return Err(Error::InvalidOrder(format!(
+                "calendar far leg {} does not expire after near leg {}",
+                long.symbol(),
+                short.symbol()
+            )));
+        }
+        Ok(Self {
+            kind: StrategyKind::Calendar,
+            legs: vec![
+                StrategyLeg::new(short, LegSide::Sell, 1),
+                StrategyLeg::new(long, LegSide::Buy, 1),
+            ],
+        })
+    }
+
+    /// Returns the shape of the strategy.
+    pub fn kind(&self) -> StrategyKind {
+        self.kind
+    }
+
+    /// Returns the legs, in strike order except for calendars (near first).
+    pub fn legs(&self) -> &[StrategyLeg] {
+        &self.legs
+    }
+
+    /// Returns the same legs with every side flipped, e.g. a short
+    /// butterfly from a long one.
+    #[must_use]
+    pub fn reversed(mut self) -> Self {
+        for leg in &mut self.legs {
+            leg.side = leg.side.opposite();
+        }
+        self
+    }
+
+    /// Returns the net price at the legs' mids: positive for a debit,
+    /// negative for a credit. `None` if a leg has no two-sided quote.
+    pub fn net_debit(&self) -> Option<f64> {
+        self.net_price(|leg| leg.contract.mid())
+    }
+
+    /// Returns the net price crossing the spread on every leg: paying the
+    /// ask on bought legs and receiving the bid on sold ones.
+    pub fn natural_debit(&self) -> Option<f64> {
+        self.net_price(|leg| match leg.side {
+            LegSide::Buy => leg.contract.ask(),
+            LegSide::Sell => leg.contract.bid(),
+        })
+    }
+
+    fn net_price(&self, price: impl Fn(&StrategyLeg) -> Option<f64>) -> Option<f64> {
+        self.legs.iter().try_fold(0.0, |net, leg| {
+            Some(net + leg.side.sign() * f64::from(leg.ratio) * price(leg)?)
+        })
+    }
+
+    /// Returns the profit at expiration with the underlying at `price`,
+    /// after paying `net_debit` (negative for a credit).
+    ///
+    /// `None` for calendars, whose far leg still has time value then.
+    pub fn payoff_at_expiration(&self, price: f64, net_debit: f64) -> Option<f64> {
+        self.single_expiration()
+            .then(|| self.legs.iter().map(|leg| leg.payoff(price)).sum::<f64>() - net_debit)
+    }
+
+    /// Returns the max profit, max loss and breakevens at expiration after
+    /// paying `net_debit` (negative for a credit).
+    ///
+    /// `None` for calendars, whose far leg still has time value then.
+    pub fn payoff_profile(&self, net_debit: f64) -> Option<PayoffProfile> {
+        if !self.single_expiration() {
+            return None;
+        }
+        // The payoff is piecewise linear with kinks at the strikes, so its
+        // extremes lie at zero, at a strike, or beyond the highest strike,
+        // where only calls still move it.
+        let mut prices: Vec<f64> = self.legs.iter().map(|leg| leg.contract.strike()).collect();
+        prices.push(0.0);
+        prices.sort_by(f64::total_cmp);
+        prices.dedup();
+        let points: Vec<(f64, f64)> = prices
+            .into_iter()
+            .map(|price| {
+                let payoff = self.legs.iter().map(|leg| leg.payoff(price)).sum::<f64>();
+                (price, payoff - net_debit)
+            })
+            .collect();
+        let slope: f64 = self
+            .legs
+            .iter()
+            .filter(|leg| leg.contract.option_type() == OptionType::Call)
+            .map(|leg| leg.side.sign() * f64::from(leg.ratio))
+            .sum();
+
+        let values = || points.iter().map(|(_, value)| *value);
+        let max_profit = (slope <= 0.0).then(|| values().fold(f64::NEG_INFINITY, f64::max));
+        let max_loss = (slope >= 0.0).then(|| -values().fold(f64::INFINITY, f64::min));
+
+        let mut breakevens = Vec::new();
+        for pair in points.windows(2) {
+            let ((x0, y0), (x1, y1)) = (pair[0], pair[1]);
+            if y0 == 0.0 {
+                breakevens.push(x0);
+            } else if y0 * y1 < 0.0 {
+                breakevens.push(x0 + (x1 - x0) * y0 / (y0 - y1));
+            }
+        }
+        let (last, value) = *points.last().expect("at least the zero price");
+        if value == 0.0 {
+            breakevens.push(last);
+        } else if value * slope < 0.0 {
+            breakevens.push(last - value / slope);
+        }
+        Some(PayoffProfile {
+            max_profit,
+            max_loss,
+            breakevens,
+        })
+    }
+
+    /// Builds the order opening `quantity` units of the strategy at a net
+    /// limit of `net_debit`: a debit order if positive, a credit order if
+    /// negative, an even order if zero.
+    ///
+    /// # Errors
+    /// Returns [`Error::InvalidOrder`] if `quantity` is zero or the
+    /// strategy has more legs than a multileg order allows, and
+    /// [`Error::MarketDataParseError`] if a leg symbol is not an OCC
+    /// option symbol.
+    pub fn order(
+        &self,
+        quantity: u32,
+        net_debit: f64,
+        duration: OrderDuration,
+    ) -> Result<MultilegOrderRequest> {
+        let (order_type, price) = if net_debit > 0.0 {
+            (OrderType::Debit, Some(net_debit))
+        } else if net_debit < 0.0 {
+            (OrderType::Credit, Some(-net_debit))
+        } else {
+            (OrderType::Even, None)
+        };
+        self.order_request(quantity, order_type, price, duration)
+    }
+
+    /// Builds the order opening `quantity` units of the strategy at market.
+    ///
+    /// # Errors
+    /// As [`OptionStrategy::order`].
+    pub fn market_order(
+        &self,
+        quantity: u32,
+        duration: OrderDuration,
+    ) -> Result<MultilegOrderRequest> {
+        self.order_request(quantity, OrderType::Market, None, duration)
+    }
+
+    fn order_request(
+        &self,
+        quantity: u32,
+        order_type: OrderType,
+        price: Option<f64>,
+        duration: OrderDuration,
+    ) -> Result<MultilegOrderRequest> {
+        let legs = self
+            .legs
+            .iter()
+            .map(|leg| {
+                let leg_quantity = leg.ratio.checked_mul(quantity).ok_or_else(|| {
+                    Error::InvalidOrder(format!(
+                        "{quantity} units of a ratio {} leg overflow the leg quantity",
+                        leg.ratio
+                    ))
+                })?;
+                Ok(LegRequest {
+                    option_symbol: leg.contract.contract()?,
+                    side: leg.side.opening_side(),
+                    quantity: leg_quantity,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        MultilegOrderRequest::builder()
+            .symbol(self.underlying()?)
+            .order_type(order_type)
+            .maybe_price(price)
+            .duration(duration)
+            .legs(legs)
+            .build()
+    }
+
+    /// The underlying from the first leg's quote, or its option root.
+    fn underlying(&self) -> Result<Symbol> {
+        let contract = &self.legs[0].contract;
+        match &contract.quote().underlying {
+            Some(underlying) => underlying.parse(),
+            None => contract
+                .contract()
+                .and_then(|symbol: OptionSymbol| symbol.root().parse()),
+        }
+    }
+
+    fn single_expiration(&self) -> bool {
+        let first = self.legs[0].contract.expiration();
+        self.legs
+            .iter()
+            .all(|leg| leg.contract.expiration() == first)
+    }
+}
+
+/// Resolves `selector` to a contract of `option_type`.
+fn select(
+    chain: &OptionChain,
+    option_type: OptionType,
+    selector: StrikeSelector,
+) -> Result<&OptionContract> {
+    match selector {
+        StrikeSelector::Strike(strike) => at_strike(chain, option_type, strike),
+        StrikeSelector::NearestTo(price) => {
+            let row = chain.nearest_strike(price).ok_or_else(|| {
+                Error::InvalidOrder("cannot select a strike from an empty chain".to_owned())
+            })?;
+            at_strike(chain, option_type, row.strike)
+        }
+        StrikeSelector::Delta(delta) => chain.by_delta(option_type, delta).ok_or_else(|| {
+            Error::InvalidOrder(format!("no {option_type} with a delta in the chain"))
+        }),
+    }
+}
+
+/// Returns the contract of `option_type` listed at exactly `strike`.
+fn at_strike(chain: &OptionChain, option_type: OptionType, strike: f64) -> Result<&OptionContract> {
+    chain
+        .strike(strike)
+        .and_then(|row| row.contract(option_type))
+        .ok_or_else(|| Error::InvalidOrder(format!("no {option_type} at strike {strike}")))
+}
+
+fn distinct_strikes(legs: &[&OptionContract]) -> Result<()> {
+    if legs
+        .windows(2)
+        .any(|pair| pair[0].strike() == pair[1].strike())
+    {
+        return Err(Error::InvalidOrder(format!(
+            "legs share strike {}",
+            legs[0].strike()
+        )));
+    }
+    Ok(())
+}
+
+fn ascending_strikes(legs: &[&OptionContract]) -> Result<()> {
+    if legs
+        .windows(2)
+        .any(|pair| pair[0].strike() >= pair[1].strike())
+    {
+        let strikes: Vec<f64> = legs.iter().map(|leg| leg.strike()).collect();
+        return Err(Error::InvalidOrder(format!(
+            "strikes {strikes:?} are not strictly ascending"
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::market_data::test_support::option_quote_json;
+    use crate::market_data::types::GetOptionChainsResponse;
+
+    /// Calls and puts at 460..=480 step 5, quoted 0.10 wide around the mid.
+    fn chain(expiration: &str) -> OptionChain {
+        chain_at(
+            expiration,
+            &[460.0, 465.0, 470.0, 475.0, 480.0],
+            &[12.0, 8.5, 5.5, 3.0, 1.5],
+        )
+    }
+
+    /// Calls at `strikes` with mids `calls`, and puts with the call mids
+    /// mirrored, quoted 0.10 wide.
+    fn chain_at(expiration: &str, strikes: &[f64], calls: &[f64]) -> OptionChain {
+        let code = expiration.replace('-', "");
+        let code = &code[2..];
+        let last = strikes.len() - 1;
+        let mut quotes = Vec::new();
+        for (i, &strike) in strikes.iter().enumerate() {
+            for (option_type, value, delta) in [
+                ("call", calls[i], 0.9 - 0.2 * i as f64),
+                ("put", calls[last - i], -0.1 - 0.2 * i as f64),
+            ] {
+                let flag = if option_type == "call" { 'C' } else { 'P' };
+                let symbol = format!("SPY{code}{flag}{:08}", (strike * 1000.0).round() as u64);
+                let mut quote = option_quote_json(&symbol, option_type, strike, expiration);
+                quote["bid"] = (value - 0.05).into();
+                quote["ask"] = (value + 0.05).into();
+                quote["greeks"] = serde_json::json!({ "delta": delta });
+                quotes.push(quote);
+            }
+        }
+        let response = serde_json::json!({ "options": { "option": quotes } });
+        let response: GetOptionChainsResponse = serde_json::from_value(response).unwrap();
+        OptionChain::try_from(response).unwrap()
+    }
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-9
+    }
+
+    #[test]
+    fn test_vertical_prices_profile_and_order() {
+        let chain = chain("2024-01-19");
+        let spread = OptionStrategy::vertical(
+            &chain,
+            OptionType::Call,
+            StrikeSelector::NearestTo(469.0),
+            StrikeSelector::Strike(475.0),
+        )
+        .unwrap();
+        assert_eq!(spread.kind(), StrategyKind::Vertical);
+        let debit = spread.net_debit().unwrap();
+        assert!(close(debit, 2.5));
+        assert!(close(spread.natural_debit().unwrap(), 2.6));
+
+        let profile = spread.payoff_profile(debit).unwrap();
+        assert!(close(profile.max_profit.unwrap(), 2.5));
+        assert!(close(profile.max_loss.unwrap(), 2.5));
+        assert_eq!(profile.breakevens.len(), 1);
+        assert!(close(profile.breakevens[0], 472.5));
+        assert!(close(
+            spread.payoff_at_expiration(480.0, debit).unwrap(),
+            2.5
+        ));
+
+        let order = spread.order(3, debit, OrderDuration::Gtc).unwrap();
+        assert_eq!(order.symbol().as_str(), "SPY");
+        assert_eq!(order.order_type(), OrderType::Debit);
+        assert!(close(order.price().unwrap(), 2.5));
+        let legs = order.legs();
+        assert_eq!(legs[0].option_symbol.to_string(), "SPY240119C00470000");
+        assert_eq!(legs[0].side, OrderSide::BuyToOpen);
+        assert_eq!(legs[1].side, OrderSide::SellToOpen);
+        assert_eq!(legs[1].quantity, 3);
+    }
+
+    #[test]
+    fn test_credit_and_unbounded_profiles() {
+        let chain = chain("2024-01-19");
+        let condor = OptionStrategy::iron_condor(
+            &chain,
+            StrikeSelector::Delta(-0.3),
+            StrikeSelector::Delta(0.3),
+            5.0,
+        )
+        .unwrap();
+        let strikes: Vec<f64> = condor.legs().iter().map(|l| l.contract.strike()).collect();
+        assert_eq!(strikes, [460.0, 465.0, 475.0, 480.0]);
+        let credit = condor.net_debit().unwrap();
+        assert!(close(credit, -3.0));
+        let profile = condor.payoff_profile(credit).unwrap();
+        assert!(close(profile.max_profit.unwrap(), 3.0));
+        assert!(close(profile.max_loss.unwrap(), 2.0));
+        assert!(close(profile.breakevens[0], 462.0));
+        assert!(close(profile.breakevens[1], 478.0));
+        let order = condor.order(1, credit, OrderDuration::Day).unwrap();
+        assert_eq!(order.order_type(), OrderType::Credit);
+        assert!(close(order.price().unwrap(), 3.0));
+
+        let straddle =
+            OptionStrategy::straddle(&chain, StrikeSelector::NearestTo(471.0), LegSide::Buy)
+                .unwrap();
+        let debit = straddle.net_debit().unwrap();
+        assert!(close(debit, 11.0));
+        let profile = straddle.payoff_profile(debit).unwrap();
+        assert_eq!(profile.max_profit, None);
+        assert!(close(profile.max_loss.unwrap(), 11.0));
+        assert!(close(profile.breakevens[0], 459.0));
+        assert!(close(profile.breakevens[1], 481.0));
+        let short = straddle.reversed().payoff_profile(-debit).unwrap();
+        assert_eq!(short.max_loss, None);
+
+        let fly =
+            OptionStrategy::butterfly(&chain, OptionType::Put, StrikeSelector::Strike(470.0), 5.0)
+                .unwrap();
+        assert_eq!(fly.legs()[1].ratio, 2);
+        let profile = fly.payoff_profile(fly.net_debit().unwrap()).unwrap();
+        assert!(close(profile.max_profit.unwrap(), 4.5));
+        assert!(close(profile.max_loss.unwrap(), 0.5));
+        assert!(close(profile.breakevens[0], 465.5));
+        assert!(close(profile.breakevens[1], 474.5));
+        assert_eq!(
+            fly.market_order(2, OrderDuration::Day).unwrap().legs()[1].quantity,
+            4
+        );
+    }
+
+    #[test]
+    fn test_sub_dollar_wings_and_quantity_overflow() {
+        let chain = chain_at(
+            "2024-01-19",
+            &[12.1, 12.2, 12.3, 12.4],
+            &[0.6, 0.4, 0.25, 0.15],
+        );
+        let fly =
+            OptionStrategy::butterfly(&chain, OptionType::Call, StrikeSelector::Strike(12.2), 0.1)
+                .unwrap();
+        let strikes: Vec<f64> = fly.legs().iter().map(|l| l.contract.strike()).collect();
+        assert_eq!(strikes, [12.1, 12.2, 12.3]);
+        let condor = OptionStrategy::iron_condor(
+            &chain,
+            StrikeSelector::Strike(12.2),
+            StrikeSelector::Strike(12.3),
+            0.1,
+        )
+        .unwrap();
+        let strikes: Vec<f64> = condor.legs().iter().map(|l| l.contract.strike()).collect();
+        assert_eq!(strikes, [12.1, 12.2, 12.3, 12.4]);
+
+        assert!(matches!(
+            fly.market_order(u32::MAX, OrderDuration::Day),
+            Err(Error::InvalidOrder(_))
+        ));
+    }
+
+    #[test]
+    fn test_calendar_and_selection_errors() {
+        let near = chain("2024-01-19");
+        let far = chain("2024-02-16");
+        let calendar =
+            OptionStrategy::calendar(&near, &far, OptionType::Call, StrikeSelector::Strike(470.0))
+                .unwrap();
+        assert_eq!(calendar.legs()[0].side, LegSide::Sell);
+        assert_eq!(calendar.payoff_profile(1.0), None);
+        assert!(
+            OptionStrategy::calendar(&far, &near, OptionType::Call, StrikeSelector::Strike(470.0))
+                .is_err()
+        );
+
+        assert!(matches!(
+            OptionStrategy::butterfly(&near, OptionType::Call, StrikeSelector::Strike(480.0), 5.0),
+            Err(Error::InvalidOrder(_))
+        ));
+        assert!(matches!(
+            OptionStrategy::strangle(
+                &near,
+                StrikeSelector::Strike(475.0),
+                StrikeSelector::Strike(465.0),
+                LegSide::Sell
+            ),
+            Err(Error::InvalidOrder(_))
+        ));
+        assert!(matches!(
+            OptionStrategy::vertical(
+                &near,
+                OptionType::Put,
+                StrikeSelector::Strike(470.0),
+                StrikeSelector::Strike(470.0)
+            ),
+            Err(Error::InvalidOrder(_))
+        ));
+    }
+}
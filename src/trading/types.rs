@@ -0,0 +1,229 @@
+//! Request types for placing orders.
+
+use crate::common::{OptionSymbol, Symbol};
+use crate::types::{OrderDuration, OrderSide, OrderType};
+use crate::{Error, Result};
+
+/// Legs a Tradier multileg order may carry.
+const LEG_COUNT: std::ops::RangeInclusive<usize> = 2..=4;
+
+/// One leg of a [`MultilegOrderRequest`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct LegRequest {
+    pub option_symbol: OptionSymbol,
+    pub side: OrderSide,
+    pub quantity: u32,
+}
+
+/// A validated `class=multileg` order for
+/// `POST /v1/accounts/{account_id}/orders`.
+///
+/// [`MultilegOrderRequest::form`] renders the form body Tradier expects.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MultilegOrderRequest {
+    symbol: Symbol,
+    order_type: OrderType,
+    duration: OrderDuration,
+    price: Option<f64>,
+    legs: Vec<LegRequest>,
+    tag: Option<String>,
+}
+
+#[bon::bon]
+impl MultilegOrderRequest {
+    /// Constructs a new `MultilegOrderRequest`.
+    ///
+    /// # Arguments
+    /// - `symbol`: Underlying symbol.
+    /// - `order_type`: [`OrderType::Market`], [`OrderType::Debit`],
+    ///   [`OrderType::Credit`] or [`OrderType::Even`].
+    /// - `duration`: Defaults to [`OrderDuration::Day`].
+    /// - `price`: Net limit price, required for debit and credit orders.
+    ///   Rounded to cents, which is what [`Self::price`] returns and
+    ///   [`Self::form`] sends.
+    /// - `legs`: Two to four option legs.
+    /// - `tag`: Optional client tag.
+    ///
+    /// # Errors
+    /// Returns [`Error::InvalidOrder`] if the leg count, a leg quantity, the
+    /// order type or the price does not fit a multileg order.
+    #[builder(builder_type(vis = "pub"))]
+    fn new(
+        symbol: Symbol,
+        order_type: OrderType,
+        #[builder(default = OrderDuration::Day)] duration: OrderDuration,
+        price: Option<f64>,
+        legs: Vec<LegRequest>,
+        tag: Option<String>,
+    ) -> Result<Self> {
+        if !LEG_COUNT.contains(&legs.len()) {
+            return Err(Error::InvalidOrder(format!(
+                "a multileg order takes 2 to 4 legs, got {}",
+                legs.len()
+            )));
+        }
+        if let Some(leg) = legs.iter().find(|leg| leg.quantity == 0) {
+            return Err(Error::InvalidOrder(format!(
+                "leg {} has zero quantity",
+                leg.option_symbol
+            )));
+        }
+        let price = price.map(|price| (price * 100.0).round() / 100.0);
+        match (order_type, price) {
+            (OrderType::Debit | OrderType::Credit, Some(price))
+                if price.is_finite() && price > 0.0 => {}
+            (OrderType::Debit | OrderType::Credit, _) => {
+                return Err(Error::InvalidOrder(format!(
+                    "a {order_type} order needs a finite price of at least one cent"
+                )));
+            }
+            (OrderType::Market | OrderType::Even, None) => {}
+            (OrderType::Market | OrderType::Even, Some(_)) => {
+                return Err(Error::InvalidOrder(format!(
+                    "a {order_type} order takes no price"
+                )));
+            }
+            _ => {
+                return Err(Error::InvalidOrder(format!(
+                    "{order_type} is not a multileg order type"
+                )));
+            }
+        }
+        Ok(Self {
+            symbol,
+            order_type,
+            duration,
+            price,
+            legs,
+            tag,
+        })
+    }
+
+    /// Returns the underlying symbol.
+    pub fn symbol(&self) -> &Symbol {
+        &self.symbol
+    }
+
+    /// Returns the order type.
+    pub fn order_type(&self) -> OrderType {
+        self.order_type
+    }
+
+    /// Returns the time in force.
+    pub fn duration(&self) -> OrderDuration {
+        self.duration
+    }
+
+    /// Returns the net limit price, if any.
+    pub fn price(&self) -> Option<f64> {
+        self.price
+    }
+
+    /// Returns the legs.
+    pub fn legs(&self) -> &[LegRequest] {
+        &self.legs
+    }
+
+    /// Returns the form fields of the order, with indexed leg fields
+    /// (`option_symbol[0]`, `side[0]`, `quantity[0]`, ...).
+    pub fn form(&self) -> Vec<(String, String)> {
+        let mut form = vec![
+            ("class".to_owned(), "multileg".to_owned()),
+            ("symbol".to_owned(), self.symbol.to_string()),
+            ("type".to_owned(), self.order_type.to_string()),
+            ("duration".to_owned(), self.duration.to_string()),
+        ];
+        if let Some(price) = self.price {
+            form.push(("price".to_owned(), format!("{price:.2}")));
+        }
+        if let Some(tag) = &self.tag {
+            form.push(("tag".to_owned(), tag.clone()));
+        }
+        for (i, leg) in self.legs.iter().enumerate() {
+            form.push((format!("option_symbol[{i}]"), leg.option_symbol.to_string()));
+            form.push((format!("side[{i}]"), leg.side.to_string()));
+            form.push((format!("quantity[{i}]"), leg.quantity.to_string()));
+        }
+        form
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn leg(symbol: &str, side: OrderSide) -> LegRequest {
+        LegRequest {
+            option_symbol: symbol.parse().unwrap(),
+            side,
+            quantity: 1,
+        }
+    }
+
+    #[test]
+    fn test_renders_form_fields() {
+        let order = MultilegOrderRequest::builder()
+            .symbol("SPY".parse().unwrap())
+            .order_type(OrderType::Debit)
+            .price(1.254)
+            .legs(vec![
+                leg("SPY240119C00470000", OrderSide::BuyToOpen),
+                leg("SPY240119C00475000", OrderSide::SellToOpen),
+            ])
+            .build()
+            .unwrap();
+        let form = order.form();
+        let field = |key: &str| form.iter().find(|(k, _)| k == key).map(|(_, v)| v.as_str());
+        assert_eq!(field("class"), Some("multileg"));
+        assert_eq!(field("symbol"), Some("SPY"));
+        assert_eq!(field("type"), Some("debit"));
+        assert_eq!(field("duration"), Some("day"));
+        assert_eq!(order.price(), Some(1.25));
+        assert_eq!(field("price"), Some("1.25"));
+        assert_eq!(field("option_symbol[1]"), Some("SPY240119C00475000"));
+        assert_eq!(field("side[0]"), Some("buy_to_open"));
+        assert_eq!(field("quantity[1]"), Some("1"));
+        assert_eq!(field("tag"), None);
+    }
+
+    #[test]
+    fn test_rejects_invalid_orders() {
+        let legs = || {
+            vec![
+                leg("SPY240119C00470000", OrderSide::BuyToOpen),
+                leg("SPY240119C00475000", OrderSide::SellToOpen),
+            ]
+        };
+        let build = |order_type, price, legs| {
+            MultilegOrderRequest::builder()
+                .symbol("SPY".parse().unwrap())
+                .order_type(order_type)
+                .maybe_price(price)
+                .legs(legs)
+                .build()
+        };
+        assert!(matches!(
+            build(OrderType::Credit, None, legs()),
+            Err(Error::InvalidOrder(_))
+        ));
+        for price in [f64::INFINITY, f64::NAN, 0.004] {
+            assert!(matches!(
+                build(OrderType::Debit, Some(price), legs()),
+                Err(Error::InvalidOrder(_))
+            ));
+        }
+        assert!(matches!(
+            build(OrderType::Even, Some(0.5), legs()),
+            Err(Error::InvalidOrder(_))
+        ));
+        assert!(matches!(
+            build(OrderType::Limit, Some(0.5), legs()),
+            Err(Error::InvalidOrder(_))
+        ));
+        assert!(matches!(
+            build(OrderType::Market, None, legs()[..1].to_vec()),
+            Err(Error::InvalidOrder(_))
+        ));
+        assert!(build(OrderType::Market, None, legs()).is_ok());
+    }
+}
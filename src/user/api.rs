@@ -7,6 +7,7 @@ pub mod non_blocking {
         async fn get_user_profile(&self) -> Result<UserProfileResponse>;
     }
 }
+#[cfg(feature = "blocking")]
 pub mod blocking {
     use super::*;
     pub trait User: Sealed {
@@ -0,0 +1,124 @@
+//! Exact-decimal conversions used by the `decimal` feature.
+//!
+//! Tradier sends money either as JSON strings (streaming trades, summaries,
+//! time-and-sales) or as JSON numbers that serde has already read into `f64`.
+//! Strings are parsed directly so no precision is ever lost. `f64` values
+//! are converted through their shortest round-trip representation, which is
+//! the literal Tradier put on the wire (`281.84` stays `281.84` rather than
+//! becoming `281.839999999999974988`).
+
+use std::str::FromStr;
+
+use rust_decimal::Decimal;
+
+use crate::{Error, Result};
+
+/// Parses an upstream decimal string into a [`Decimal`] without going
+/// through binary floating point.
+///
+/// # Errors
+/// Returns [`Error::ParseDecimal`] if the value is not a well-formed decimal
+/// number or does not fit in a [`Decimal`].
+pub(crate) fn parse_decimal(value: &str) -> Result<Decimal> {
+    Decimal::from_str(value).map_err(|e| Error::ParseDecimal(value.to_owned(), e.to_string()))
+}
+
+/// Converts an `f64` deserialized from a JSON number into a [`Decimal`].
+///
+/// # Errors
+/// Returns [`Error::ParseDecimal`] if the value is not finite or does not fit
+/// in a [`Decimal`].
+pub(crate) fn decimal_from_f64(value: f64) -> Result<Decimal> {
+    if !value.is_finite() {
+        return Err(Error::ParseDecimal(
+            value.to_string(),
+            "value is not finite".to_owned(),
+        ));
+    }
+    parse_decimal(&value.to_string())
+}
+
+/// Generates `<field>_decimal()` accessors for `f64` / `Option<f64>` fields.
+///
+/// ```ignore
+/// decimal_accessors!(AccountBalances {
+///     value total_cash => total_cash_decimal,
+/// });
+/// decimal_accessors!(Quote {
+///     option bid => bid_decimal,
+/// });
+/// ```
+///
+/// `value` fields are `f64` and produce `fn(&self) -> Result<Decimal>`;
+/// `option` fields are `Option<f64>` and produce
+/// `fn(&self) -> Result<Option<Decimal>>`.
+macro_rules! decimal_accessors {
+    (@accessor value $field:ident => $name:ident) => {
+        #[doc = concat!("Returns `", stringify!($field), "` as an exact [`rust_decimal::Decimal`].")]
+        ///
+        /// # Errors
+        /// Returns [`crate::Error::ParseDecimal`] if the value cannot be
+        /// represented as a `Decimal`.
+        pub fn $name(&self) -> $crate::Result<::rust_decimal::Decimal> {
+            $crate::utils::decimal::decimal_from_f64(self.$field)
+        }
+    };
+    (@accessor option $field:ident => $name:ident) => {
+        #[doc = concat!("Returns `", stringify!($field), "` as an exact [`rust_decimal::Decimal`], if present.")]
+        ///
+        /// # Errors
+        /// Returns [`crate::Error::ParseDecimal`] if the value cannot be
+        /// represented as a `Decimal`.
+        pub fn $name(&self) -> $crate::Result<Option<::rust_decimal::Decimal>> {
+            self.$field
+                .map($crate::utils::decimal::decimal_from_f64)
+                .transpose()
+        }
+    };
+    ($ty:ty { $($kind:ident $field:ident => $name:ident),* $(,)? }) => {
+        impl $ty {
+            $($crate::utils::decimal::decimal_accessors!(@accessor $kind $field => $name);)*
+        }
+    };
+}
+
+pub(crate) use decimal_accessors;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_decimal_keeps_exact_digits() {
+        let value = parse_decimal("281.1200").expect("parse");
+        assert_eq!(value.to_string(), "281.1200");
+    }
+
+    #[test]
+    fn test_parse_decimal_malformed_returns_parse_decimal_error() {
+        assert!(matches!(
+            parse_decimal("not-a-number"),
+            Err(Error::ParseDecimal(_, _))
+        ));
+    }
+
+    #[test]
+    fn test_decimal_from_f64_uses_shortest_representation() {
+        let value = decimal_from_f64(281.84).expect("convert");
+        assert_eq!(value, Decimal::from_str("281.84").unwrap());
+        let sum = decimal_from_f64(0.1).unwrap() + decimal_from_f64(0.2).unwrap();
+        assert_eq!(sum, Decimal::from_str("0.3").unwrap());
+    }
+
+    #[test]
+    fn test_decimal_from_f64_non_finite_returns_error() {
+        assert!(matches!(
+            decimal_from_f64(f64::NAN),
+            Err(Error::ParseDecimal(_, _))
+        ));
+        assert!(matches!(
+            decimal_from_f64(f64::INFINITY),
+            Err(Error::ParseDecimal(_, _))
+        ));
+    }
+}
@@ -1,6 +1,9 @@
+#[cfg(feature = "decimal")]
+pub(crate) mod decimal;
 pub mod logger;
 mod one_or_many;
 mod sealed;
+pub(crate) mod telemetry;
 #[cfg(test)]
 pub(crate) mod tests;
 
@@ -14,6 +14,14 @@ impl<T> OneOrMany<T> {
             OneOrMany::Many(things) => things,
         }
     }
+
+    /// Returns the items as a slice without consuming them.
+    pub fn as_slice(&self) -> &[T] {
+        match self {
+            OneOrMany::One(thing) => std::slice::from_ref(thing),
+            OneOrMany::Many(things) => things,
+        }
+    }
 }
 
 impl<T> Default for OneOrMany<T> {
@@ -0,0 +1,330 @@
+//! Tracing and `metrics` facade hooks shared by the REST client and the
+//! streaming decoders.
+//!
+//! Every hook is always callable; the `metrics` calls compile away unless
+//! the `metrics` cargo feature is enabled. Install any `metrics` exporter
+//! (Prometheus, StatsD, ...) in the application to collect them.
+//!
+//! | Metric | Kind | Labels |
+//! |--------|------|--------|
+//! | `tradier_rest_requests_total` | counter | `endpoint`, `status` |
+//! | `tradier_rest_request_duration_seconds` | histogram | `endpoint` |
+//! | `tradier_rest_errors_total` | counter | `endpoint`, `kind` |
+//! | `tradier_rate_limit_available` | gauge | `endpoint` |
+//! | `tradier_stream_connections_total` | counter | `transport` |
+//! | `tradier_stream_messages_total` | counter | `transport` |
+//! | `tradier_stream_decode_errors_total` | counter | `transport` |
+//! | `tradier_stream_errors_total` | counter | `transport` |
+//! | `tradier_stream_reconnects_total` | counter | `transport` |
+//!
+//! `endpoint` is the request path with the account id replaced by
+//! `{account_id}`, so label cardinality stays bounded. `transport` is `ws`
+//! or `http`. Messages per second is `rate(tradier_stream_messages_total)`.
+
+use std::time::Instant;
+
+use tracing::Span;
+use url::Url;
+
+/// Streaming transport label for WebSocket decoders.
+#[cfg(feature = "wssession")]
+pub(crate) const TRANSPORT_WS: &str = "ws";
+
+/// Streaming transport label for HTTP chunked decoders.
+#[cfg(feature = "streaming")]
+pub(crate) const TRANSPORT_HTTP: &str = "http";
+
+/// An in-flight REST request.
+///
+/// Created right before the request is sent and finished once the response
+/// headers (or a transport error) arrive.
+pub(crate) struct RestCall {
+    started: Instant,
+    #[cfg(feature = "metrics")]
+    endpoint: String,
+}
+
+impl RestCall {
+    /// Starts timing a request to `url`.
+    #[inline]
+    pub(crate) fn start(url: &Url) -> Self {
+        #[cfg(not(feature = "metrics"))]
+        let _ = url;
+        RestCall {
+            started: Instant::now(),
+            #[cfg(feature = "metrics")]
+            endpoint: endpoint_label(url.path()),
+        }
+    }
+
+    /// Records the outcome of the request.
+    ///
+    /// Fills the `http.status` / `latency_ms` fields of the current
+    /// `tradier.rest` span (a no-op outside such a span) and, with the
+    /// `metrics` feature, updates the REST counters, latency histogram and
+    /// rate-limit gauge.
+    pub(crate) fn finish(self, response: &reqwest::Result<reqwest::Response>) {
+        let elapsed = self.started.elapsed();
+        let status = match response {
+            Ok(response) => Some(response.status()),
+            Err(e) => e.status(),
+        };
+
+        let span = Span::current();
+        span.record("latency_ms", elapsed.as_millis() as u64);
+        if let Some(status) = status {
+            span.record("http.status", status.as_u16());
+        }
+
+        #[cfg(feature = "metrics")]
+        {
+            let endpoint = self.endpoint;
+            let status_label = status.map_or_else(|| "none".to_owned(), |s| s.as_u16().to_string());
+            ::metrics::counter!(
+                "tradier_rest_requests_total",
+                "endpoint" => endpoint.clone(),
+                "status" => status_label
+            )
+            .increment(1);
+            ::metrics::histogram!(
+                "tradier_rest_request_duration_seconds",
+                "endpoint" => endpoint.clone()
+            )
+            .record(elapsed.as_secs_f64());
+
+            let error_kind = match response {
+                Ok(response) if response.status().is_client_error() => Some("http_4xx"),
+                Ok(response) if response.status().is_server_error() => Some("http_5xx"),
+                Ok(_) => None,
+                Err(e) => Some(reqwest_error_kind(e)),
+            };
+            if let Some(kind) = error_kind {
+                ::metrics::counter!(
+                    "tradier_rest_errors_total",
+                    "endpoint" => endpoint.clone(),
+                    "kind" => kind
+                )
+                .increment(1);
+            }
+
+            if let Ok(response) = response
+                && let Some(available) = response
+                    .headers()
+                    .get("x-ratelimit-available")
+                    .and_then(|v| v.to_str().ok())
+                    .and_then(|v| v.parse::<f64>().ok())
+            {
+                ::metrics::gauge!("tradier_rate_limit_available", "endpoint" => endpoint)
+                    .set(available);
+            }
+        }
+    }
+}
+
+/// Counts a newly opened stream connection.
+#[cfg(any(feature = "wssession", feature = "streaming"))]
+#[inline]
+pub(crate) fn record_stream_connect(transport: &'static str) {
+    #[cfg(feature = "metrics")]
+    ::metrics::counter!("tradier_stream_connections_total", "transport" => transport).increment(1);
+    #[cfg(not(feature = "metrics"))]
+    let _ = transport;
+}
+
+/// Counts a reconnect attempt made by a supervised stream.
+#[cfg(feature = "wssession")]
+#[inline]
+pub(crate) fn record_stream_reconnect(transport: &'static str) {
+    #[cfg(feature = "metrics")]
+    ::metrics::counter!("tradier_stream_reconnects_total", "transport" => transport).increment(1);
+    #[cfg(not(feature = "metrics"))]
+    let _ = transport;
+}
+
+/// Counts one item yielded by a streaming decoder.
+#[cfg(any(feature = "wssession", feature = "streaming"))]
+#[inline]
+pub(crate) fn record_stream_item<T>(transport: &'static str, item: &crate::Result<T>) {
+    #[cfg(feature = "metrics")]
+    match item {
+        Ok(_) => {
+            ::metrics::counter!("tradier_stream_messages_total", "transport" => transport)
+                .increment(1);
+        }
+        Err(crate::Error::StreamDecodeError(_, _)) => {
+            ::metrics::counter!("tradier_stream_decode_errors_total", "transport" => transport)
+                .increment(1);
+        }
+        Err(_) => {
+            ::metrics::counter!("tradier_stream_errors_total", "transport" => transport)
+                .increment(1);
+        }
+    }
+    #[cfg(not(feature = "metrics"))]
+    let _ = (transport, item);
+}
+
+/// Maps a request path to a bounded-cardinality label by masking the
+/// account id in `/v1/accounts/{id}/...` paths.
+#[cfg(feature = "metrics")]
+fn endpoint_label(path: &str) -> String {
+    const ACCOUNTS: &str = "/v1/accounts/";
+    match path.strip_prefix(ACCOUNTS) {
+        Some(rest) => match rest.find('/') {
+            Some(idx) => format!("{ACCOUNTS}{{account_id}}{}", &rest[idx..]),
+            None => format!("{ACCOUNTS}{{account_id}}"),
+        },
+        None => path.to_owned(),
+    }
+}
+
+#[cfg(feature = "metrics")]
+fn reqwest_error_kind(e: &reqwest::Error) -> &'static str {
+    if e.is_timeout() {
+        "timeout"
+    } else if e.is_connect() {
+        "connect"
+    } else if e.is_decode() {
+        "decode"
+    } else if e.is_status() {
+        "status"
+    } else {
+        "network"
+    }
+}
+
+#[cfg(all(test, feature = "metrics"))]
+mod tests {
+    use std::collections::HashMap;
+
+    use metrics_util::debugging::{DebugValue, DebuggingRecorder, Snapshotter};
+
+    use super::*;
+    use crate::{Config, client::non_blocking::TradierRestClient, utils::tests::with_env_vars};
+    use httpmock::MockServer;
+
+    /// Flattens a snapshot into `name{k=v,...}` -> value.
+    fn snapshot(snapshotter: &Snapshotter) -> HashMap<String, DebugValue> {
+        snapshotter
+            .snapshot()
+            .into_vec()
+            .into_iter()
+            .map(|(key, _, _, value)| {
+                let key = key.key();
+                let mut labels: Vec<String> = key
+                    .labels()
+                    .map(|l| format!("{}={}", l.key(), l.value()))
+                    .collect();
+                labels.sort();
+                (format!("{}{{{}}}", key.name(), labels.join(",")), value)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_endpoint_label_masks_account_id() {
+        assert_eq!(
+            endpoint_label("/v1/accounts/VA12345678/balances"),
+            "/v1/accounts/{account_id}/balances"
+        );
+        assert_eq!(
+            endpoint_label("/v1/accounts/VA1"),
+            "/v1/accounts/{account_id}"
+        );
+        assert_eq!(endpoint_label("/v1/markets/quotes"), "/v1/markets/quotes");
+    }
+
+    #[test]
+    fn test_rest_call_records_requests_errors_and_rate_limit() {
+        let server = MockServer::start();
+        let _op = server.mock(|when, then| {
+            when.path("/v1/accounts/VA12345678/balances");
+            then.status(500)
+                .header("x-ratelimit-available", "117")
+                .body("{}");
+        });
+        let recorder = DebuggingRecorder::new();
+        let snapshotter = recorder.snapshotter();
+        with_env_vars(
+            vec![
+                ("TRADIER_REST_BASE_URL", &server.base_url()),
+                ("TRADIER_ACCESS_TOKEN", "testToken"),
+            ],
+            || {
+                metrics::with_local_recorder(&recorder, || {
+                    let rt = tokio::runtime::Builder::new_current_thread()
+                        .enable_all()
+                        .build()
+                        .expect("runtime");
+                    rt.block_on(async {
+                        let client = TradierRestClient::new(Config::new());
+                        let url = client
+                            .get_request_url("/v1/accounts/VA12345678/balances")
+                            .unwrap();
+                        let bearer = client.get_bearer_token().unwrap();
+                        let _ = client.make_service_call(url, bearer).await;
+                    });
+                });
+            },
+        );
+
+        let metrics = snapshot(&snapshotter);
+        let endpoint = "endpoint=/v1/accounts/{account_id}/balances";
+        assert_eq!(
+            metrics.get(&format!(
+                "tradier_rest_requests_total{{{endpoint},status=500}}"
+            )),
+            Some(&DebugValue::Counter(1))
+        );
+        assert_eq!(
+            metrics.get(&format!(
+                "tradier_rest_errors_total{{{endpoint},kind=http_5xx}}"
+            )),
+            Some(&DebugValue::Counter(1))
+        );
+        assert!(matches!(
+            metrics.get(&format!("tradier_rate_limit_available{{{endpoint}}}")),
+            Some(DebugValue::Gauge(v)) if v.into_inner() == 117.0
+        ));
+        assert!(matches!(
+            metrics.get(&format!("tradier_rest_request_duration_seconds{{{endpoint}}}")),
+            Some(DebugValue::Histogram(samples)) if samples.len() == 1
+        ));
+    }
+
+    #[cfg(any(feature = "wssession", feature = "streaming"))]
+    #[test]
+    fn test_record_stream_item_counts_messages_and_errors_by_kind() {
+        let recorder = DebuggingRecorder::new();
+        let snapshotter = recorder.snapshotter();
+        metrics::with_local_recorder(&recorder, || {
+            record_stream_connect("http");
+            record_stream_item::<()>("http", &Ok(()));
+            record_stream_item::<()>("http", &Ok(()));
+            record_stream_item::<()>(
+                "http",
+                &Err(crate::Error::StreamDecodeError("x".into(), "bad".into())),
+            );
+            record_stream_item::<()>("http", &Err(crate::Error::UnexpectedError("boom".into())));
+        });
+
+        let metrics = snapshot(&snapshotter);
+        let get = |name: &str| metrics.get(&format!("{name}{{transport=http}}"));
+        assert_eq!(
+            get("tradier_stream_connections_total"),
+            Some(&DebugValue::Counter(1))
+        );
+        assert_eq!(
+            get("tradier_stream_messages_total"),
+            Some(&DebugValue::Counter(2))
+        );
+        assert_eq!(
+            get("tradier_stream_decode_errors_total"),
+            Some(&DebugValue::Counter(1))
+        );
+        assert_eq!(
+            get("tradier_stream_errors_total"),
+            Some(&DebugValue::Counter(1))
+        );
+    }
+}
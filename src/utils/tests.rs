@@ -1,15 +1,18 @@
 use std::{env, sync::Mutex};
 
-use crate::{
-    config::{Config, Credentials, RestApiConfig, StreamingConfig},
-    wssession::MarketSessionPayload,
-};
+use crate::config::{Config, Credentials, RestApiConfig, StreamingConfig};
+#[cfg(feature = "wssession")]
+use crate::wssession::MarketSessionPayload;
 use chrono::{DateTime, Utc};
+#[cfg(feature = "wssession")]
 use futures_util::{SinkExt, StreamExt};
 use proptest::prelude::Strategy;
 use serde::Serialize;
+#[cfg(feature = "wssession")]
 use tokio::net::TcpListener;
+#[cfg(feature = "wssession")]
 use tokio_tungstenite::accept_async;
+#[cfg(feature = "wssession")]
 use tungstenite::protocol::{CloseFrame, Message, frame::coding::CloseCode};
 
 #[macro_export]
@@ -76,6 +79,9 @@ pub(crate) fn create_test_config(
             ws_base_url: web_socket_url.to_string(),
             events_path: web_socket_path.to_string(),
             reconnect_interval: 5,
+            session_timeout: 300,
+            idle_timeout: 0,
+            ping_interval: 0,
         },
     }
 }
@@ -112,7 +118,7 @@ where
 }
 
 #[bon::builder(finish_fn = create)]
-#[cfg(test)]
+#[cfg(feature = "wssession")]
 pub(crate) async fn mock_websocket_server(
     #[builder(with = |a: &'static str, p: u16| (a, p) )] address: (&str, u16),
     expected_request: MarketSessionPayload<'_>,
@@ -145,7 +151,7 @@ pub(crate) async fn mock_websocket_server(
 /// Scripted actions a mock WebSocket server can play against a client,
 /// in order. Designed for tests of the event-stream decoders under
 /// `wssession::market` and `wssession::account`.
-#[cfg(test)]
+#[cfg(feature = "wssession")]
 #[derive(Clone, Debug)]
 #[allow(clippy::enum_variant_names)]
 pub(crate) enum ScriptedWsAction {
@@ -155,6 +161,9 @@ pub(crate) enum ScriptedWsAction {
     SendPing(&'static [u8]),
     /// Send a `Message::Close` frame with `CloseCode::Normal`.
     SendClose,
+    /// Wait for the next client text frame and report it like the
+    /// subscription frame.
+    AwaitText,
 }
 
 /// Minimal scripted WebSocket server for event-stream tests.
@@ -166,67 +175,109 @@ pub(crate) enum ScriptedWsAction {
 /// - Replays `script` in order and then ends the task.
 ///
 /// Tests pick a port with [`free_tcp_port`] so they can run in parallel.
-#[cfg(test)]
+#[cfg(feature = "wssession")]
 pub(crate) async fn scripted_websocket_server<F>(
     address: (&'static str, u16),
     script: Vec<ScriptedWsAction>,
     on_subscription: F,
 ) where
     F: FnOnce(&str) + Send + 'static,
+{
+    let mut on_subscription = Some(on_subscription);
+    scripted_reconnecting_websocket_server(address, vec![script], move |_, msg| {
+        if let Some(f) = on_subscription.take() {
+            f(msg)
+        }
+    })
+    .await;
+}
+
+/// Scripted WebSocket server that accepts one connection per script.
+///
+/// Connections are served one after another on the same listener, so a
+/// client that reconnects lands on the next script. `on_text` receives the
+/// 0-based connection index and each client text frame read: the
+/// subscription frame and any frame consumed by
+/// [`ScriptedWsAction::AwaitText`].
+#[cfg(feature = "wssession")]
+pub(crate) async fn scripted_reconnecting_websocket_server<F>(
+    address: (&'static str, u16),
+    scripts: Vec<Vec<ScriptedWsAction>>,
+    mut on_text: F,
+) where
+    F: FnMut(usize, &str) + Send + 'static,
 {
     let listener = TcpListener::bind(address).await.expect("bind mock ws");
     tokio::spawn(async move {
-        let (stream, _) = listener.accept().await.expect("accept mock ws");
-        let mut websocket = accept_async(stream).await.expect("ws handshake");
+        for (connection, script) in scripts.into_iter().enumerate() {
+            let (stream, _) = listener.accept().await.expect("accept mock ws");
+            let mut websocket = accept_async(stream).await.expect("ws handshake");
+
+            // Drain the subscription frame.
+            match websocket.next().await {
+                Some(Ok(Message::Text(msg))) => on_text(connection, msg.as_ref()),
+                other => panic!("expected subscription text frame, got {other:?}"),
+            }
 
-        // Drain the subscription frame.
-        match websocket.next().await {
-            Some(Ok(Message::Text(msg))) => on_subscription(msg.as_ref()),
-            other => panic!("expected subscription text frame, got {other:?}"),
+            play_script(&mut websocket, script, |msg| on_text(connection, msg)).await;
         }
+    });
+}
 
-        for action in script {
-            match action {
-                ScriptedWsAction::SendText(payload) => {
-                    websocket
-                        .send(Message::Text(payload.into()))
-                        .await
-                        .expect("send text");
-                }
-                ScriptedWsAction::SendPing(payload) => {
-                    websocket
-                        .send(Message::Ping(payload.to_vec().into()))
-                        .await
-                        .expect("send ping");
-                    // Let tokio-tungstenite push the pong back. We
-                    // consume one frame so the peer's pong does not
-                    // show up as a surprise when we next read.
-                    match websocket.next().await {
-                        Some(Ok(Message::Pong(_))) => {}
-                        Some(Ok(other)) => panic!("expected pong, got {other:?}"),
-                        Some(Err(e)) => panic!("ws error waiting for pong: {e}"),
-                        None => panic!("connection closed waiting for pong"),
-                    }
-                }
-                ScriptedWsAction::SendClose => {
-                    websocket
-                        .close(Some(CloseFrame {
-                            code: CloseCode::Normal,
-                            reason: "done".into(),
-                        }))
-                        .await
-                        .expect("send close");
+#[cfg(feature = "wssession")]
+async fn play_script<S, F>(
+    websocket: &mut tokio_tungstenite::WebSocketStream<S>,
+    script: Vec<ScriptedWsAction>,
+    mut on_text: F,
+) where
+    S: tokio::io::AsyncRead + tokio::io::AsyncWrite + Unpin,
+    F: FnMut(&str),
+{
+    for action in script {
+        match action {
+            ScriptedWsAction::SendText(payload) => {
+                websocket
+                    .send(Message::Text(payload.into()))
+                    .await
+                    .expect("send text");
+            }
+            ScriptedWsAction::SendPing(payload) => {
+                websocket
+                    .send(Message::Ping(payload.to_vec().into()))
+                    .await
+                    .expect("send ping");
+                // Let tokio-tungstenite push the pong back. We
+                // consume one frame so the peer's pong does not
+                // show up as a surprise when we next read.
+                match websocket.next().await {
+                    Some(Ok(Message::Pong(_))) => {}
+                    Some(Ok(other)) => panic!("expected pong, got {other:?}"),
+                    Some(Err(e)) => panic!("ws error waiting for pong: {e}"),
+                    None => panic!("connection closed waiting for pong"),
                 }
             }
+            ScriptedWsAction::SendClose => {
+                websocket
+                    .close(Some(CloseFrame {
+                        code: CloseCode::Normal,
+                        reason: "done".into(),
+                    }))
+                    .await
+                    .expect("send close");
+            }
+            ScriptedWsAction::AwaitText => match websocket.next().await {
+                Some(Ok(Message::Text(msg))) => on_text(msg.as_ref()),
+                other => panic!("expected client text frame, got {other:?}"),
+            },
         }
-    });
+    }
 }
 
 /// Reserves an unused local TCP port by binding to `127.0.0.1:0` and
 /// dropping the listener. Good enough for hermetic tests — the window
 /// where the port could be reclaimed is tiny and the test owns the
 /// process.
-#[cfg(test)]
+#[cfg(feature = "wssession")]
 pub(crate) fn free_tcp_port() -> u16 {
     let listener = std::net::TcpListener::bind("127.0.0.1:0").expect("bind to ephemeral port");
     let port = listener.local_addr().expect("local addr").port();
@@ -12,13 +12,13 @@
 //!
 //! An `AccountSession` in this module wraps Tradier’s WebSocket session specifically for account-related events,
 //! ensuring efficient management of the WebSocket connection and enabling clients to receive streaming data
-//! continuously. The `SessionManager` is used to enforce a singleton pattern, allowing only one session at a time
-//! to prevent overlapping WebSocket connections.
+//! continuously. A `SessionManager` caps how many account sessions may be open at once; the slot is freed when
+//! the `AccountSession` is dropped.
 //!
 //! ## Usage
 //!
-//! To set up an account WebSocket session, initialize a `Config` with the necessary API credentials, and create
-//! a `SessionManager` to manage the session state. Then, instantiate an `AccountSession` to begin streaming
+//! To set up an account WebSocket session, initialize a `Config` with the necessary API credentials, then
+//! instantiate an `AccountSession` (or open one through your own `SessionManager`) to begin streaming
 //! account events from Tradier.
 //!
 //! ### Example
@@ -54,25 +54,39 @@
 //! The `AccountSession` creation will return an error in cases such as:
 //! - Missing or invalid API credentials
 //! - Network connectivity issues preventing WebSocket connection establishment
-//! - Attempting to create multiple sessions simultaneously, which is restricted by `SessionManager`
+//! - Exceeding the account session limit of the `SessionManager` in use
 //!
 //! For additional details on the API, refer to the [Tradier Account WebSocket documentation](https://documentation.tradier.com/brokerage-api/streaming/wss-account-websocket).
 
 use std::borrow::Cow;
 use std::fmt::{Display, Formatter};
+#[cfg(feature = "wssession")]
+use std::sync::Arc;
 
+#[cfg(feature = "wssession")]
+use futures_util::lock::Mutex;
+#[cfg(feature = "wssession")]
 use futures_util::stream::Stream;
+#[cfg(feature = "wssession")]
 use futures_util::{SinkExt, StreamExt};
 use serde::{Deserialize, Serialize};
+#[cfg(feature = "wssession")]
 use tokio_tungstenite::connect_async;
+#[cfg(feature = "wssession")]
 use tracing::{debug, info, trace, warn};
+#[cfg(feature = "wssession")]
 use tungstenite::Message;
+#[cfg(feature = "wssession")]
 use url::Url;
 
 use crate::Config;
+#[cfg(feature = "wssession")]
+use crate::utils::telemetry;
+#[cfg(feature = "wssession")]
 use crate::wssession::account_events::AccountEvent;
 use crate::wssession::session::{Session, SessionType};
 use crate::{Error, Result};
+use chrono::{DateTime, Utc};
 
 use self::session_manager::{GLOBAL_SESSION_MANAGER, SessionManager};
 
@@ -158,11 +172,22 @@ impl<'a> AccountSessionPayload<'a> {
         }
     }
 
+    /// Clones any borrowed data so the payload can outlive the events,
+    /// session id and excluded accounts it was built from.
+    pub fn into_owned(self) -> AccountSessionPayload<'static> {
+        AccountSessionPayload {
+            events: Cow::Owned(self.events.into_owned()),
+            session_id: Cow::Owned(self.session_id.into_owned()),
+            exclude_accounts: self.exclude_accounts.map(|a| Cow::Owned(a.into_owned())),
+        }
+    }
+
     /// Converts the payload to a WebSocket `Message` for sending.
     ///
     /// # Errors
     /// Returns [`Error::JsonParsingError`] if the payload cannot be
     /// serialized as JSON.
+    #[cfg(feature = "wssession")]
     pub fn get_message(&self) -> Result<Message> {
         serde_json::to_string(self)
             .map(|s| Message::Text(s.into()))
@@ -189,7 +214,7 @@ impl<'a> Display for AccountSessionPayload<'a> {
 /// establish and manage WebSocket connections for account-related data.
 ///
 /// For more details on the Tradier Account WebSocket API, see the official [documentation](https://documentation.tradier.com/brokerage-api/streaming/wss-account-websocket).
-#[derive(Debug, Clone)]
+#[derive(Debug)]
 pub struct AccountSession<'a>(Session<'a>);
 
 impl<'a> AccountSession<'a> {
@@ -226,8 +251,7 @@ impl<'a> AccountSession<'a> {
         Self::new_with_session_manager(config, &GLOBAL_SESSION_MANAGER).await
     }
 
-    #[allow(dead_code)]
-    async fn new_with_session_manager(
+    pub(super) async fn new_with_session_manager(
         config: &Config,
         session_manager: &'a SessionManager,
     ) -> Result<Self> {
@@ -294,6 +318,37 @@ impl<'a> AccountSession<'a> {
         self.0.get_websocket_url()
     }
 
+    /// Returns when the session id was issued.
+    pub fn created_at(&self) -> DateTime<Utc> {
+        self.0.created_at()
+    }
+
+    /// Returns when the session id goes stale: its creation time plus
+    /// [`crate::config::StreamingConfig::session_timeout`].
+    pub fn expires_at(&self) -> DateTime<Utc> {
+        self.0.expires_at()
+    }
+
+    /// Returns `true` once [`Self::expires_at`] has passed. Tradier rejects
+    /// stale session ids, so connect with a fresh one instead (see
+    /// [`Self::renew_if_expired`]).
+    pub fn is_expired(&self) -> bool {
+        self.0.is_expired()
+    }
+
+    /// Returns this session unchanged while its id is still fresh;
+    /// otherwise releases it and creates a new session through the same
+    /// `SessionManager`, so callers can always connect with a valid id.
+    ///
+    /// Build the subscription payload from the returned session's
+    /// [`Self::get_session_id`].
+    ///
+    /// # Errors
+    /// Same as [`Self::new`]. The stale session is released either way.
+    pub async fn renew_if_expired(self, config: &Config) -> Result<Self> {
+        Ok(Self(self.0.renew_if_expired(config).await?))
+    }
+
     /// Opens the account WebSocket and returns an async [`Stream`] of
     /// typed [`AccountEvent`] values.
     ///
@@ -317,10 +372,11 @@ impl<'a> AccountSession<'a> {
     ///
     /// After the stream is established, per-frame errors are yielded as
     /// `Err(_)` items of the stream rather than aborting early.
+    #[cfg(feature = "wssession")]
     pub async fn event_stream(
         &self,
         payload: AccountSessionPayload<'a>,
-    ) -> Result<impl Stream<Item = Result<AccountEvent>>> {
+    ) -> Result<impl Stream<Item = Result<AccountEvent>> + use<>> {
         let uri = self.0.get_websocket_url();
         let url = Url::parse(uri)?;
 
@@ -331,9 +387,12 @@ impl<'a> AccountSession<'a> {
         let message = payload.get_message()?;
         write.send(message).await.map_err(Box::new)?;
         debug!("sent account subscription payload");
+        telemetry::record_stream_connect(telemetry::TRANSPORT_WS);
 
         Ok(super::ws_decode::ws_event_stream(
             read,
+            Arc::new(Mutex::new(write)),
+            self.0.heartbeat(),
             AccountEvent::from_json,
         ))
     }
@@ -347,6 +406,7 @@ impl<'a> AccountSession<'a> {
     ///
     /// # Errors
     /// Same as [`Self::event_stream`].
+    #[cfg(feature = "wssession")]
     pub async fn ws_stream(&self, payload: AccountSessionPayload<'a>) -> Result<()> {
         let stream = self.event_stream(payload).await?;
         futures_util::pin_mut!(stream);
@@ -373,7 +433,7 @@ impl<'a> AccountSession<'a> {
     }
 }
 
-#[cfg(test)]
+#[cfg(all(test, feature = "wssession"))]
 mod tests {
     use mockito::Server;
 
@@ -17,15 +17,16 @@
 
 use serde::{Deserialize, Serialize};
 
+use super::events::has_known_tag;
 use crate::{Error, Result};
 
 /// An account event received from the Tradier WebSocket stream.
 ///
-/// Tagged on the upstream `event` field. Unknown variants are rejected
-/// with a deserialization error — callers that want
-/// forward-compatibility should deserialize into [`serde_json::Value`]
-/// first and then into [`AccountEvent`].
-#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+/// Tagged on the upstream `event` field. Objects with an `event` this
+/// crate does not know (new event kinds, heartbeats, status messages)
+/// decode to [`AccountEvent::Unknown`] instead of failing. A known `event`
+/// with a malformed body is still a decode error.
+#[derive(Clone, Debug, PartialEq, Serialize)]
 #[serde(tag = "event", rename_all = "lowercase")]
 #[non_exhaustive]
 pub enum AccountEvent {
@@ -42,6 +43,53 @@ pub enum AccountEvent {
     Trade(AccountTradeEvent),
     /// Session dropped / account unsubscribed.
     Drop(AccountDropEvent),
+    /// Any other message, kept verbatim.
+    #[serde(untagged)]
+    Unknown(serde_json::Value),
+}
+
+/// The [`AccountEvent`] variants with a known `event` tag.
+#[derive(Deserialize)]
+#[serde(tag = "event", rename_all = "lowercase")]
+enum KnownAccountEvent {
+    Order(AccountOrderEvent),
+    Fill(AccountFillEvent),
+    Position(AccountPositionEvent),
+    Balance(AccountBalanceEvent),
+    Trade(AccountTradeEvent),
+    Drop(AccountDropEvent),
+}
+
+/// `event` tags of [`KnownAccountEvent`].
+const KNOWN_ACCOUNT_EVENT_TYPES: [&str; 6] =
+    ["order", "fill", "position", "balance", "trade", "drop"];
+
+impl From<KnownAccountEvent> for AccountEvent {
+    fn from(event: KnownAccountEvent) -> Self {
+        match event {
+            KnownAccountEvent::Order(o) => AccountEvent::Order(o),
+            KnownAccountEvent::Fill(f) => AccountEvent::Fill(f),
+            KnownAccountEvent::Position(p) => AccountEvent::Position(p),
+            KnownAccountEvent::Balance(b) => AccountEvent::Balance(b),
+            KnownAccountEvent::Trade(t) => AccountEvent::Trade(t),
+            KnownAccountEvent::Drop(d) => AccountEvent::Drop(d),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for AccountEvent {
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let value = serde_json::Value::deserialize(deserializer)?;
+        if !has_known_tag(&value, "event", &KNOWN_ACCOUNT_EVENT_TYPES) {
+            return Ok(AccountEvent::Unknown(value));
+        }
+        KnownAccountEvent::deserialize(value)
+            .map(Into::into)
+            .map_err(serde::de::Error::custom)
+    }
 }
 
 /// Order lifecycle event.
@@ -288,18 +336,32 @@ impl AccountEvent {
             AccountEvent::Balance(b) => b.account_number.as_deref(),
             AccountEvent::Trade(t) => t.account_number.as_deref(),
             AccountEvent::Drop(d) => d.account_number.as_deref(),
+            AccountEvent::Unknown(value) => value
+                .get("account_number")
+                .and_then(serde_json::Value::as_str),
         }
     }
 
     /// Parses a single account event from a JSON line.
     ///
+    /// Valid JSON with an unrecognized `event` (or none) yields
+    /// [`AccountEvent::Unknown`].
+    ///
     /// # Errors
     /// Returns [`Error::StreamDecodeError`] with the offending payload
-    /// and the underlying `serde_json` error when the JSON does not
-    /// match any known variant.
+    /// and the underlying `serde_json` error when the line is not JSON or
+    /// a known event kind has a malformed body.
     pub fn from_json(line: &str) -> Result<Self> {
-        serde_json::from_str::<Self>(line)
-            .map_err(|e| Error::StreamDecodeError(line.to_owned(), e.to_string()))
+        let error = match serde_json::from_str::<KnownAccountEvent>(line) {
+            Ok(event) => return Ok(event.into()),
+            Err(e) => e,
+        };
+        match serde_json::from_str::<serde_json::Value>(line) {
+            Ok(value) if !has_known_tag(&value, "event", &KNOWN_ACCOUNT_EVENT_TYPES) => {
+                Ok(AccountEvent::Unknown(value))
+            }
+            _ => Err(Error::StreamDecodeError(line.to_owned(), error.to_string())),
+        }
     }
 }
 
@@ -372,9 +434,20 @@ mod tests {
     }
 
     #[test]
-    fn test_account_event_from_json_unknown_event_returns_decode_error() {
-        let line = r#"{"event":"unknown","id":1}"#;
-        let result = AccountEvent::from_json(line);
+    fn test_account_event_from_json_unknown_event_is_kept_verbatim() {
+        let line = r#"{"event":"heartbeat","account_number":"VA1234","id":1}"#;
+        let event = AccountEvent::from_json(line).expect("unknown events decode");
+        assert!(matches!(&event, AccountEvent::Unknown(value) if value["id"] == 1));
+        assert_eq!(event.account_number(), Some("VA1234"));
+        assert_eq!(
+            serde_json::to_value(&event).unwrap(),
+            serde_json::from_str::<serde_json::Value>(line).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_account_event_from_json_malformed_known_event_returns_decode_error() {
+        let result = AccountEvent::from_json(r#"{"event":"order","id":"not a number"}"#);
         assert!(matches!(result, Err(Error::StreamDecodeError(_, _))));
     }
 
@@ -0,0 +1,628 @@
+//! Real-time OHLCV bars built from trade prints.
+//!
+//! [`BarAggregator`] folds [`MarketEvent::Trade`] and
+//! [`MarketEvent::Timesale`] prints into per-symbol bars. Time bars are
+//! aligned to multiples of the interval since the Unix epoch (UTC), so a
+//! one-minute bar covers `hh:mm:00` to `hh:mm+1:00`, the same buckets
+//! as the minute bars of `GET /v1/markets/timesales`.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use futures_util::stream::{self, Stream, StreamExt};
+use tracing::debug;
+
+use super::events::{MarketEvent, TradeSession, parse_epoch_millis};
+use crate::Result;
+
+/// How prints are grouped into bars.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BarInterval {
+    /// Bars spanning a fixed wall-clock interval, e.g. one minute.
+    Time(Duration),
+    /// Bars of a fixed number of prints.
+    Ticks(u64),
+    /// Bars closing once their volume reaches the given number of shares.
+    /// The print that crosses the threshold belongs to the closing bar.
+    Volume(u64),
+}
+
+impl BarInterval {
+    /// A time bar of `n` seconds.
+    pub fn seconds(n: u64) -> Self {
+        BarInterval::Time(Duration::from_secs(n))
+    }
+
+    /// A time bar of `n` minutes.
+    pub fn minutes(n: u64) -> Self {
+        BarInterval::Time(Duration::from_secs(n * 60))
+    }
+}
+
+/// One OHLCV bar for a symbol.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Bar {
+    pub symbol: String,
+    /// Start of the bar. For time bars, the bucket start; otherwise the
+    /// time of the first print.
+    pub start: DateTime<Utc>,
+    /// End of the bar. For time bars, the exclusive bucket end; otherwise
+    /// the time of the last print.
+    pub end: DateTime<Utc>,
+    pub open: f64,
+    pub high: f64,
+    pub low: f64,
+    pub close: f64,
+    pub volume: u64,
+    /// Number of prints in the bar.
+    pub trades: u64,
+    /// Volume-weighted average price.
+    pub vwap: f64,
+}
+
+impl Bar {
+    fn open(symbol: &str, start: DateTime<Utc>, print: &Print) -> Self {
+        Bar {
+            symbol: symbol.to_owned(),
+            start,
+            end: print.time,
+            open: print.price,
+            high: print.price,
+            low: print.price,
+            close: print.price,
+            volume: 0,
+            trades: 0,
+            vwap: print.price,
+        }
+    }
+
+    fn add(&mut self, print: &Print) {
+        self.high = self.high.max(print.price);
+        self.low = self.low.min(print.price);
+        self.close = print.price;
+        let volume = self.volume + print.size;
+        if volume > 0 {
+            self.vwap =
+                (self.vwap * self.volume as f64 + print.price * print.size as f64) / volume as f64;
+        }
+        self.volume = volume;
+        self.trades += 1;
+    }
+}
+
+/// A single trade extracted from an event.
+struct Print {
+    price: f64,
+    size: u64,
+    time: DateTime<Utc>,
+}
+
+impl Print {
+    /// Returns `Ok(None)` for events that carry no countable trade.
+    fn from_event(event: &MarketEvent, sessions: Option<&[TradeSession]>) -> Result<Option<Self>> {
+        match event {
+            MarketEvent::Trade(trade) => Ok(Some(Print {
+                price: trade.price_f64()?,
+                size: trade.size_u64()?,
+                time: parse_epoch_millis(&trade.date)?,
+            })),
+            MarketEvent::Timesale(sale) => {
+                // Cancelled prints never happened and corrections restate an
+                // earlier print; counting either would double the volume.
+                if sale.cancel || sale.correction {
+                    debug!(symbol = %sale.symbol, seq = sale.seq, "skipping cancelled or corrected timesale");
+                    return Ok(None);
+                }
+                if sessions.is_some_and(|sessions| !sessions.contains(&sale.session)) {
+                    return Ok(None);
+                }
+                Ok(Some(Print {
+                    price: sale.last_f64()?,
+                    size: sale.size_u64()?,
+                    time: parse_epoch_millis(&sale.date)?,
+                }))
+            }
+            _ => Ok(None),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct State {
+    interval: BarInterval,
+    sessions: Option<Vec<TradeSession>>,
+    forming: HashMap<String, Bar>,
+    /// Start of the last time bar closed per symbol; prints at or before it
+    /// are late and dropped.
+    closed: HashMap<String, DateTime<Utc>>,
+}
+
+impl State {
+    fn push(&mut self, event: &MarketEvent) -> Result<Option<Bar>> {
+        let Some(print) = Print::from_event(event, self.sessions.as_deref())? else {
+            return Ok(None);
+        };
+        let symbol = event.symbol();
+        match self.interval {
+            BarInterval::Time(period) => Ok(self.push_timed(symbol, &print, period)),
+            BarInterval::Ticks(limit) => {
+                Ok(self.push_counted(symbol, &print, |bar| bar.trades >= limit))
+            }
+            BarInterval::Volume(limit) => {
+                Ok(self.push_counted(symbol, &print, |bar| bar.volume >= limit))
+            }
+        }
+    }
+
+    fn push_timed(&mut self, symbol: &str, print: &Print, period: Duration) -> Option<Bar> {
+        let period_ms = (period.as_millis() as i64).max(1);
+        let start_ms = print.time.timestamp_millis().div_euclid(period_ms) * period_ms;
+        let start = DateTime::from_timestamp_millis(start_ms).unwrap_or(print.time);
+        let end = DateTime::from_timestamp_millis(start_ms + period_ms).unwrap_or(print.time);
+
+        if self
+            .closed
+            .get(symbol)
+            .is_some_and(|closed| start <= *closed)
+        {
+            debug!(symbol, "skipping print for an already closed bar");
+            return None;
+        }
+        let mut completed = None;
+        if let Some(bar) = self.forming.get(symbol) {
+            if start < bar.start {
+                debug!(symbol, "skipping print older than the forming bar");
+                return None;
+            }
+            if start > bar.start {
+                completed = self.close(symbol);
+            }
+        }
+        let bar = self
+            .forming
+            .entry(symbol.to_owned())
+            .or_insert_with(|| Bar::open(symbol, start, print));
+        bar.add(print);
+        bar.end = end;
+        completed
+    }
+
+    /// Removes the forming bar of `symbol` and remembers its start so late
+    /// prints for it are dropped.
+    fn close(&mut self, symbol: &str) -> Option<Bar> {
+        let bar = self.forming.remove(symbol)?;
+        self.closed.insert(symbol.to_owned(), bar.start);
+        Some(bar)
+    }
+
+    fn push_counted(
+        &mut self,
+        symbol: &str,
+        print: &Print,
+        is_full: impl Fn(&Bar) -> bool,
+    ) -> Option<Bar> {
+        let bar = self
+            .forming
+            .entry(symbol.to_owned())
+            .or_insert_with(|| Bar::open(symbol, print.time, print));
+        bar.add(print);
+        bar.end = print.time;
+        if is_full(bar) {
+            self.forming.remove(symbol)
+        } else {
+            None
+        }
+    }
+}
+
+/// Aggregates trade prints into OHLCV bars per symbol.
+///
+/// Feed it events with [`Self::push`] or let [`Self::bars`] drive a whole
+/// event stream. Completed bars are returned / yielded as soon as they
+/// close; the bar still forming for a symbol is available at any time
+/// through [`Self::forming`]. The aggregator is a cheap handle: clones
+/// share the same bars, so one clone can drive the stream while another
+/// answers [`Self::forming`].
+///
+/// - Time bars close when the first print of a later bucket arrives, with
+///   [`Self::close_expired`], or on [`Self::bars`]' timer after the bucket
+///   ends; buckets without prints produce no bar.
+///   Prints older than the forming bar, or falling in a bucket that was
+///   already closed, are dropped so no bar is emitted twice.
+/// - [`crate::wssession::Timesale`] prints flagged `cancel` or `correction`
+///   are ignored, and `sessions` restricts time-and-sales prints to the
+///   given [`TradeSession`]s. [`crate::wssession::Trade`] events carry no
+///   session and are always counted.
+/// - Subscribe to either `trade` or `timesale` events, not both, or every
+///   print is counted twice.
+///
+/// # Example
+/// ```no_run
+/// use futures_util::StreamExt;
+/// use tradier::Config;
+/// use tradier::wssession::{
+///     BarAggregator, BarInterval, MarketSession, MarketSessionFilter, MarketSessionPayload,
+///     TradeSession,
+/// };
+///
+/// # async fn run() -> tradier::Result<()> {
+/// let config = Config::new();
+/// let session = MarketSession::new(&config).await?;
+/// let symbols = vec!["SPY".to_string()];
+/// let filters = [MarketSessionFilter::TIMESALE];
+/// let payload = MarketSessionPayload::builder()
+///     .symbols(&symbols)
+///     .filters(&filters)
+///     .session_id(session.get_session_id())
+///     .build();
+/// let events = session.event_stream(payload).await?;
+///
+/// let bars = BarAggregator::builder()
+///     .interval(BarInterval::minutes(1))
+///     .sessions(vec![TradeSession::Normal])
+///     .build();
+/// let completed = bars.bars(events);
+/// futures_util::pin_mut!(completed);
+/// while let Some(bar) = completed.next().await {
+///     let bar = bar?;
+///     println!("{} {} o={} c={} v={}", bar.symbol, bar.start, bar.open, bar.close, bar.volume);
+///     println!("forming: {:?}", bars.forming("SPY"));
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct BarAggregator {
+    state: Arc<Mutex<State>>,
+}
+
+#[bon::bon]
+impl BarAggregator {
+    /// Constructs a new `BarAggregator`.
+    ///
+    /// # Arguments
+    /// - `interval`: How prints are grouped into bars.
+    /// - `sessions`: Trading sessions whose time-and-sales prints are
+    ///   counted. Defaults to all sessions.
+    #[builder(builder_type(vis = "pub"))]
+    fn new(interval: BarInterval, sessions: Option<Vec<TradeSession>>) -> Self {
+        BarAggregator {
+            state: Arc::new(Mutex::new(State {
+                interval,
+                sessions,
+                forming: HashMap::new(),
+                closed: HashMap::new(),
+            })),
+        }
+    }
+
+    fn state(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Adds the print carried by `event`, if any.
+    ///
+    /// # Returns
+    /// - `Ok(Some(bar))` when the print closed a bar.
+    /// - `Ok(None)` otherwise, including for events that are not trades.
+    ///
+    /// # Errors
+    /// - [`Error::ParseFloat`] / [`Error::ParseInt`] if the print's price,
+    ///   size or timestamp is malformed. The print is skipped.
+    pub fn push(&self, event: &MarketEvent) -> Result<Option<Bar>> {
+        self.state().push(event)
+    }
+
+    /// Returns the bar still forming for `symbol`.
+    pub fn forming(&self, symbol: &str) -> Option<Bar> {
+        self.state().forming.get(symbol).cloned()
+    }
+
+    /// Closes and returns the time bars whose bucket ended at or before
+    /// `now`, for symbols that have not printed since. Does nothing for
+    /// tick and volume bars. Later prints that fall in a closed bucket are
+    /// dropped.
+    pub fn close_expired(&self, now: DateTime<Utc>) -> Vec<Bar> {
+        let mut state = self.state();
+        if !matches!(state.interval, BarInterval::Time(_)) {
+            return Vec::new();
+        }
+        let expired: Vec<String> = state
+            .forming
+            .iter()
+            .filter(|(_, bar)| bar.end <= now)
+            .map(|(symbol, _)| symbol.clone())
+            .collect();
+        let mut bars: Vec<Bar> = expired
+            .iter()
+            .filter_map(|symbol| state.close(symbol))
+            .collect();
+        bars.sort_by(|a, b| a.start.cmp(&b.start).then_with(|| a.symbol.cmp(&b.symbol)));
+        bars
+    }
+
+    /// Removes and returns every forming bar, e.g. at the end of a session.
+    /// For time bars, later prints that fall in a flushed bucket are dropped.
+    pub fn flush(&self) -> Vec<Bar> {
+        let mut state = self.state();
+        let symbols: Vec<String> = state.forming.keys().cloned().collect();
+        let mut bars: Vec<Bar> = symbols
+            .iter()
+            .filter_map(|symbol| state.close(symbol))
+            .collect();
+        bars.sort_by(|a, b| a.start.cmp(&b.start).then_with(|| a.symbol.cmp(&b.symbol)));
+        bars
+    }
+
+    /// Drives `events` and yields every completed bar.
+    ///
+    /// Time bars are also closed on a timer: shortly after each bucket
+    /// boundary, bars whose bucket has ended are yielded through
+    /// [`Self::close_expired`], so a quiet symbol's bar is not held back
+    /// until its next print. The timer runs on Tokio's clock, offset to
+    /// wall-clock time when the stream starts, so it suits live events; to
+    /// aggregate a replay of older prints, use [`Self::push`] instead.
+    ///
+    /// Upstream errors and malformed prints are yielded as `Err` items
+    /// without ending the stream. When `events` ends, the bars still
+    /// forming are flushed and yielded.
+    pub fn bars<S>(&self, events: S) -> impl Stream<Item = Result<Bar>> + use<S>
+    where
+        S: Stream<Item = Result<MarketEvent>>,
+    {
+        let aggregator = self.clone();
+        let period = match self.state().interval {
+            BarInterval::Time(period) => Some(period.max(Duration::from_millis(1))),
+            BarInterval::Ticks(_) | BarInterval::Volume(_) => None,
+        };
+        let clock = WallClock::start();
+        // Each item is the batch of bars to yield; `None` marks the end of
+        // `events`, which also stops the timer.
+        let expired = {
+            let aggregator = aggregator.clone();
+            stream::unfold(
+                period.map(|period| clock.bucket_timer(period)),
+                move |timer| {
+                    let aggregator = aggregator.clone();
+                    async move {
+                        let Some(mut timer) = timer else {
+                            return std::future::pending().await;
+                        };
+                        timer.tick().await;
+                        let bars: Vec<Result<Bar>> = aggregator
+                            .close_expired(clock.now())
+                            .into_iter()
+                            .map(Ok)
+                            .collect();
+                        Some((Some(bars), Some(timer)))
+                    }
+                },
+            )
+        };
+        let pushed = {
+            let aggregator = aggregator.clone();
+            events
+                .map(move |item| {
+                    let bar = item.and_then(|event| aggregator.push(&event)).transpose();
+                    Some(bar.into_iter().collect::<Vec<_>>())
+                })
+                .chain(stream::once(async { None }))
+        };
+        let completed = stream::select(pushed, expired)
+            .take_while(|bars| std::future::ready(bars.is_some()))
+            .flat_map(|bars| stream::iter(bars.unwrap_or_default()));
+        let flushed =
+            stream::once(async move { stream::iter(aggregator.flush().into_iter().map(Ok)) })
+                .flatten();
+        completed.chain(flushed)
+    }
+}
+
+/// Longest delay after a bucket boundary before [`BarAggregator::bars`]
+/// closes the expired bars, leaving prints from the ending bucket time to
+/// arrive.
+const CLOSE_GRACE: Duration = Duration::from_secs(1);
+
+/// Wall-clock time derived from Tokio's clock, so bar timers follow a
+/// paused test clock as well as real time.
+#[derive(Debug, Clone, Copy)]
+struct WallClock {
+    wall: DateTime<Utc>,
+    instant: tokio::time::Instant,
+}
+
+impl WallClock {
+    fn start() -> Self {
+        WallClock {
+            wall: Utc::now(),
+            instant: tokio::time::Instant::now(),
+        }
+    }
+
+    fn now(&self) -> DateTime<Utc> {
+        let elapsed = chrono::TimeDelta::from_std(self.instant.elapsed()).unwrap_or_default();
+        self.wall + elapsed
+    }
+
+    /// Returns a timer firing a little after every `period` boundary.
+    fn bucket_timer(&self, period: Duration) -> tokio::time::Interval {
+        let period_ms = (period.as_millis() as i64).max(1);
+        let now_ms = self.now().timestamp_millis();
+        let next_boundary = (now_ms.div_euclid(period_ms) + 1) * period_ms;
+        let grace = (period / 10).min(CLOSE_GRACE);
+        let first = Duration::from_millis((next_boundary - now_ms) as u64) + grace;
+        let mut timer = tokio::time::interval_at(tokio::time::Instant::now() + first, period);
+        timer.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        timer
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Error;
+
+    fn trade(symbol: &str, price: f64, size: u64, millis: i64) -> MarketEvent {
+        MarketEvent::from_json(&format!(
+            r#"{{"type":"trade","symbol":"{symbol}","exch":"Q","price":"{price}","size":"{size}","cvol":"0","date":"{millis}","last":"{price}"}}"#
+        ))
+        .unwrap()
+    }
+
+    fn timesale(price: f64, size: u64, millis: i64, flags: &str, session: &str) -> MarketEvent {
+        MarketEvent::from_json(&format!(
+            r#"{{"type":"timesale","symbol":"SPY","exch":"Q","bid":"0","ask":"0","last":"{price}","size":"{size}","date":"{millis}","seq":1,"flag":"","cancel":{cancel},"correction":{correction},"session":"{session}"}}"#,
+            cancel = flags.contains("cancel"),
+            correction = flags.contains("correction"),
+        ))
+        .unwrap()
+    }
+
+    const MINUTE: i64 = 60_000;
+    const T0: i64 = 1_700_000_040_000; // aligned to a minute
+
+    #[tokio::test]
+    async fn test_time_bars_close_on_next_bucket_and_flush_at_end() {
+        let aggregator = BarAggregator::builder()
+            .interval(BarInterval::minutes(1))
+            .build();
+        let events = stream::iter(vec![
+            Ok(trade("SPY", 10.0, 100, T0 + 1_000)),
+            Ok(trade("SPY", 12.0, 100, T0 + 20_000)),
+            Ok(trade("SPY", 9.0, 200, T0 + 59_999)),
+            Ok(trade("SPY", 11.0, 10, T0 + 2 * MINUTE + 1)),
+            Ok(trade("SPY", 11.0, 50, T0 + 5_000)), // bucket already closed, dropped
+        ]);
+        let bars: Vec<Bar> = aggregator
+            .bars(events)
+            .map(|bar| bar.unwrap())
+            .collect()
+            .await;
+
+        assert_eq!(bars.len(), 2);
+        let first = &bars[0];
+        assert_eq!(first.start.timestamp_millis(), T0);
+        assert_eq!(first.end.timestamp_millis(), T0 + MINUTE);
+        assert_eq!(
+            (first.open, first.high, first.low, first.close),
+            (10.0, 12.0, 9.0, 9.0)
+        );
+        assert_eq!((first.volume, first.trades), (400, 3));
+        assert!((first.vwap - 10.0).abs() < 1e-9);
+        assert_eq!(bars[1].start.timestamp_millis(), T0 + 2 * MINUTE);
+        assert!(aggregator.forming("SPY").is_none());
+    }
+
+    #[test]
+    fn test_timesale_flags_and_sessions_are_respected() {
+        let aggregator = BarAggregator::builder()
+            .interval(BarInterval::Ticks(2))
+            .sessions(vec![TradeSession::Normal])
+            .build();
+        for event in [
+            timesale(10.0, 100, T0, "cancel", "normal"),
+            timesale(10.5, 100, T0, "correction", "normal"),
+            timesale(11.0, 100, T0, "", "pre"),
+            timesale(12.0, 100, T0 + 1, "", "normal"),
+        ] {
+            assert!(aggregator.push(&event).unwrap().is_none());
+        }
+        let forming = aggregator.forming("SPY").unwrap();
+        assert_eq!((forming.open, forming.trades), (12.0, 1));
+
+        let bar = aggregator
+            .push(&timesale(13.0, 50, T0 + 2, "", "normal"))
+            .unwrap()
+            .expect("second print closes the tick bar");
+        assert_eq!((bar.open, bar.close, bar.volume), (12.0, 13.0, 150));
+        assert!(aggregator.forming("SPY").is_none());
+    }
+
+    #[test]
+    fn test_volume_bars_and_close_expired() {
+        let volume = BarAggregator::builder()
+            .interval(BarInterval::Volume(300))
+            .build();
+        assert!(volume.push(&trade("A", 1.0, 200, T0)).unwrap().is_none());
+        let bar = volume.push(&trade("A", 2.0, 150, T0 + 1)).unwrap().unwrap();
+        assert_eq!((bar.volume, bar.trades), (350, 2));
+
+        let timed = BarAggregator::builder()
+            .interval(BarInterval::seconds(1))
+            .build();
+        timed.push(&trade("A", 1.0, 1, T0)).unwrap();
+        timed.push(&trade("B", 1.0, 1, T0 + 1_500)).unwrap();
+        let at = DateTime::from_timestamp_millis(T0 + 1_000).unwrap();
+        let closed = timed.close_expired(at);
+        assert_eq!(closed.len(), 1);
+        assert_eq!(closed[0].symbol, "A");
+        assert!(timed.forming("B").is_some());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_time_bars_close_on_timer_without_a_later_print() {
+        let aggregator = BarAggregator::builder()
+            .interval(BarInterval::minutes(1))
+            .build();
+        let now = Utc::now().timestamp_millis();
+        let events = stream::iter(vec![Ok(trade("SPY", 10.0, 100, now))]).chain(stream::pending());
+        let bars = aggregator.bars(events);
+        futures_util::pin_mut!(bars);
+
+        let bar = tokio::time::timeout(Duration::from_secs(120), bars.next())
+            .await
+            .expect("the timer closes the bar")
+            .unwrap()
+            .unwrap();
+        assert_eq!(bar.symbol, "SPY");
+        assert_eq!(bar.start.timestamp_millis(), now - now.rem_euclid(MINUTE));
+        assert!(aggregator.forming("SPY").is_none());
+    }
+
+    #[test]
+    fn test_late_print_after_close_expired_emits_no_bar() {
+        let aggregator = BarAggregator::builder()
+            .interval(BarInterval::minutes(1))
+            .build();
+        aggregator
+            .push(&trade("SPY", 10.0, 100, T0 + 1_000))
+            .unwrap();
+        let at = DateTime::from_timestamp_millis(T0 + MINUTE).unwrap();
+        assert_eq!(aggregator.close_expired(at).len(), 1);
+
+        let late = aggregator
+            .push(&trade("SPY", 9.0, 50, T0 + 30_000))
+            .unwrap();
+        assert!(late.is_none());
+        assert!(aggregator.forming("SPY").is_none());
+        assert!(aggregator.flush().is_empty());
+
+        aggregator
+            .push(&trade("SPY", 11.0, 10, T0 + MINUTE))
+            .unwrap();
+        let flushed = aggregator.flush();
+        assert_eq!(flushed.len(), 1);
+        assert_eq!(flushed[0].start.timestamp_millis(), T0 + MINUTE);
+        assert!(
+            aggregator
+                .push(&trade("SPY", 12.0, 10, T0 + MINUTE + 1))
+                .unwrap()
+                .is_none()
+        );
+        assert!(aggregator.forming("SPY").is_none());
+    }
+
+    #[test]
+    fn test_malformed_print_is_an_error() {
+        let aggregator = BarAggregator::builder()
+            .interval(BarInterval::Ticks(1))
+            .build();
+        let bad = MarketEvent::from_json(
+            r#"{"type":"trade","symbol":"SPY","exch":"Q","price":"x","size":"1","cvol":"0","date":"1","last":"x"}"#,
+        )
+        .unwrap();
+        assert!(matches!(aggregator.push(&bad), Err(Error::ParseFloat(..))));
+    }
+}
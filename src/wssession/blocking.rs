@@ -0,0 +1,440 @@
+//! Blocking iterators over the streaming APIs.
+//!
+//! The synchronous counterpart of the async streams, for tools built on
+//! [`crate::blocking::Client`] that do not run a Tokio runtime:
+//!
+//! - [`BlockingMarketSession`] / [`BlockingAccountSession`] wrap the
+//!   WebSocket sessions.
+//! - [`market_events`] / [`account_events`] wrap
+//!   [`crate::streaming::http_stream`].
+//!
+//! Each returns an [`EventIter`], a plain [`Iterator`] that drives the
+//! underlying stream on a private single-thread runtime while `next` is
+//! called. [`EventIter::stop_handle`] returns a [`StopHandle`] that ends
+//! the iteration from any thread, waking a `next` that is waiting for the
+//! next event.
+//!
+//! Like the blocking REST client, none of these may be created or used
+//! inside a Tokio runtime.
+
+use std::fmt;
+use std::sync::Arc;
+
+use futures_util::future::{Either, select};
+use futures_util::stream::{BoxStream, Stream, StreamExt};
+use tokio::runtime::Runtime;
+use tokio::sync::watch;
+
+#[cfg(feature = "wssession")]
+use chrono::{DateTime, Utc};
+
+#[cfg(feature = "wssession")]
+use crate::Config;
+use crate::Result;
+#[cfg(feature = "streaming")]
+use crate::client::blocking::BlockingTradierRestClient;
+use crate::wssession::{AccountEvent, MarketEvent};
+#[cfg(feature = "wssession")]
+use crate::wssession::{
+    AccountSession, AccountSessionPayload, MarketSession, MarketSessionPayload, ReconnectPolicy,
+    SupervisedMarketEvent,
+};
+#[cfg(feature = "streaming")]
+use crate::wssession::{AccountSessionEvent, MarketSessionFilter};
+
+/// Stops an [`EventIter`] from another thread.
+#[derive(Debug, Clone)]
+pub struct StopHandle(Arc<watch::Sender<bool>>);
+
+impl StopHandle {
+    /// Ends the iteration. A pending `next` returns `None` right away and
+    /// the underlying connection is closed.
+    pub fn stop(&self) {
+        self.0.send_replace(true);
+    }
+
+    /// Returns `true` once [`Self::stop`] has been called.
+    pub fn is_stopped(&self) -> bool {
+        *self.0.borrow()
+    }
+}
+
+/// A blocking [`Iterator`] over a streaming API.
+///
+/// Iteration ends when the upstream stream does or when a [`StopHandle`]
+/// is triggered. Items are the same `Result`s the async stream yields, so
+/// decode failures do not end the iteration.
+pub struct EventIter<T> {
+    runtime: Arc<Runtime>,
+    events: Option<BoxStream<'static, Result<T>>>,
+    stop: StopHandle,
+    stopped: watch::Receiver<bool>,
+}
+
+impl<T> EventIter<T> {
+    fn new<S>(runtime: Arc<Runtime>, events: S) -> Self
+    where
+        S: Stream<Item = Result<T>> + Send + 'static,
+    {
+        let (sender, stopped) = watch::channel(false);
+        Self {
+            runtime,
+            events: Some(events.boxed()),
+            stop: StopHandle(Arc::new(sender)),
+            stopped,
+        }
+    }
+
+    /// Returns a handle that stops this iterator; it can be sent to other
+    /// threads.
+    pub fn stop_handle(&self) -> StopHandle {
+        self.stop.clone()
+    }
+}
+
+impl<T> Iterator for EventIter<T> {
+    type Item = Result<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let events = self.events.as_mut()?;
+        let stopped = self.stopped.wait_for(|stopped| *stopped);
+        let item = self.runtime.block_on(async {
+            futures_util::pin_mut!(stopped);
+            match select(events.next(), stopped).await {
+                Either::Left((item, _)) => item,
+                Either::Right(_) => None,
+            }
+        });
+        if item.is_none() {
+            // Drop the stream to close the connection.
+            self.events = None;
+        }
+        item
+    }
+}
+
+impl<T> fmt::Debug for EventIter<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EventIter")
+            .field("finished", &self.events.is_none())
+            .field("stopped", &self.stop.is_stopped())
+            .finish_non_exhaustive()
+    }
+}
+
+/// Builds the private runtime of a blocking session.
+#[cfg(feature = "wssession")]
+fn new_runtime() -> Result<Arc<Runtime>> {
+    if tokio::runtime::Handle::try_current().is_ok() {
+        return Err(crate::Error::BlockingClientInsideAsyncRuntime);
+    }
+    Ok(Arc::new(
+        tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()?,
+    ))
+}
+
+/// A blocking [`MarketSession`].
+///
+/// # Example
+/// ```no_run
+/// use std::time::Duration;
+/// use tradier::Config;
+/// use tradier::wssession::MarketSessionPayload;
+/// use tradier::wssession::blocking::BlockingMarketSession;
+///
+/// fn main() -> tradier::Result<()> {
+///     let config = Config::new();
+///     let session = BlockingMarketSession::new(&config)?;
+///     let symbols = vec!["SPY".to_string()];
+///     let payload = MarketSessionPayload::recommended(&symbols, session.get_session_id());
+///     let events = session.event_stream(payload)?;
+///
+///     let stop = events.stop_handle();
+///     std::thread::spawn(move || {
+///         std::thread::sleep(Duration::from_secs(60));
+///         stop.stop();
+///     });
+///     for event in events {
+///         println!("{:?}", event?);
+///     }
+///     Ok(())
+/// }
+/// ```
+#[cfg(feature = "wssession")]
+pub struct BlockingMarketSession {
+    session: MarketSession<'static>,
+    runtime: Arc<Runtime>,
+}
+
+#[cfg(feature = "wssession")]
+impl BlockingMarketSession {
+    /// Creates a market session, blocking until Tradier has issued the
+    /// session id.
+    ///
+    /// # Errors
+    /// - [`crate::Error::BlockingClientInsideAsyncRuntime`] if called
+    ///   inside a Tokio runtime.
+    /// - Same as [`MarketSession::new`].
+    pub fn new(config: &Config) -> Result<Self> {
+        let runtime = new_runtime()?;
+        let session = runtime.block_on(MarketSession::new(config))?;
+        Ok(Self { session, runtime })
+    }
+
+    /// Retrieves the session ID.
+    pub fn get_session_id(&self) -> &str {
+        self.session.get_session_id()
+    }
+
+    /// Retrieves the WebSocket URL.
+    pub fn get_websocket_url(&self) -> &str {
+        self.session.get_websocket_url()
+    }
+
+    /// Returns when the session id was issued.
+    pub fn created_at(&self) -> DateTime<Utc> {
+        self.session.created_at()
+    }
+
+    /// Returns when the session id goes stale.
+    pub fn expires_at(&self) -> DateTime<Utc> {
+        self.session.expires_at()
+    }
+
+    /// Returns `true` once [`Self::expires_at`] has passed.
+    pub fn is_expired(&self) -> bool {
+        self.session.is_expired()
+    }
+
+    /// Opens the market WebSocket and returns a blocking iterator over
+    /// its events. See [`MarketSession::event_stream`].
+    ///
+    /// # Errors
+    /// Same as [`MarketSession::event_stream`].
+    pub fn event_stream(
+        &self,
+        payload: MarketSessionPayload<'_>,
+    ) -> Result<EventIter<MarketEvent>> {
+        let events = self
+            .runtime
+            .block_on(self.session.event_stream(payload.into_owned()))?;
+        Ok(EventIter::new(self.runtime.clone(), events))
+    }
+
+    /// Consumes the session and returns a blocking iterator that
+    /// reconnects on its own. See [`MarketSession::supervised_event_stream`].
+    pub fn supervised_event_stream(
+        self,
+        config: &Config,
+        payload: MarketSessionPayload<'_>,
+        policy: ReconnectPolicy,
+    ) -> EventIter<SupervisedMarketEvent> {
+        let events = self
+            .session
+            .supervised_event_stream(config, payload, policy);
+        EventIter::new(self.runtime, events)
+    }
+}
+
+#[cfg(feature = "wssession")]
+impl fmt::Debug for BlockingMarketSession {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BlockingMarketSession")
+            .field("session_id", &self.get_session_id())
+            .finish_non_exhaustive()
+    }
+}
+
+/// A blocking [`AccountSession`].
+#[cfg(feature = "wssession")]
+#[derive(Debug)]
+pub struct BlockingAccountSession {
+    session: AccountSession<'static>,
+    runtime: Arc<Runtime>,
+}
+
+#[cfg(feature = "wssession")]
+impl BlockingAccountSession {
+    /// Creates an account session, blocking until Tradier has issued the
+    /// session id.
+    ///
+    /// # Errors
+    /// - [`crate::Error::BlockingClientInsideAsyncRuntime`] if called
+    ///   inside a Tokio runtime.
+    /// - Same as [`AccountSession::new`].
+    pub fn new(config: &Config) -> Result<Self> {
+        let runtime = new_runtime()?;
+        let session = runtime.block_on(AccountSession::new(config))?;
+        Ok(Self { session, runtime })
+    }
+
+    /// Retrieves the session ID.
+    pub fn get_session_id(&self) -> &str {
+        self.session.get_session_id()
+    }
+
+    /// Retrieves the WebSocket URL.
+    pub fn get_websocket_url(&self) -> &str {
+        self.session.get_websocket_url()
+    }
+
+    /// Returns when the session id was issued.
+    pub fn created_at(&self) -> DateTime<Utc> {
+        self.session.created_at()
+    }
+
+    /// Returns when the session id goes stale.
+    pub fn expires_at(&self) -> DateTime<Utc> {
+        self.session.expires_at()
+    }
+
+    /// Returns `true` once [`Self::expires_at`] has passed.
+    pub fn is_expired(&self) -> bool {
+        self.session.is_expired()
+    }
+
+    /// Opens the account WebSocket and returns a blocking iterator over
+    /// its events. See [`AccountSession::event_stream`].
+    ///
+    /// # Errors
+    /// Same as [`AccountSession::event_stream`].
+    pub fn event_stream(
+        &self,
+        payload: AccountSessionPayload<'_>,
+    ) -> Result<EventIter<AccountEvent>> {
+        let events = self
+            .runtime
+            .block_on(self.session.event_stream(payload.into_owned()))?;
+        Ok(EventIter::new(self.runtime.clone(), events))
+    }
+}
+
+/// Blocking [`crate::streaming::http_stream::market_events`], driven by
+/// `client`'s runtime.
+///
+/// # Errors
+/// Same as [`crate::streaming::http_stream::market_events`].
+#[cfg(feature = "streaming")]
+pub fn market_events(
+    client: &BlockingTradierRestClient,
+    session_id: &str,
+    symbols: &[String],
+    filters: Option<&[MarketSessionFilter]>,
+    linebreak: Option<bool>,
+    valid_only: Option<bool>,
+    advanced_details: Option<bool>,
+) -> Result<EventIter<MarketEvent>> {
+    let events = client
+        .runtime()
+        .block_on(crate::streaming::http_stream::market_events(
+            client.rest_client(),
+            session_id,
+            symbols,
+            filters,
+            linebreak,
+            valid_only,
+            advanced_details,
+        ))?;
+    Ok(EventIter::new(client.runtime().clone(), events))
+}
+
+/// Blocking [`crate::streaming::http_stream::account_events`], driven by
+/// `client`'s runtime.
+///
+/// # Errors
+/// Same as [`crate::streaming::http_stream::account_events`].
+#[cfg(feature = "streaming")]
+pub fn account_events(
+    client: &BlockingTradierRestClient,
+    session_id: &str,
+    events: Option<&[AccountSessionEvent]>,
+    exclude_accounts: Option<&[String]>,
+) -> Result<EventIter<AccountEvent>> {
+    let events = client
+        .runtime()
+        .block_on(crate::streaming::http_stream::account_events(
+            client.rest_client(),
+            session_id,
+            events,
+            exclude_accounts,
+        ))?;
+    Ok(EventIter::new(client.runtime().clone(), events))
+}
+
+#[cfg(all(test, feature = "streaming"))]
+mod tests {
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+    use std::time::Duration;
+
+    use httpmock::prelude::*;
+
+    use super::*;
+    use crate::utils::tests::create_test_config;
+
+    const QUOTE: &str = r#"{"type":"quote","symbol":"SPY","bid":1.0,"bidsz":1,"bidexch":"Q","biddate":"1","ask":2.0,"asksz":1,"askexch":"Q","askdate":"1"}"#;
+
+    fn client(stream_url: &str) -> BlockingTradierRestClient {
+        let mut config = create_test_config().server_url(stream_url).finish();
+        config.streaming.http_base_url = stream_url.to_owned();
+        BlockingTradierRestClient::new(config).unwrap()
+    }
+
+    #[test]
+    fn test_market_events_iterates_until_the_body_ends() {
+        let server = MockServer::start();
+        server.mock(|when, then| {
+            when.method(GET).path("/v1/markets/events");
+            then.status(200)
+                .body(format!("{QUOTE}\nnot-json\n{QUOTE}\n"));
+        });
+        let client = client(&server.base_url());
+        let symbols = ["SPY".to_string()];
+        let events: Vec<Result<MarketEvent>> =
+            market_events(&client, "sid", &symbols, None, None, None, None)
+                .unwrap()
+                .collect();
+        assert_eq!(events.len(), 3, "got {events:?}");
+        assert!(matches!(events[0], Ok(MarketEvent::Quote(_))));
+        assert!(matches!(
+            events[1],
+            Err(crate::Error::StreamDecodeError(..))
+        ));
+        assert!(matches!(events[2], Ok(MarketEvent::Quote(_))));
+    }
+
+    #[test]
+    fn test_stop_handle_wakes_a_pending_next_from_another_thread() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        std::thread::spawn(move || {
+            let (mut socket, _) = listener.accept().unwrap();
+            let _ = socket.read(&mut [0; 4096]).unwrap();
+            let line = format!("{QUOTE}\n");
+            write!(
+                socket,
+                "HTTP/1.1 200 OK\r\ntransfer-encoding: chunked\r\n\r\n{:x}\r\n{line}\r\n",
+                line.len()
+            )
+            .unwrap();
+            // Keep the body open without sending anything else.
+            std::thread::sleep(Duration::from_secs(30));
+        });
+
+        let client = client(&url);
+        let symbols = ["SPY".to_string()];
+        let mut events = market_events(&client, "sid", &symbols, None, None, None, None).unwrap();
+        assert!(matches!(events.next(), Some(Ok(MarketEvent::Quote(_)))));
+
+        let stop = events.stop_handle();
+        std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(50));
+            stop.stop();
+        });
+        assert!(events.next().is_none());
+        assert!(events.stop_handle().is_stopped());
+        assert!(events.next().is_none());
+    }
+}
@@ -9,21 +9,33 @@
 //! (trade / timesale / tradex prices and sizes) — the types below mirror
 //! the upstream shape exactly, and helper methods (e.g. [`Trade::price_f64`])
 //! parse strings to numbers at the call site rather than silently papering
-//! over the mismatch.
+//! over the mismatch. [`MarketEvent::parse`] converts a whole event into its
+//! [`crate::wssession::parsed_events`] counterpart in one step.
+//!
+//! With the `decimal` feature enabled every price carries a matching
+//! `*_decimal()` accessor (e.g. [`Trade::price_decimal`]) that parses the
+//! upstream string straight into a `rust_decimal::Decimal`, so reconciliation
+//! code never round-trips through `f64`.
 
 use std::str::FromStr;
 
+use chrono::{DateTime, Utc};
+
 use serde::{Deserialize, Serialize};
 
+use super::market::MarketSessionFilter;
+#[cfg(feature = "decimal")]
+use crate::utils::decimal::parse_decimal;
 use crate::{Error, Result};
 
 /// A market event received from the Tradier WebSocket stream.
 ///
-/// This enum is tagged by the upstream `type` field. Unknown variants
-/// are rejected with a deserialization error — callers that want
-/// forward-compatibility should deserialize into [`serde_json::Value`]
-/// first and then into [`MarketEvent`].
-#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+/// This enum is tagged by the upstream `type` field. Objects with a `type`
+/// this crate does not know (new event types, heartbeats, status
+/// messages) decode to [`MarketEvent::Unknown`] instead of failing, so
+/// they never surface as decode errors. A known `type` with a malformed
+/// body is still a decode error.
+#[derive(Clone, Debug, PartialEq, Serialize)]
 #[serde(tag = "type", rename_all = "lowercase")]
 #[non_exhaustive]
 pub enum MarketEvent {
@@ -37,6 +49,59 @@ pub enum MarketEvent {
     Timesale(Timesale),
     /// Extended-hours / off-exchange trade.
     Tradex(Tradex),
+    /// Any other message, kept verbatim.
+    #[serde(untagged)]
+    Unknown(serde_json::Value),
+}
+
+/// The [`MarketEvent`] variants with a known `type` tag.
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+enum KnownMarketEvent {
+    Quote(Quote),
+    Trade(Trade),
+    Summary(Summary),
+    Timesale(Timesale),
+    Tradex(Tradex),
+}
+
+/// `type` tags of [`KnownMarketEvent`].
+const KNOWN_MARKET_EVENT_TYPES: [&str; 5] = ["quote", "trade", "summary", "timesale", "tradex"];
+
+impl From<KnownMarketEvent> for MarketEvent {
+    fn from(event: KnownMarketEvent) -> Self {
+        match event {
+            KnownMarketEvent::Quote(q) => MarketEvent::Quote(q),
+            KnownMarketEvent::Trade(t) => MarketEvent::Trade(t),
+            KnownMarketEvent::Summary(s) => MarketEvent::Summary(s),
+            KnownMarketEvent::Timesale(ts) => MarketEvent::Timesale(ts),
+            KnownMarketEvent::Tradex(tx) => MarketEvent::Tradex(tx),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for MarketEvent {
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let value = serde_json::Value::deserialize(deserializer)?;
+        if !has_known_tag(&value, "type", &KNOWN_MARKET_EVENT_TYPES) {
+            return Ok(MarketEvent::Unknown(value));
+        }
+        KnownMarketEvent::deserialize(value)
+            .map(Into::into)
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+/// Returns `true` if `value` is an object whose `tag` field is one of
+/// `known`.
+pub(super) fn has_known_tag(value: &serde_json::Value, tag: &str, known: &[&str]) -> bool {
+    value
+        .get(tag)
+        .and_then(serde_json::Value::as_str)
+        .is_some_and(|tag| known.contains(&tag))
 }
 
 /// Best bid / ask for a symbol.
@@ -119,6 +184,28 @@ pub struct Timesale {
     pub session: TradeSession,
 }
 
+impl Timesale {
+    /// Parses [`Timesale::last`] from the upstream string to `f64`.
+    ///
+    /// # Errors
+    /// Returns [`Error::ParseFloat`] if the upstream value is not a
+    /// well-formed decimal number.
+    #[inline]
+    pub fn last_f64(&self) -> Result<f64> {
+        parse_f64(&self.last)
+    }
+
+    /// Parses [`Timesale::size`] from the upstream string to `u64`.
+    ///
+    /// # Errors
+    /// Returns [`Error::ParseInt`] if the upstream value is not a
+    /// well-formed integer.
+    #[inline]
+    pub fn size_u64(&self) -> Result<u64> {
+        parse_u64(&self.size)
+    }
+}
+
 /// Extended trade event (off-exchange / extended-hours / multi-leg).
 #[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
 pub struct Tradex {
@@ -153,31 +240,221 @@ impl MarketEvent {
             MarketEvent::Summary(s) => &s.symbol,
             MarketEvent::Timesale(ts) => &ts.symbol,
             MarketEvent::Tradex(tx) => &tx.symbol,
+            MarketEvent::Unknown(value) => value
+                .get("symbol")
+                .and_then(serde_json::Value::as_str)
+                .unwrap_or_default(),
+        }
+    }
+
+    /// Returns the event type as the [`MarketSessionFilter`] that selects
+    /// it, or `None` for [`MarketEvent::Unknown`].
+    #[must_use]
+    #[inline]
+    pub fn kind(&self) -> Option<MarketSessionFilter> {
+        match self {
+            MarketEvent::Quote(_) => Some(MarketSessionFilter::QUOTE),
+            MarketEvent::Trade(_) => Some(MarketSessionFilter::TRADE),
+            MarketEvent::Summary(_) => Some(MarketSessionFilter::SUMMARY),
+            MarketEvent::Timesale(_) => Some(MarketSessionFilter::TIMESALE),
+            MarketEvent::Tradex(_) => Some(MarketSessionFilter::TRADEX),
+            MarketEvent::Unknown(_) => None,
         }
     }
 
     /// Parses a single market event from a JSON line.
     ///
+    /// Valid JSON with an unrecognized `type` (or none) yields
+    /// [`MarketEvent::Unknown`].
+    ///
     /// # Errors
     /// Returns [`Error::StreamDecodeError`] with the offending payload
-    /// and the underlying `serde_json` error when the JSON does not
-    /// match any known variant.
+    /// and the underlying `serde_json` error when the line is not JSON or
+    /// a known event type has a malformed body.
     pub fn from_json(line: &str) -> Result<Self> {
-        serde_json::from_str::<Self>(line)
-            .map_err(|e| Error::StreamDecodeError(line.to_owned(), e.to_string()))
+        // Known events decode straight from the text; only misses pay for
+        // the intermediate `Value`.
+        let error = match serde_json::from_str::<KnownMarketEvent>(line) {
+            Ok(event) => return Ok(event.into()),
+            Err(e) => e,
+        };
+        match serde_json::from_str::<serde_json::Value>(line) {
+            Ok(value) if !has_known_tag(&value, "type", &KNOWN_MARKET_EVENT_TYPES) => {
+                Ok(MarketEvent::Unknown(value))
+            }
+            _ => Err(Error::StreamDecodeError(line.to_owned(), error.to_string())),
+        }
+    }
+}
+
+#[cfg(feature = "decimal")]
+impl Quote {
+    /// Returns [`Quote::bid`] as an exact `Decimal`.
+    ///
+    /// # Errors
+    /// Returns [`Error::ParseDecimal`] if the value cannot be represented as
+    /// a `Decimal`.
+    #[inline]
+    pub fn bid_decimal(&self) -> Result<rust_decimal::Decimal> {
+        crate::utils::decimal::decimal_from_f64(self.bid)
+    }
+
+    /// Returns [`Quote::ask`] as an exact `Decimal`.
+    ///
+    /// # Errors
+    /// Returns [`Error::ParseDecimal`] if the value cannot be represented as
+    /// a `Decimal`.
+    #[inline]
+    pub fn ask_decimal(&self) -> Result<rust_decimal::Decimal> {
+        crate::utils::decimal::decimal_from_f64(self.ask)
+    }
+}
+
+#[cfg(feature = "decimal")]
+impl Trade {
+    /// Parses [`Trade::price`] from the upstream string to `Decimal`.
+    ///
+    /// # Errors
+    /// Returns [`Error::ParseDecimal`] if the upstream value is not a
+    /// well-formed decimal number.
+    #[inline]
+    pub fn price_decimal(&self) -> Result<rust_decimal::Decimal> {
+        parse_decimal(&self.price)
+    }
+
+    /// Parses [`Trade::last`] from the upstream string to `Decimal`.
+    ///
+    /// # Errors
+    /// Returns [`Error::ParseDecimal`] if the upstream value is not a
+    /// well-formed decimal number.
+    #[inline]
+    pub fn last_decimal(&self) -> Result<rust_decimal::Decimal> {
+        parse_decimal(&self.last)
+    }
+}
+
+#[cfg(feature = "decimal")]
+impl Summary {
+    /// Parses [`Summary::open`] from the upstream string to `Decimal`.
+    ///
+    /// # Errors
+    /// Returns [`Error::ParseDecimal`] if the upstream value is not a
+    /// well-formed decimal number.
+    #[inline]
+    pub fn open_decimal(&self) -> Result<rust_decimal::Decimal> {
+        parse_decimal(&self.open)
+    }
+
+    /// Parses [`Summary::high`] from the upstream string to `Decimal`.
+    ///
+    /// # Errors
+    /// Returns [`Error::ParseDecimal`] if the upstream value is not a
+    /// well-formed decimal number.
+    #[inline]
+    pub fn high_decimal(&self) -> Result<rust_decimal::Decimal> {
+        parse_decimal(&self.high)
+    }
+
+    /// Parses [`Summary::low`] from the upstream string to `Decimal`.
+    ///
+    /// # Errors
+    /// Returns [`Error::ParseDecimal`] if the upstream value is not a
+    /// well-formed decimal number.
+    #[inline]
+    pub fn low_decimal(&self) -> Result<rust_decimal::Decimal> {
+        parse_decimal(&self.low)
+    }
+
+    /// Parses [`Summary::prev_close`] from the upstream string to `Decimal`.
+    ///
+    /// # Errors
+    /// Returns [`Error::ParseDecimal`] if the upstream value is not a
+    /// well-formed decimal number.
+    #[inline]
+    pub fn prev_close_decimal(&self) -> Result<rust_decimal::Decimal> {
+        parse_decimal(&self.prev_close)
+    }
+}
+
+#[cfg(feature = "decimal")]
+impl Timesale {
+    /// Parses [`Timesale::bid`] from the upstream string to `Decimal`.
+    ///
+    /// # Errors
+    /// Returns [`Error::ParseDecimal`] if the upstream value is not a
+    /// well-formed decimal number.
+    #[inline]
+    pub fn bid_decimal(&self) -> Result<rust_decimal::Decimal> {
+        parse_decimal(&self.bid)
+    }
+
+    /// Parses [`Timesale::ask`] from the upstream string to `Decimal`.
+    ///
+    /// # Errors
+    /// Returns [`Error::ParseDecimal`] if the upstream value is not a
+    /// well-formed decimal number.
+    #[inline]
+    pub fn ask_decimal(&self) -> Result<rust_decimal::Decimal> {
+        parse_decimal(&self.ask)
+    }
+
+    /// Parses [`Timesale::last`] from the upstream string to `Decimal`.
+    ///
+    /// # Errors
+    /// Returns [`Error::ParseDecimal`] if the upstream value is not a
+    /// well-formed decimal number.
+    #[inline]
+    pub fn last_decimal(&self) -> Result<rust_decimal::Decimal> {
+        parse_decimal(&self.last)
+    }
+}
+
+#[cfg(feature = "decimal")]
+impl Tradex {
+    /// Parses [`Tradex::price`] from the upstream string to `Decimal`.
+    ///
+    /// # Errors
+    /// Returns [`Error::ParseDecimal`] if the upstream value is not a
+    /// well-formed decimal number.
+    #[inline]
+    pub fn price_decimal(&self) -> Result<rust_decimal::Decimal> {
+        parse_decimal(&self.price)
+    }
+
+    /// Parses [`Tradex::last`] from the upstream string to `Decimal`.
+    ///
+    /// # Errors
+    /// Returns [`Error::ParseDecimal`] if the upstream value is not a
+    /// well-formed decimal number.
+    #[inline]
+    pub fn last_decimal(&self) -> Result<rust_decimal::Decimal> {
+        parse_decimal(&self.last)
     }
 }
 
 #[cold]
-fn parse_f64(value: &str) -> Result<f64> {
+pub(super) fn parse_f64(value: &str) -> Result<f64> {
     f64::from_str(value).map_err(|e| Error::ParseFloat(value.to_owned(), e.to_string()))
 }
 
 #[cold]
-fn parse_u64(value: &str) -> Result<u64> {
+pub(super) fn parse_u64(value: &str) -> Result<u64> {
     u64::from_str(value).map_err(|e| Error::ParseInt(value.to_owned(), e.to_string()))
 }
 
+/// Parses an upstream millisecond-epoch string to a UTC timestamp.
+pub(super) fn parse_epoch_millis(value: &str) -> Result<DateTime<Utc>> {
+    i64::from_str(value)
+        .ok()
+        .and_then(DateTime::from_timestamp_millis)
+        .ok_or_else(|| {
+            Error::ParseInt(
+                value.to_owned(),
+                "invalid millisecond-epoch timestamp".to_owned(),
+            )
+        })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -256,10 +533,31 @@ mod tests {
     }
 
     #[test]
-    fn test_market_event_from_json_unknown_type_returns_decode_error() {
-        let line = r#"{"type":"unknown","symbol":"SPY"}"#;
+    fn test_market_event_from_json_unknown_type_is_kept_verbatim() {
+        let line = r#"{"type":"heartbeat","symbol":"SPY","ts":1}"#;
+        let event = MarketEvent::from_json(line).expect("unknown types decode");
+        let MarketEvent::Unknown(value) = &event else {
+            panic!("expected unknown variant, got {event:?}");
+        };
+        assert_eq!(value["ts"], 1);
+        assert_eq!(event.symbol(), "SPY");
+        assert_eq!(event.kind(), None);
+        assert_eq!(
+            serde_json::to_value(&event).unwrap(),
+            serde_json::from_str::<serde_json::Value>(line).unwrap()
+        );
+        assert_eq!(serde_json::from_str::<MarketEvent>(line).unwrap(), event);
+
+        let untagged = MarketEvent::from_json(r#"{"status":"ok"}"#).unwrap();
+        assert!(matches!(untagged, MarketEvent::Unknown(_)));
+    }
+
+    #[test]
+    fn test_market_event_from_json_malformed_known_type_returns_decode_error() {
+        let line = r#"{"type":"quote","symbol":"SPY"}"#;
         let result = MarketEvent::from_json(line);
         assert!(matches!(result, Err(Error::StreamDecodeError(_, _))));
+        assert!(serde_json::from_str::<MarketEvent>(line).is_err());
     }
 
     #[test]
@@ -295,4 +593,46 @@ mod tests {
         };
         assert!(matches!(trade.size_u64(), Err(Error::ParseInt(_, _))));
     }
+
+    #[cfg(feature = "decimal")]
+    #[test]
+    fn test_trade_price_decimal_keeps_upstream_digits() {
+        let line = r#"{"type":"trade","symbol":"SPY","exch":"Q","price":"281.1200","size":"100","cvol":"34507070","date":"1557757204760","last":"281.1200"}"#;
+        let MarketEvent::Trade(trade) = MarketEvent::from_json(line).expect("parse") else {
+            panic!("expected trade variant");
+        };
+        assert_eq!(
+            trade.price_decimal().expect("price").to_string(),
+            "281.1200"
+        );
+        assert_eq!(trade.last_decimal().expect("last").to_string(), "281.1200");
+    }
+
+    #[cfg(feature = "decimal")]
+    #[test]
+    fn test_quote_bid_decimal_matches_wire_literal() {
+        let line = r#"{"type":"quote","symbol":"C","bid":281.84,"bidsz":60,"bidexch":"M","biddate":"1557757189000","ask":281.85,"asksz":6,"askexch":"Z","askdate":"1557757190000"}"#;
+        let MarketEvent::Quote(quote) = MarketEvent::from_json(line).expect("parse") else {
+            panic!("expected quote variant");
+        };
+        assert_eq!(quote.bid_decimal().expect("bid").to_string(), "281.84");
+        assert_eq!(quote.ask_decimal().expect("ask").to_string(), "281.85");
+    }
+
+    #[cfg(feature = "decimal")]
+    #[test]
+    fn test_summary_prev_close_decimal_malformed_returns_parse_decimal_error() {
+        let summary = Summary {
+            symbol: "SPY".into(),
+            open: "284.01".into(),
+            high: "284.42".into(),
+            low: "280.51".into(),
+            prev_close: "n/a".into(),
+        };
+        assert!(summary.open_decimal().is_ok());
+        assert!(matches!(
+            summary.prev_close_decimal(),
+            Err(Error::ParseDecimal(_, _))
+        ));
+    }
 }
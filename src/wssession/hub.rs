@@ -0,0 +1,457 @@
+//! Fan-out of one market event stream to many subscribers.
+//!
+//! A `Stream` of [`MarketEvent`] can only be polled by one consumer.
+//! [`MarketEventHub`] drives the stream on a background task and copies
+//! every event into `tokio::sync::broadcast` channels: one for all events,
+//! one per symbol and one per event type. Subscribers only receive the
+//! events they asked for.
+
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::sync::{Arc, Mutex, MutexGuard, OnceLock, PoisonError};
+
+use futures_util::stream::{self, Stream, StreamExt};
+use tokio::sync::broadcast::{self, error::RecvError};
+use tokio::task::JoinHandle;
+use tracing::{debug, warn};
+
+use super::events::MarketEvent;
+use super::market::MarketSessionFilter;
+use crate::{Error, Result};
+
+/// What a [`HubReceiver`] does when it falls more than the hub's capacity
+/// behind the upstream stream.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LagPolicy {
+    /// Skip the events that were overwritten and continue with the oldest
+    /// one still buffered.
+    #[default]
+    DropOldest,
+    /// Yield `Err(Error::SubscriberLagged(_))` once and end the receiver,
+    /// so a slow consumer is disconnected instead of silently missing
+    /// events.
+    Disconnect,
+}
+
+/// Broadcasts one upstream [`MarketEvent`] stream to many subscribers.
+///
+/// Subscribers register for every event ([`Self::subscribe_all`]), for
+/// one symbol ([`Self::subscribe_symbol`]) or for one event type
+/// ([`Self::subscribe_kind`]). Each receiver has its own buffer of
+/// `capacity` events; what happens when it overflows is set by
+/// [`LagPolicy`].
+///
+/// Upstream decode errors are logged and not forwarded. When the upstream
+/// stream ends, every receiver ends after draining its buffer; if the last
+/// upstream item was any other error (a transport error, an idle timeout),
+/// each receiver first yields it as `Err(Error::StreamFailed(_))`, so a
+/// failure can be told apart from a normal close. Dropping the hub stops
+/// polling the upstream stream.
+///
+/// # Example
+/// ```no_run
+/// use tradier::Config;
+/// use tradier::wssession::{
+///     LagPolicy, MarketEventHub, MarketSession, MarketSessionFilter, MarketSessionPayload,
+/// };
+///
+/// # async fn run() -> tradier::Result<()> {
+/// let config = Config::new();
+/// let session = MarketSession::new(&config).await?;
+/// let symbols = vec!["SPY".to_string(), "AAPL".to_string()];
+/// let payload = MarketSessionPayload::recommended(&symbols, session.get_session_id());
+/// let events = session.event_stream(payload).await?;
+///
+/// let hub = MarketEventHub::builder()
+///     .capacity(1024)
+///     .lag_policy(LagPolicy::Disconnect)
+///     .spawn(events);
+/// let mut spy = hub.subscribe_symbol("SPY");
+/// let mut quotes = hub.subscribe_kind(MarketSessionFilter::QUOTE);
+///
+/// while let Some(event) = spy.recv().await {
+///     println!("{:?}", event?);
+/// }
+/// # drop(quotes.recv());
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct MarketEventHub {
+    shared: Arc<Shared>,
+    task: JoinHandle<()>,
+}
+
+#[derive(Debug)]
+struct Shared {
+    capacity: usize,
+    lag_policy: LagPolicy,
+    channels: Mutex<Channels>,
+    /// Message of the error the upstream stream ended with, set before the
+    /// channels close.
+    failure: Arc<OnceLock<String>>,
+}
+
+#[derive(Debug, Default)]
+struct Channels {
+    closed: bool,
+    all: Option<broadcast::Sender<MarketEvent>>,
+    by_symbol: HashMap<String, broadcast::Sender<MarketEvent>>,
+    by_kind: HashMap<MarketSessionFilter, broadcast::Sender<MarketEvent>>,
+}
+
+#[bon::bon]
+impl MarketEventHub {
+    /// Spawns the task that drives `events` and returns the hub.
+    ///
+    /// Must be called from within a Tokio runtime.
+    ///
+    /// # Arguments
+    /// - `events`: The upstream stream, e.g. from
+    ///   [`crate::wssession::MarketSession::event_stream`].
+    /// - `capacity`: Events buffered per receiver. Defaults to 1024.
+    /// - `lag_policy`: Behavior of receivers that overflow their buffer.
+    ///   Defaults to [`LagPolicy::DropOldest`].
+    #[builder(builder_type(vis = "pub"), finish_fn = spawn)]
+    fn new<S>(
+        #[builder(finish_fn)] events: S,
+        #[builder(default = 1024)] capacity: usize,
+        #[builder(default)] lag_policy: LagPolicy,
+    ) -> Self
+    where
+        S: Stream<Item = Result<MarketEvent>> + Send + 'static,
+    {
+        let shared = Arc::new(Shared {
+            capacity: capacity.max(1),
+            lag_policy,
+            channels: Mutex::new(Channels::default()),
+            failure: Arc::default(),
+        });
+        let task = tokio::spawn(dispatch(events, Arc::clone(&shared)));
+        MarketEventHub { shared, task }
+    }
+
+    /// Receives every event.
+    pub fn subscribe_all(&self) -> HubReceiver {
+        let mut channels = self.shared.channels();
+        let closed = channels.closed;
+        let sender = channels
+            .all
+            .get_or_insert_with(|| broadcast::channel(self.shared.capacity).0);
+        self.shared.receiver(sender, closed)
+    }
+
+    /// Receives the events for `symbol`.
+    pub fn subscribe_symbol(&self, symbol: impl Into<String>) -> HubReceiver {
+        let mut channels = self.shared.channels();
+        let closed = channels.closed;
+        let sender = channels
+            .by_symbol
+            .entry(symbol.into())
+            .or_insert_with(|| broadcast::channel(self.shared.capacity).0);
+        self.shared.receiver(sender, closed)
+    }
+
+    /// Receives the events of one type, e.g. only quotes. Unknown event
+    /// types reach only [`Self::subscribe_all`] and symbol subscribers.
+    pub fn subscribe_kind(&self, kind: MarketSessionFilter) -> HubReceiver {
+        let mut channels = self.shared.channels();
+        let closed = channels.closed;
+        let sender = channels
+            .by_kind
+            .entry(kind)
+            .or_insert_with(|| broadcast::channel(self.shared.capacity).0);
+        self.shared.receiver(sender, closed)
+    }
+
+    /// Returns `true` once the upstream stream has ended.
+    pub fn is_closed(&self) -> bool {
+        self.shared.channels().closed
+    }
+}
+
+impl Drop for MarketEventHub {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+impl Shared {
+    fn channels(&self) -> MutexGuard<'_, Channels> {
+        self.channels.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn receiver(&self, sender: &broadcast::Sender<MarketEvent>, closed: bool) -> HubReceiver {
+        HubReceiver {
+            receiver: (!closed).then(|| sender.subscribe()),
+            lag_policy: self.lag_policy,
+            failure: Some(Arc::clone(&self.failure)),
+        }
+    }
+}
+
+/// Forwards every upstream event to the matching channels, then closes
+/// them all, recording the error the upstream stream ended with, if any.
+async fn dispatch<S>(events: S, shared: Arc<Shared>)
+where
+    S: Stream<Item = Result<MarketEvent>>,
+{
+    futures_util::pin_mut!(events);
+    let mut last_error = None;
+    while let Some(item) = events.next().await {
+        let event = match item {
+            Ok(event) => event,
+            Err(e @ Error::StreamDecodeError(_, _)) => {
+                warn!(error = %e, "market event hub dropped undecodable event");
+                continue;
+            }
+            Err(e) => {
+                warn!(error = %e, "market event hub upstream error");
+                last_error = Some(e.to_string());
+                continue;
+            }
+        };
+        last_error = None;
+        let mut channels = shared.channels();
+        let Channels {
+            all,
+            by_symbol,
+            by_kind,
+            ..
+        } = &mut *channels;
+        if let Some(kind) = event.kind() {
+            publish(by_kind, &kind, &event);
+        }
+        publish(by_symbol, event.symbol(), &event);
+        if let Some(sender) = all {
+            // No receivers is not an error for a hub.
+            let _ = sender.send(event);
+        }
+    }
+    debug!("market event hub upstream ended, closing receivers");
+    if let Some(error) = last_error {
+        let _ = shared.failure.set(error);
+    }
+    let mut channels = shared.channels();
+    *channels = Channels {
+        closed: true,
+        ..Channels::default()
+    };
+}
+
+/// Sends `event` on the channel for `key`, forgetting the channel once all
+/// its receivers are gone.
+fn publish<K, Q>(
+    senders: &mut HashMap<K, broadcast::Sender<MarketEvent>>,
+    key: &Q,
+    event: &MarketEvent,
+) where
+    K: std::borrow::Borrow<Q> + Eq + Hash,
+    Q: Eq + Hash + ?Sized,
+{
+    if let Some(sender) = senders.get(key)
+        && sender.send(event.clone()).is_err()
+    {
+        senders.remove(key);
+    }
+}
+
+/// A subscription to a [`MarketEventHub`].
+#[derive(Debug)]
+pub struct HubReceiver {
+    receiver: Option<broadcast::Receiver<MarketEvent>>,
+    lag_policy: LagPolicy,
+    /// Taken once the upstream failure has been reported.
+    failure: Option<Arc<OnceLock<String>>>,
+}
+
+impl HubReceiver {
+    /// Waits for the next event.
+    ///
+    /// # Returns
+    /// - `Some(Ok(event))` for each event.
+    /// - `Some(Err(Error::SubscriberLagged(n)))` once, when the receiver
+    ///   missed `n` events under [`LagPolicy::Disconnect`]; `None` follows.
+    /// - `Some(Err(Error::StreamFailed(_)))` once, after the buffer is
+    ///   drained, when the upstream stream ended with an error; `None`
+    ///   follows.
+    /// - `None` once the upstream stream has ended (or the hub was dropped)
+    ///   and the buffer is drained.
+    pub async fn recv(&mut self) -> Option<Result<MarketEvent>> {
+        loop {
+            let Some(receiver) = self.receiver.as_mut() else {
+                return self.take_failure();
+            };
+            match receiver.recv().await {
+                Ok(event) => return Some(Ok(event)),
+                Err(RecvError::Closed) => {
+                    self.receiver = None;
+                    return self.take_failure();
+                }
+                Err(RecvError::Lagged(skipped)) => match self.lag_policy {
+                    LagPolicy::DropOldest => {
+                        warn!(
+                            skipped,
+                            "market event hub subscriber lagged, dropped oldest events"
+                        );
+                    }
+                    LagPolicy::Disconnect => {
+                        warn!(skipped, "market event hub subscriber lagged, disconnecting");
+                        self.receiver = None;
+                        self.failure = None;
+                        return Some(Err(Error::SubscriberLagged(skipped)));
+                    }
+                },
+            }
+        }
+    }
+
+    fn take_failure(&mut self) -> Option<Result<MarketEvent>> {
+        let failure = self.failure.take()?;
+        failure
+            .get()
+            .map(|message| Err(Error::StreamFailed(message.clone())))
+    }
+
+    /// Turns the receiver into a [`Stream`] of the items of [`Self::recv`].
+    pub fn into_stream(self) -> impl Stream<Item = Result<MarketEvent>> {
+        stream::unfold(self, |mut receiver| async move {
+            let item = receiver.recv().await?;
+            Some((item, receiver))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::sync::mpsc;
+
+    /// An upstream stream fed by the returned sender.
+    fn upstream() -> (
+        mpsc::UnboundedSender<Result<MarketEvent>>,
+        impl Stream<Item = Result<MarketEvent>> + Send + 'static,
+    ) {
+        let (tx, rx) = mpsc::unbounded_channel();
+        let events = stream::unfold(rx, |mut rx| async move {
+            let item = rx.recv().await?;
+            Some((item, rx))
+        });
+        (tx, events)
+    }
+
+    fn quote(symbol: &str) -> MarketEvent {
+        MarketEvent::from_json(&format!(
+            r#"{{"type":"quote","symbol":"{symbol}","bid":1.0,"bidsz":1,"bidexch":"Q","biddate":"1","ask":1.1,"asksz":1,"askexch":"Q","askdate":"1"}}"#
+        ))
+        .unwrap()
+    }
+
+    fn trade(symbol: &str) -> MarketEvent {
+        MarketEvent::from_json(&format!(
+            r#"{{"type":"trade","symbol":"{symbol}","exch":"Q","price":"1.0","size":"1","cvol":"1","date":"1","last":"1.0"}}"#
+        ))
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_subscribers_receive_only_what_they_registered_for() {
+        let (tx, rx) = upstream();
+        let hub = MarketEventHub::builder().spawn(rx);
+        let all = hub.subscribe_all();
+        let spy = hub.subscribe_symbol("SPY");
+        let trades = hub.subscribe_kind(MarketSessionFilter::TRADE);
+
+        tx.send(Ok(quote("SPY"))).unwrap();
+        tx.send(Err(Error::StreamDecodeError("x".into(), "bad".into())))
+            .unwrap();
+        tx.send(Ok(trade("AAPL"))).unwrap();
+        tx.send(Ok(trade("SPY"))).unwrap();
+        drop(tx);
+
+        let symbols = |items: Vec<Result<MarketEvent>>| -> Vec<(String, MarketSessionFilter)> {
+            items
+                .into_iter()
+                .map(|e| e.unwrap())
+                .map(|e| (e.symbol().to_owned(), e.kind().unwrap()))
+                .collect()
+        };
+        assert_eq!(symbols(all.into_stream().collect().await).len(), 3);
+        assert_eq!(
+            symbols(spy.into_stream().collect().await),
+            [
+                ("SPY".to_owned(), MarketSessionFilter::QUOTE),
+                ("SPY".to_owned(), MarketSessionFilter::TRADE)
+            ]
+        );
+        assert_eq!(
+            symbols(trades.into_stream().collect().await),
+            [
+                ("AAPL".to_owned(), MarketSessionFilter::TRADE),
+                ("SPY".to_owned(), MarketSessionFilter::TRADE)
+            ]
+        );
+        assert!(hub.is_closed());
+        assert!(hub.subscribe_all().recv().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_upstream_failure_is_forwarded_after_buffered_events() {
+        let (tx, rx) = upstream();
+        let hub = MarketEventHub::builder().spawn(rx);
+        let all = hub.subscribe_all();
+
+        tx.send(Ok(quote("SPY"))).unwrap();
+        tx.send(Err(Error::StreamIdle(std::time::Duration::from_secs(30))))
+            .unwrap();
+        drop(tx);
+
+        let items: Vec<Result<MarketEvent>> = all.into_stream().collect().await;
+        assert_eq!(items.len(), 2);
+        assert!(matches!(items[0], Ok(MarketEvent::Quote(_))));
+        assert!(matches!(&items[1], Err(Error::StreamFailed(message)) if message.contains("30s")));
+
+        // Late subscribers learn how the stream ended, too.
+        let mut late = hub.subscribe_symbol("SPY");
+        assert!(matches!(
+            late.recv().await,
+            Some(Err(Error::StreamFailed(_)))
+        ));
+        assert!(late.recv().await.is_none());
+    }
+
+    async fn lagged_receiver(policy: LagPolicy) -> Vec<Result<MarketEvent>> {
+        let (tx, rx) = upstream();
+        let hub = MarketEventHub::builder()
+            .capacity(2)
+            .lag_policy(policy)
+            .spawn(rx);
+        let slow = hub.subscribe_all();
+        let mut fast = hub.subscribe_all();
+        for symbol in ["A", "B", "C", "D"] {
+            tx.send(Ok(quote(symbol))).unwrap();
+            // Keep one receiver in step so the dispatcher has processed
+            // every event before the slow one reads.
+            fast.recv().await.unwrap().unwrap();
+        }
+        drop(tx);
+        slow.into_stream().collect().await
+    }
+
+    #[tokio::test]
+    async fn test_drop_oldest_skips_overwritten_events() {
+        let items = lagged_receiver(LagPolicy::DropOldest).await;
+        let symbols: Vec<String> = items
+            .into_iter()
+            .map(|e| e.unwrap().symbol().to_owned())
+            .collect();
+        assert_eq!(symbols, ["C", "D"]);
+    }
+
+    #[tokio::test]
+    async fn test_disconnect_ends_slow_subscriber_with_error() {
+        let items = lagged_receiver(LagPolicy::Disconnect).await;
+        assert_eq!(items.len(), 1);
+        assert!(matches!(items[0], Err(Error::SubscriberLagged(2))));
+    }
+}
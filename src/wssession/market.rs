@@ -1,15 +1,35 @@
 use crate::config::Config;
+#[cfg(feature = "wssession")]
+use crate::utils::telemetry;
+#[cfg(feature = "wssession")]
 use crate::wssession::events::MarketEvent;
+#[cfg(feature = "wssession")]
+use crate::wssession::reconnect::{ReconnectPolicy, SupervisedMarketEvent};
 use crate::wssession::session::{Session, SessionType};
+#[cfg(feature = "wssession")]
+use crate::wssession::subscription::{MarketSink, MarketSubscription};
+#[cfg(feature = "wssession")]
+use crate::wssession::ws_decode::{Heartbeat, SharedSink};
 use crate::{Error, Result};
+use chrono::{DateTime, Utc};
+#[cfg(feature = "wssession")]
+use futures_util::lock::Mutex;
+#[cfg(feature = "wssession")]
 use futures_util::stream::Stream;
+#[cfg(feature = "wssession")]
 use futures_util::{SinkExt, StreamExt};
 use serde::{Deserialize, Serialize};
 use std::borrow::Cow;
 use std::fmt::{Display, Formatter};
+#[cfg(feature = "wssession")]
+use std::sync::Arc;
+#[cfg(feature = "wssession")]
 use tokio_tungstenite::connect_async;
+#[cfg(feature = "wssession")]
 use tracing::{debug, info, trace, warn};
+#[cfg(feature = "wssession")]
 use tungstenite::Message;
+#[cfg(feature = "wssession")]
 use url::Url;
 
 use super::session_manager::{GLOBAL_SESSION_MANAGER, SessionManager};
@@ -22,7 +42,7 @@ use super::session_manager::{GLOBAL_SESSION_MANAGER, SessionManager};
 /// - `SUMMARY`: Filters summary events.
 /// - `TIMESALE`: Filters time sale events.
 /// - `TRADEX`: Filters extended trade events.
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash)]
 #[serde(into = "String", try_from = "&str")]
 pub enum MarketSessionFilter {
     TRADE,
@@ -133,11 +153,25 @@ impl<'a> MarketSessionPayload<'a> {
             .build()
     }
 
+    /// Clones any borrowed data so the payload can outlive the symbols,
+    /// filters and session id it was built from.
+    pub fn into_owned(self) -> MarketSessionPayload<'static> {
+        MarketSessionPayload {
+            symbols: Cow::Owned(self.symbols.into_owned()),
+            filters: self.filters.map(|f| Cow::Owned(f.into_owned())),
+            session_id: Cow::Owned(self.session_id.into_owned()),
+            linebreak: self.linebreak,
+            valid_only: self.valid_only,
+            advanced_details: self.advanced_details,
+        }
+    }
+
     /// Converts the payload to a WebSocket `Message` for sending.
     ///
     /// # Returns
     /// - `Ok(Message)`: The WebSocket message if serialization is successful.
     /// - `Err(Box<dyn Error>)`: An error if serialization fails.
+    #[cfg(feature = "wssession")]
     pub fn get_message(&self) -> Result<Message> {
         serde_json::to_string(self)
             .map(|s| Message::Text(s.into()))
@@ -190,7 +224,7 @@ impl<'a> MarketSession<'a> {
         Self::new_with_session_manager(config, &GLOBAL_SESSION_MANAGER).await
     }
 
-    async fn new_with_session_manager(
+    pub(super) async fn new_with_session_manager(
         config: &Config,
         session_manager: &'a SessionManager,
     ) -> Result<Self> {
@@ -215,6 +249,37 @@ impl<'a> MarketSession<'a> {
         self.0.get_websocket_url()
     }
 
+    /// Returns when the session id was issued.
+    pub fn created_at(&self) -> DateTime<Utc> {
+        self.0.created_at()
+    }
+
+    /// Returns when the session id goes stale: its creation time plus
+    /// [`crate::config::StreamingConfig::session_timeout`].
+    pub fn expires_at(&self) -> DateTime<Utc> {
+        self.0.expires_at()
+    }
+
+    /// Returns `true` once [`Self::expires_at`] has passed. Tradier rejects
+    /// stale session ids, so connect with a fresh one instead (see
+    /// [`Self::renew_if_expired`]).
+    pub fn is_expired(&self) -> bool {
+        self.0.is_expired()
+    }
+
+    /// Returns this session unchanged while its id is still fresh;
+    /// otherwise releases it and creates a new session through the same
+    /// `SessionManager`, so callers can always connect with a valid id.
+    ///
+    /// Build the subscription payload from the returned session's
+    /// [`Self::get_session_id`].
+    ///
+    /// # Errors
+    /// Same as [`Self::new`]. The stale session is released either way.
+    pub async fn renew_if_expired(self, config: &Config) -> Result<Self> {
+        Ok(Self(self.0.renew_if_expired(config).await?))
+    }
+
     /// Opens the market WebSocket and returns an async [`Stream`] of typed
     /// [`MarketEvent`] values.
     ///
@@ -231,8 +296,8 @@ impl<'a> MarketSession<'a> {
     /// fails, the failure is surfaced as `Err(Error::WebSocketError(...))`
     /// and the stream terminates.
     ///
-    /// Reconnect policy is the caller's responsibility — see the example
-    /// under `examples/auth_websocket_example.rs`.
+    /// The stream ends when the connection does; use
+    /// [`Self::supervised_event_stream`] for automatic reconnects.
     ///
     /// # Errors
     /// - [`Error::UrlParsingError`] if the session URL is malformed.
@@ -243,22 +308,134 @@ impl<'a> MarketSession<'a> {
     ///
     /// After the stream is established, per-frame errors are yielded as
     /// `Err(_)` items of the stream rather than aborting early.
+    #[cfg(feature = "wssession")]
     pub async fn event_stream(
         &self,
         payload: MarketSessionPayload<'a>,
-    ) -> Result<impl Stream<Item = Result<MarketEvent>>> {
-        let uri = self.0.get_websocket_url();
-        let url = Url::parse(uri)?;
+    ) -> Result<impl Stream<Item = Result<MarketEvent>> + use<>> {
+        connect_market_stream(self.0.get_websocket_url(), &payload, self.0.heartbeat()).await
+    }
+
+    /// Opens the market WebSocket like [`Self::event_stream`] and also
+    /// returns a [`MarketSubscription`] handle for changing the symbols and
+    /// filters on the open socket.
+    ///
+    /// Tradier applies a subscription change when the full payload is sent
+    /// again; the handle does that for every edit, so symbols can be
+    /// rotated without reconnecting or creating a new session.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use futures_util::StreamExt;
+    /// use tradier::Config;
+    /// use tradier::wssession::{MarketSession, MarketSessionPayload};
+    ///
+    /// # async fn run() -> tradier::Result<()> {
+    /// let config = Config::new();
+    /// let session = MarketSession::new(&config).await?;
+    /// let symbols = vec!["SPY".to_string()];
+    /// let payload = MarketSessionPayload::recommended(&symbols, session.get_session_id());
+    ///
+    /// let (mut subscription, events) = session.subscribe(payload).await?;
+    /// subscription.add_symbols(["AAPL", "MSFT"]).await?;
+    /// subscription.remove_symbols(["SPY"]).await?;
+    ///
+    /// futures_util::pin_mut!(events);
+    /// while let Some(event) = events.next().await {
+    ///     println!("{:?}", event?);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    /// Same as [`Self::event_stream`].
+    #[cfg(feature = "wssession")]
+    pub async fn subscribe(
+        &self,
+        payload: MarketSessionPayload<'_>,
+    ) -> Result<(
+        MarketSubscription,
+        impl Stream<Item = Result<MarketEvent>> + use<>,
+    )> {
+        let payload = payload.into_owned();
+        let (write, events) =
+            open_market_socket(self.0.get_websocket_url(), &payload, self.0.heartbeat()).await?;
+        Ok((MarketSubscription::new(write, payload), events))
+    }
 
-        info!(url = %uri, "connecting to market stream");
-        let (ws_stream, _) = connect_async(url.as_str()).await.map_err(Box::new)?;
-        let (mut write, read) = ws_stream.split();
+    /// Consumes the session and returns a self-healing [`Stream`] of
+    /// [`SupervisedMarketEvent`] items.
+    ///
+    /// Whenever the socket closes or fails, the supervisor releases the
+    /// current session, creates a fresh session id, reconnects and re-sends
+    /// `payload` (with the new session id) after waiting as dictated by
+    /// `policy`. Each attempt is announced with
+    /// [`SupervisedMarketEvent::Reconnecting`] and a successful resubscribe
+    /// with [`SupervisedMarketEvent::Reconnected`].
+    ///
+    /// Decode failures are yielded as `Err(Error::StreamDecodeError(...))`
+    /// items without triggering a reconnect, exactly like
+    /// [`Self::event_stream`]. The stream only ends with
+    /// `Err(Error::ReconnectAttemptsExhausted(...))` once
+    /// [`ReconnectPolicy::max_attempts`] consecutive attempts have failed;
+    /// with no limit it never ends on its own.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use futures_util::StreamExt;
+    /// use tradier::Config;
+    /// use tradier::wssession::{
+    ///     MarketSession, MarketSessionPayload, ReconnectPolicy, SupervisedMarketEvent,
+    /// };
+    ///
+    /// # async fn run() -> tradier::Result<()> {
+    /// let config = Config::new();
+    /// let session = MarketSession::new(&config).await?;
+    /// let symbols = vec!["SPY".to_string()];
+    /// let session_id = session.get_session_id().to_owned();
+    /// let payload = MarketSessionPayload::recommended(&symbols, &session_id);
+    /// let policy = ReconnectPolicy::from_config(&config.streaming);
+    ///
+    /// let stream = session.supervised_event_stream(&config, payload, policy);
+    /// futures_util::pin_mut!(stream);
+    /// while let Some(item) = stream.next().await {
+    ///     match item? {
+    ///         SupervisedMarketEvent::Event(event) => println!("{}", event.symbol()),
+    ///         SupervisedMarketEvent::Reconnecting { attempt, delay, reason } => {
+    ///             eprintln!("reconnect #{attempt} in {delay:?}: {reason}")
+    ///         }
+    ///         SupervisedMarketEvent::Reconnected { .. } => eprintln!("resubscribed"),
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "wssession")]
+    pub fn supervised_event_stream(
+        self,
+        config: &Config,
+        payload: MarketSessionPayload<'_>,
+        policy: ReconnectPolicy,
+    ) -> impl Stream<Item = Result<SupervisedMarketEvent>> + use<'a> {
+        super::reconnect::supervised_market_stream(
+            self,
+            config.clone(),
+            payload.into_owned(),
+            policy,
+        )
+    }
 
-        let message = payload.get_message()?;
-        write.send(message).await.map_err(Box::new)?;
-        debug!("sent market subscription payload");
+    /// Returns the keep-alive settings for streams opened with this session.
+    #[cfg(feature = "wssession")]
+    pub(super) fn heartbeat(&self) -> Heartbeat {
+        self.0.heartbeat()
+    }
 
-        Ok(market_event_stream(read))
+    /// Returns the manager holding this session's slot.
+    #[cfg(feature = "wssession")]
+    pub(super) fn session_manager(&self) -> &'a SessionManager {
+        self.0.session_manager()
     }
 
     /// Initiates a WebSocket connection and streams data based on the provided payload.
@@ -269,6 +446,7 @@ impl<'a> MarketSession<'a> {
     ///
     /// # Errors
     /// Same as [`Self::event_stream`].
+    #[cfg(feature = "wssession")]
     pub async fn ws_stream(&self, payload: MarketSessionPayload<'a>) -> Result<()> {
         let stream = self.event_stream(payload).await?;
         futures_util::pin_mut!(stream);
@@ -290,26 +468,56 @@ impl<'a> MarketSession<'a> {
     }
 }
 
-/// Internal newline-delimited [`MarketEvent`] decoder over a split
-/// WebSocket read half.
-///
-/// Text frames are split on `\n`, each non-empty line is decoded into a
-/// [`MarketEvent`]. Decode errors are yielded as `Err(_)` items but do
-/// not terminate the stream. `Close` frames end the stream cleanly.
-#[inline]
-fn market_event_stream<S>(read: S) -> impl Stream<Item = Result<MarketEvent>>
-where
-    S: Stream<Item = std::result::Result<Message, tungstenite::Error>> + Unpin,
-{
-    super::ws_decode::ws_event_stream(read, MarketEvent::from_json)
+/// Opens the market WebSocket at `uri`, sends the subscription `payload`
+/// and returns the decoded event stream.
+#[cfg(feature = "wssession")]
+pub(super) async fn connect_market_stream(
+    uri: &str,
+    payload: &MarketSessionPayload<'_>,
+    heartbeat: Heartbeat,
+) -> Result<impl Stream<Item = Result<MarketEvent>> + Send + use<>> {
+    let (_write, events) = open_market_socket(uri, payload, heartbeat).await?;
+    Ok(events)
+}
+
+/// Like [`connect_market_stream`], but keeps the write half so the
+/// subscription can be changed later.
+#[cfg(feature = "wssession")]
+async fn open_market_socket(
+    uri: &str,
+    payload: &MarketSessionPayload<'_>,
+    heartbeat: Heartbeat,
+) -> Result<(
+    SharedSink<MarketSink>,
+    impl Stream<Item = Result<MarketEvent>> + Send + use<>,
+)> {
+    let url = Url::parse(uri)?;
+
+    info!(url = %uri, "connecting to market stream");
+    let (ws_stream, _) = connect_async(url.as_str()).await.map_err(Box::new)?;
+    let (mut write, read) = ws_stream.split();
+
+    let message = payload.get_message()?;
+    write.send(message).await.map_err(Box::new)?;
+    debug!("sent market subscription payload");
+    telemetry::record_stream_connect(telemetry::TRANSPORT_WS);
+
+    let write = Arc::new(Mutex::new(write));
+    let events = super::ws_decode::ws_event_stream(
+        read,
+        Arc::clone(&write),
+        heartbeat,
+        MarketEvent::from_json,
+    );
+    Ok((write, events))
 }
 
-#[cfg(test)]
+#[cfg(all(test, feature = "wssession"))]
 mod tests {
     use crate::{
         utils::tests::{
             ScriptedWsAction, create_test_config, free_tcp_port, mock_websocket_server,
-            scripted_websocket_server,
+            scripted_reconnecting_websocket_server, scripted_websocket_server,
         },
         wssession::session_manager::SessionManager,
     };
@@ -762,4 +970,87 @@ mod tests {
         assert_eq!(collected.len(), 1);
         assert!(matches!(collected[0], Ok(MarketEvent::Quote(_))));
     }
+
+    #[tokio::test]
+    async fn test_subscribe_resends_full_payload_on_each_change() {
+        let port = free_tcp_port();
+        let session = build_market_session_against_port(port).await;
+
+        let frames = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let seen = std::sync::Arc::clone(&frames);
+        scripted_reconnecting_websocket_server(
+            ("127.0.0.1", port),
+            vec![vec![
+                ScriptedWsAction::AwaitText,
+                ScriptedWsAction::AwaitText,
+                ScriptedWsAction::AwaitText,
+                ScriptedWsAction::SendText(QUOTE_FRAME),
+                ScriptedWsAction::SendClose,
+            ]],
+            move |_, msg| {
+                seen.lock()
+                    .unwrap()
+                    .push(serde_json::from_str::<serde_json::Value>(msg).unwrap())
+            },
+        )
+        .await;
+
+        let symbols = ["SPY".to_string()];
+        let payload = MarketSessionPayload::recommended(&symbols, session.get_session_id());
+        let (mut subscription, events) = session.subscribe(payload).await.expect("subscribe");
+
+        subscription
+            .add_symbols(["AAPL", "SPY", "MSFT"])
+            .await
+            .expect("add");
+        subscription.remove_symbols(["SPY"]).await.expect("remove");
+        subscription
+            .set_filters(Some(vec![MarketSessionFilter::TRADE]))
+            .await
+            .expect("filters");
+        assert_eq!(subscription.symbols(), ["AAPL", "MSFT"]);
+
+        let collected: Vec<Result<MarketEvent>> = events.collect().await;
+        assert_eq!(collected.len(), 1);
+        assert!(matches!(collected[0], Ok(MarketEvent::Quote(_))));
+
+        let frames = frames.lock().unwrap();
+        assert_eq!(frames.len(), 4);
+        assert_eq!(frames[0]["symbols"], serde_json::json!(["SPY"]));
+        assert_eq!(
+            frames[1]["symbols"],
+            serde_json::json!(["SPY", "AAPL", "MSFT"])
+        );
+        assert_eq!(frames[2]["symbols"], serde_json::json!(["AAPL", "MSFT"]));
+        assert_eq!(frames[2]["filters"], serde_json::json!(["quote"]));
+        assert_eq!(frames[3]["filters"], serde_json::json!(["trade"]));
+        for frame in frames.iter() {
+            assert_eq!(frame["sessionid"], "c8638963-a6d4-4fb9-9bc6-e25fbd8c60c3");
+            assert_eq!(frame["linebreak"], true);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_subscribe_keeps_payload_when_resend_fails() {
+        let port = free_tcp_port();
+        let session = build_market_session_against_port(port).await;
+        scripted_reconnecting_websocket_server(
+            ("127.0.0.1", port),
+            vec![vec![ScriptedWsAction::SendClose]],
+            |_, _| {},
+        )
+        .await;
+
+        let symbols = ["SPY".to_string()];
+        let payload = MarketSessionPayload::recommended(&symbols, session.get_session_id());
+        let (mut subscription, events) = session.subscribe(payload).await.expect("subscribe");
+        let collected: Vec<Result<MarketEvent>> = events.collect().await;
+        assert!(collected.is_empty());
+
+        assert!(subscription.add_symbols(["AAPL"]).await.is_err());
+        assert!(subscription.set_symbols(["MSFT"]).await.is_err());
+        assert!(subscription.set_filters(None).await.is_err());
+        assert_eq!(subscription.symbols(), ["SPY"]);
+        assert!(subscription.payload().filters.is_some());
+    }
 }
@@ -13,9 +13,26 @@
 //!   [`AccountSession::event_stream`] for a typed [`AccountEvent`] stream.
 //! - **`MarketSession`**: Handles WebSocket sessions for streaming market data, including real-time
 //!   quotes and trades. Use [`MarketSession::event_stream`] for a typed
-//!   [`MarketEvent`] stream.
-//! - **`SessionManager`**: Ensures that only one streaming session is active at any given time, adhering
-//!   to Tradier's limitation of a single concurrent session per user.
+//!   [`MarketEvent`] stream, or [`MarketSession::supervised_event_stream`]
+//!   for one that reconnects on its own. [`MarketSession::subscribe`] also returns a
+//!   [`MarketSubscription`] handle for changing symbols and filters on the open socket.
+//! - **`MarketEventHub`**: Drives one [`MarketEvent`] stream and fans it out to many subscribers,
+//!   per symbol or per event type, with a configurable [`LagPolicy`] for slow consumers.
+//! - **`BarAggregator`**: Builds time, tick and volume OHLCV [`Bar`]s from trade and time-and-sales
+//!   prints, yielding completed bars as a stream.
+//! - **`QuoteCache`**: Lock-free latest [`QuoteSnapshot`] per symbol (top of book, last trade,
+//!   volume, day OHLC), fed by market events and optionally seeded from REST quotes.
+//! - **`StreamRecorder`** / **`StreamReplayer`**: Tee any event stream to an NDJSON (optionally
+//!   gzip) recording and replay it later at the original pace, accelerated, or as fast as possible.
+//! - **`OrderTracker`**: Folds order and fill events into per-order [`OrderState`] (typed status,
+//!   fills, average price), reconciles with the REST orders endpoint before following each
+//!   reconnected stream, and lets callers await an order reaching a terminal status.
+//! - **`SequenceTracker`**: Checks time-and-sales sequence numbers per symbol, dropping replayed
+//!   prints and reporting [`SequenceGap`]s to backfill.
+//! - **`blocking`**: Blocking iterators over the WebSocket sessions and HTTP streams, with a
+//!   `StopHandle` to end iteration from another thread, for code without a Tokio runtime.
+//! - **`SessionManager`**: Caps how many market and account sessions may be open at once (one of each
+//!   by default). Sessions hold their slot until dropped.
 //!
 //! ## HTTP streaming fallback
 //!
@@ -24,22 +41,40 @@
 //! streaming API. See [`crate::streaming::http_stream`] for
 //! `Stream`-returning helpers that reuse the pooled `reqwest::Client`
 //! on a [`crate::client::non_blocking::TradierRestClient`].
+//! `crate::streaming::MarketStream` wraps both transports behind one
+//! builder, optionally falling back to HTTP when the WebSocket cannot be
+//! opened.
 //!
 //! ## Usage
 //!
 //! By utilizing these components, developers can integrate Tradier's streaming capabilities into
-//! their applications, facilitating real-time data processing and event handling. Reconnection is
-//! the caller's responsibility — the library exposes the session id, subscription payload, and
-//! the stream itself; the caller drives the retry loop.
+//! their applications, facilitating real-time data processing and event handling. Market streams
+//! can be supervised with a [`ReconnectPolicy`] that recreates the session and resubscribes after a
+//! drop; for account streams the caller drives the retry loop using the session id, subscription
+//! payload, and the stream itself.
 
 mod account;
 pub mod account_events;
+mod bars;
+#[cfg(feature = "blocking")]
+pub mod blocking;
 
 pub mod events;
+mod hub;
 mod market;
+mod order_tracker;
+pub mod parsed_events;
+mod quote_cache;
+#[cfg(feature = "wssession")]
+mod reconnect;
+mod recording;
+mod sequence;
 
 pub(crate) mod session;
-pub(crate) mod session_manager;
+mod session_manager;
+#[cfg(feature = "wssession")]
+mod subscription;
+#[cfg(feature = "wssession")]
 pub(crate) mod ws_decode;
 
 pub use account::{AccountSession, AccountSessionEvent, AccountSessionPayload};
@@ -47,5 +82,20 @@ pub use account_events::{
     AccountBalanceEvent, AccountDropEvent, AccountEvent, AccountFillEvent, AccountOrderEvent,
     AccountPositionEvent, AccountTradeEvent,
 };
+pub use bars::{Bar, BarAggregator, BarInterval};
 pub use events::{MarketEvent, Quote, Summary, Timesale, Trade, TradeSession, Tradex};
+pub use hub::{HubReceiver, LagPolicy, MarketEventHub};
 pub use market::{MarketSession, MarketSessionFilter, MarketSessionPayload};
+pub use order_tracker::{OrderState, OrderTracker};
+pub use parsed_events::{
+    ParsedMarketEvent, ParsedQuote, ParsedSummary, ParsedTimesale, ParsedTrade, ParsedTradex,
+};
+pub use quote_cache::{QuoteCache, QuoteSnapshot};
+#[cfg(feature = "wssession")]
+pub use reconnect::{ReconnectPolicy, SupervisedMarketEvent};
+pub use recording::{Compression, RecordedEvent, ReplaySpeed, StreamRecorder, StreamReplayer};
+pub use sequence::{SequenceGap, SequenceStatus, SequenceTracker, SequencedEvent};
+pub use session::SessionType;
+pub use session_manager::SessionManager;
+#[cfg(feature = "wssession")]
+pub use subscription::MarketSubscription;
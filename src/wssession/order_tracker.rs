@@ -0,0 +1,548 @@
+//! Order lifecycle tracking from the account event stream.
+//!
+//! [`OrderTracker`] folds [`AccountEvent`]s into one [`OrderState`] per
+//! order id: typed status, cumulative filled quantity, average fill price
+//! and remaining quantity. Events missed while the stream was down are
+//! recovered with [`OrderTracker::reconcile`], which overwrites the
+//! tracked state with `GET /v1/accounts/{account_id}/orders`. Callers can
+//! await an order reaching a terminal status with
+//! [`OrderTracker::wait_for_terminal`].
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+use futures_util::future::Either;
+use futures_util::stream::{self, Stream, StreamExt};
+use tokio::sync::watch;
+use tracing::{debug, warn};
+
+use super::account_events::{AccountEvent, AccountFillEvent, AccountOrderEvent};
+use crate::Result;
+use crate::accounts::api::non_blocking::Accounts;
+use crate::client::non_blocking::TradierRestClient;
+use crate::types::{
+    AccountNumber, GetAccountOrdersResponse, IncludeTags, Limit, Order, OrderStatus, Page,
+};
+
+/// Page size used by [`OrderTracker::reconcile_from_rest`].
+const RECONCILE_PAGE_SIZE: u32 = 100;
+
+/// Tracked state of one order.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OrderState {
+    pub id: u64,
+    pub account_number: Option<String>,
+    pub symbol: Option<String>,
+    pub status: OrderStatus,
+    /// Requested quantity, once known.
+    pub quantity: Option<f64>,
+    /// Cumulative filled quantity.
+    pub filled_quantity: f64,
+    /// Average fill price, once anything has filled.
+    pub avg_fill_price: Option<f64>,
+    /// When the tracker last changed this state.
+    pub updated_at: DateTime<Utc>,
+    /// Sum of the fill events seen. Fills only add to
+    /// [`Self::filled_quantity`] once this exceeds it, so fills already
+    /// included in a reported cumulative quantity are not counted twice.
+    fill_quantity: f64,
+}
+
+impl OrderState {
+    fn new(id: u64) -> Self {
+        Self {
+            id,
+            account_number: None,
+            symbol: None,
+            status: OrderStatus::Pending,
+            quantity: None,
+            filled_quantity: 0.0,
+            avg_fill_price: None,
+            updated_at: Utc::now(),
+            fill_quantity: 0.0,
+        }
+    }
+
+    /// Returns the unfilled quantity, once the requested quantity is known.
+    #[must_use]
+    pub fn remaining_quantity(&self) -> Option<f64> {
+        self.quantity
+            .map(|quantity| (quantity - self.filled_quantity).max(0.0))
+    }
+
+    /// Returns `true` once the order can no longer change.
+    #[must_use]
+    pub fn is_terminal(&self) -> bool {
+        self.status.is_terminal()
+    }
+
+    fn apply_order_event(&mut self, event: &AccountOrderEvent) {
+        match event.status.parse::<OrderStatus>() {
+            Ok(status) => self.status = status,
+            Err(e) => warn!(order_id = self.id, error = %e, "keeping previous order status"),
+        }
+        if event.account_number.is_some() {
+            self.account_number.clone_from(&event.account_number);
+        }
+        if event.symbol.is_some() {
+            self.symbol.clone_from(&event.symbol);
+        }
+        if event.quantity.is_some() {
+            self.quantity = event.quantity;
+        }
+        if let Some(executed) = event.executed_quantity {
+            self.filled_quantity = executed;
+        } else if let (Some(quantity), Some(remaining)) = (self.quantity, event.remaining_quantity)
+        {
+            self.filled_quantity = quantity - remaining;
+        }
+        if event.avg_fill_price.is_some() {
+            self.avg_fill_price = event.avg_fill_price;
+        }
+    }
+
+    fn apply_fill_event(&mut self, fill: &AccountFillEvent) {
+        if self.account_number.is_none() {
+            self.account_number.clone_from(&fill.account_number);
+        }
+        if self.symbol.is_none() {
+            self.symbol.clone_from(&fill.symbol);
+        }
+        let (Some(quantity), Some(price)) = (fill.quantity, fill.price) else {
+            return;
+        };
+        self.fill_quantity += quantity;
+        let added = self.fill_quantity - self.filled_quantity;
+        if added <= 0.0 {
+            debug!(
+                order_id = self.id,
+                "fill already covered by the order's reported quantities"
+            );
+            return;
+        }
+        let filled = self.fill_quantity;
+        let notional = self.avg_fill_price.unwrap_or(0.0) * self.filled_quantity;
+        self.avg_fill_price = Some((notional + price * added) / filled);
+        self.filled_quantity = filled;
+        if self.status.is_terminal() {
+            return;
+        }
+        self.status = match self.quantity {
+            Some(total) if filled >= total => OrderStatus::Filled,
+            _ => OrderStatus::PartiallyFilled,
+        };
+    }
+
+    fn apply_rest_order(&mut self, order: &Order) {
+        self.symbol = Some(order.symbol().to_owned());
+        self.status = order.status();
+        self.quantity = Some(order.quantity());
+        self.filled_quantity = order.exec_quantity();
+        self.avg_fill_price = (order.exec_quantity() > 0.0).then(|| order.avg_fill_price());
+    }
+}
+
+/// Per-order state built from the account event stream.
+///
+/// Order events are authoritative: their status, executed quantity and
+/// average price replace the tracked values. Fill events add to the
+/// filled quantity and average price of their parent order once the fills
+/// seen add up to more than the last reported cumulative quantity, so a
+/// fill is never counted on top of a total that already includes it.
+/// Clones share the same state.
+///
+/// [`Self::track_with_reconcile`] follows one account stream after another
+/// and reconciles with the REST orders endpoint before each, so updates
+/// missed while reconnecting are recovered. [`Self::track`] follows a
+/// single stream; call [`Self::reconcile_from_rest`] yourself after a
+/// reconnect when using it.
+///
+/// # Example
+/// ```no_run
+/// use futures_util::StreamExt;
+/// use tradier::types::AccountNumber;
+/// use tradier::wssession::{
+///     AccountSession, AccountSessionEvent, AccountSessionPayload, OrderTracker,
+/// };
+/// use tradier::{Config, non_blocking::Client};
+///
+/// # async fn run() -> tradier::Result<()> {
+/// let config = Config::new();
+/// let client = Client::new(config.clone());
+/// let account: AccountNumber = "VA000000".parse()?;
+/// let tracker = OrderTracker::new();
+///
+/// // Call again after every reconnect, before following the new stream.
+/// tracker.reconcile_from_rest(&client, &account).await?;
+/// let session = AccountSession::new(&config).await?;
+/// let payload = AccountSessionPayload::builder()
+///     .events(&[AccountSessionEvent::Order])
+///     .session_id(session.get_session_id())
+///     .build();
+/// let events = tracker.track(session.event_stream(payload).await?);
+/// tokio::spawn(events.for_each(|_| async {}));
+///
+/// let order = tracker.wait_for_terminal(123456).await;
+/// println!("{} filled {} @ {:?}", order.id, order.filled_quantity, order.avg_fill_price);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct OrderTracker {
+    orders: Arc<watch::Sender<HashMap<u64, OrderState>>>,
+}
+
+impl Default for OrderTracker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl OrderTracker {
+    /// Creates a tracker with no orders.
+    pub fn new() -> Self {
+        Self {
+            orders: Arc::new(watch::Sender::new(HashMap::new())),
+        }
+    }
+
+    /// Returns the tracked state of order `id`.
+    pub fn get(&self, id: u64) -> Option<OrderState> {
+        self.orders.borrow().get(&id).cloned()
+    }
+
+    /// Returns every tracked order, in no particular order.
+    pub fn orders(&self) -> Vec<OrderState> {
+        self.orders.borrow().values().cloned().collect()
+    }
+
+    /// Returns the tracked orders that have not reached a terminal status.
+    pub fn open_orders(&self) -> Vec<OrderState> {
+        self.orders
+            .borrow()
+            .values()
+            .filter(|order| !order.is_terminal())
+            .cloned()
+            .collect()
+    }
+
+    /// Applies an order or fill event and returns the updated state of
+    /// its order. Other events are ignored and return `None`.
+    pub fn apply(&self, event: &AccountEvent) -> Option<OrderState> {
+        match event {
+            AccountEvent::Order(order) => {
+                Some(self.update(order.id, |state| state.apply_order_event(order)))
+            }
+            AccountEvent::Fill(fill) => {
+                Some(self.update(fill.order_id?, |state| state.apply_fill_event(fill)))
+            }
+            _ => None,
+        }
+    }
+
+    fn update(&self, id: u64, apply: impl FnOnce(&mut OrderState)) -> OrderState {
+        let mut updated = None;
+        self.orders.send_modify(|orders| {
+            let state = orders.entry(id).or_insert_with(|| OrderState::new(id));
+            apply(state);
+            state.updated_at = Utc::now();
+            updated = Some(state.clone());
+        });
+        updated.expect("send_modify runs the closure")
+    }
+
+    /// Overwrites the tracked state with a REST orders snapshot and
+    /// returns how many orders it contained.
+    pub fn reconcile(&self, response: &GetAccountOrdersResponse) -> usize {
+        let orders = response.orders().orders();
+        self.orders.send_modify(|tracked| {
+            let now = Utc::now();
+            for order in orders {
+                let id = u64::from(order.id());
+                let state = tracked.entry(id).or_insert_with(|| OrderState::new(id));
+                state.apply_rest_order(order);
+                state.updated_at = now;
+            }
+        });
+        debug!(
+            orders = orders.len(),
+            "reconciled orders from REST snapshot"
+        );
+        orders.len()
+    }
+
+    /// Fetches every page of `account_number`'s orders and
+    /// [reconciles](Self::reconcile) them, returning how many orders were
+    /// fetched. Call this after each (re)connect of the account stream so
+    /// updates missed while it was down are not lost.
+    ///
+    /// # Errors
+    /// Returns the error of the first failing `get_account_orders` call;
+    /// pages fetched before it stay applied.
+    pub async fn reconcile_from_rest(
+        &self,
+        client: &TradierRestClient,
+        account_number: &AccountNumber,
+    ) -> Result<usize> {
+        let limit = Limit::new(RECONCILE_PAGE_SIZE);
+        let include_tags = IncludeTags::new(false);
+        let mut reconciled = 0;
+        let mut page = 1;
+        loop {
+            let response = client
+                .get_account_orders(account_number, &Page::new(page), &limit, &include_tags)
+                .await?;
+            reconciled += self.reconcile(&response);
+            if u32::try_from(page).is_ok_and(|page| page >= response.orders().total_pages()) {
+                return Ok(reconciled);
+            }
+            page += 1;
+        }
+    }
+
+    /// Applies every item of `events` and passes it through unchanged.
+    pub fn track<S>(&self, events: S) -> impl Stream<Item = Result<AccountEvent>> + use<S>
+    where
+        S: Stream<Item = Result<AccountEvent>>,
+    {
+        let tracker = self.clone();
+        events.map(move |item| {
+            if let Ok(event) = &item {
+                tracker.apply(event);
+            }
+            item
+        })
+    }
+
+    /// Follows each account stream yielded by `connections` in turn,
+    /// [reconciling](Self::reconcile_from_rest) `account_number`'s orders
+    /// before applying the stream's events, and passes the events through.
+    ///
+    /// `connections` yields a new stream for every (re)connect, e.g. by
+    /// opening a fresh [`crate::wssession::AccountSession`] each time the
+    /// previous stream ends. A failed connection or reconciliation is
+    /// yielded as `Err` and tracking continues: after a failed
+    /// reconciliation the new stream is still followed.
+    pub fn track_with_reconcile<'a, C, S>(
+        &self,
+        client: &'a TradierRestClient,
+        account_number: &'a AccountNumber,
+        connections: C,
+    ) -> impl Stream<Item = Result<AccountEvent>> + 'a
+    where
+        C: Stream<Item = Result<S>> + 'a,
+        S: Stream<Item = Result<AccountEvent>> + 'a,
+    {
+        let tracker = self.clone();
+        connections.flat_map(move |connection| match connection {
+            Ok(events) => {
+                let reconciled = {
+                    let tracker = tracker.clone();
+                    async move {
+                        tracker
+                            .reconcile_from_rest(client, account_number)
+                            .await
+                            .err()
+                            .map(Err)
+                    }
+                };
+                Either::Left(
+                    stream::once(reconciled)
+                        .filter_map(std::future::ready)
+                        .chain(tracker.track(events)),
+                )
+            }
+            Err(e) => Either::Right(stream::once(std::future::ready(Err(e)))),
+        })
+    }
+
+    /// Waits until order `id` reaches a terminal status and returns its
+    /// final state. Resolves immediately if it already has; waits for the
+    /// order to appear if it is not tracked yet. Wrap in
+    /// `tokio::time::timeout` to bound the wait.
+    pub async fn wait_for_terminal(&self, id: u64) -> OrderState {
+        let mut orders = self.orders.subscribe();
+        let orders = orders
+            .wait_for(|orders| orders.get(&id).is_some_and(OrderState::is_terminal))
+            .await
+            .expect("the tracker owns the sender");
+        orders[&id].clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use futures_util::stream;
+    use mockito::Server;
+
+    use super::*;
+    use crate::Error;
+    use crate::utils::tests::create_test_config;
+
+    fn event(json: &str) -> AccountEvent {
+        AccountEvent::from_json(json).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_track_folds_order_and_fill_events() {
+        let tracker = OrderTracker::new();
+        let events = stream::iter([
+            Ok(event(
+                r#"{"event":"order","id":7,"status":"open","symbol":"SPY","quantity":10.0}"#,
+            )),
+            Ok(event(
+                r#"{"event":"fill","order_id":7,"quantity":4.0,"price":100.0}"#,
+            )),
+            Ok(event(
+                r#"{"event":"fill","order_id":7,"quantity":2.0,"price":103.0}"#,
+            )),
+        ]);
+        assert_eq!(tracker.track(events).count().await, 3);
+
+        let order = tracker.get(7).unwrap();
+        assert_eq!(order.status, OrderStatus::PartiallyFilled);
+        assert_eq!(order.filled_quantity, 6.0);
+        assert_eq!(order.avg_fill_price, Some(101.0));
+        assert_eq!(order.remaining_quantity(), Some(4.0));
+        assert_eq!(tracker.open_orders().len(), 1);
+
+        let waiter = tokio::spawn({
+            let tracker = tracker.clone();
+            async move { tracker.wait_for_terminal(7).await }
+        });
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert!(!waiter.is_finished());
+
+        tracker.apply(&event(
+            r#"{"event":"order","id":7,"status":"filled","executed_quantity":10.0,"avg_fill_price":101.5}"#,
+        ));
+        let order = tokio::time::timeout(Duration::from_secs(1), waiter)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(order.is_terminal());
+        assert_eq!(order.filled_quantity, 10.0);
+        assert_eq!(order.avg_fill_price, Some(101.5));
+        assert_eq!(order.symbol.as_deref(), Some("SPY"));
+        assert!(tracker.open_orders().is_empty());
+    }
+
+    #[test]
+    fn test_fills_are_not_counted_on_top_of_reported_quantities() {
+        let tracker = OrderTracker::new();
+        tracker.apply(&event(
+            r#"{"event":"order","id":8,"status":"partially_filled","quantity":10.0,"executed_quantity":4.0,"avg_fill_price":100.0}"#,
+        ));
+        let order = tracker
+            .apply(&event(
+                r#"{"event":"fill","order_id":8,"quantity":4.0,"price":100.0}"#,
+            ))
+            .unwrap();
+        assert_eq!(order.filled_quantity, 4.0);
+        assert_eq!(order.avg_fill_price, Some(100.0));
+        assert_eq!(order.status, OrderStatus::PartiallyFilled);
+
+        // Fills beyond the reported total are new and move the order on.
+        let order = tracker
+            .apply(&event(
+                r#"{"event":"fill","order_id":8,"quantity":2.0,"price":103.0}"#,
+            ))
+            .unwrap();
+        assert_eq!(order.filled_quantity, 6.0);
+        assert_eq!(order.avg_fill_price, Some(101.0));
+        let order = tracker
+            .apply(&event(
+                r#"{"event":"fill","order_id":8,"quantity":4.0,"price":101.0}"#,
+            ))
+            .unwrap();
+        assert_eq!(order.filled_quantity, 10.0);
+        assert_eq!(order.status, OrderStatus::Filled);
+
+        // A fill seen before the order event is replaced by its total.
+        tracker.apply(&event(
+            r#"{"event":"fill","order_id":9,"quantity":2.0,"price":50.0}"#,
+        ));
+        let order = tracker
+            .apply(&event(
+                r#"{"event":"order","id":9,"status":"partially_filled","quantity":5.0,"remaining_quantity":3.0}"#,
+            ))
+            .unwrap();
+        assert_eq!(order.filled_quantity, 2.0);
+        assert_eq!(order.remaining_quantity(), Some(3.0));
+    }
+
+    #[tokio::test]
+    async fn test_track_with_reconcile_reconciles_before_each_stream() {
+        let mut server = Server::new_async().await;
+        let orders = server
+            .mock("GET", "/v1/accounts/VA000000/orders")
+            .match_query(mockito::Matcher::Any)
+            .with_status(200)
+            .with_body(
+                r#"{"orders":{"order":{"id":7,"type":"limit","symbol":"SPY","side":"buy","quantity":10.0,"status":"open","duration":"day","avg_fill_price":0.0,"exec_quantity":0.0,"create_date":"2024-01-02T15:00:00.000Z","transaction_date":"2024-01-02T15:05:00.000Z","class":"equity"},"page":1,"total_pages":1,"total_orders":1}}"#,
+            )
+            .expect(2)
+            .create_async()
+            .await;
+        let client =
+            TradierRestClient::new(create_test_config().server_url(&server.url()).finish());
+        let account: AccountNumber = "VA000000".parse().unwrap();
+        let tracker = OrderTracker::new();
+        let connections = stream::iter([
+            Ok(stream::iter(vec![Ok(event(
+                r#"{"event":"order","id":8,"status":"open"}"#,
+            ))])),
+            Err(Error::SessionAlreadyExists),
+            Ok(stream::iter(vec![Ok(event(
+                r#"{"event":"order","id":8,"status":"filled"}"#,
+            ))])),
+        ]);
+        let items: Vec<Result<AccountEvent>> = tracker
+            .track_with_reconcile(&client, &account, connections)
+            .collect()
+            .await;
+
+        orders.assert_async().await;
+        assert_eq!(items.len(), 3);
+        assert!(matches!(items[1], Err(Error::SessionAlreadyExists)));
+        assert_eq!(tracker.get(7).unwrap().status, OrderStatus::Open);
+        assert!(tracker.get(8).unwrap().is_terminal());
+    }
+
+    #[tokio::test]
+    async fn test_reconcile_from_rest_applies_missed_updates() {
+        let mut server = Server::new_async().await;
+        let _mock = server
+            .mock("GET", "/v1/accounts/VA000000/orders")
+            .match_query(mockito::Matcher::Any)
+            .with_status(200)
+            .with_body(
+                r#"{"orders":{"order":{"id":7,"type":"limit","symbol":"SPY","side":"buy","quantity":10.0,"status":"canceled","duration":"day","avg_fill_price":99.0,"exec_quantity":3.0,"create_date":"2024-01-02T15:00:00.000Z","transaction_date":"2024-01-02T15:05:00.000Z","class":"equity"},"page":1,"total_pages":1,"total_orders":1}}"#,
+            )
+            .create_async()
+            .await;
+        let client =
+            TradierRestClient::new(create_test_config().server_url(&server.url()).finish());
+        let tracker = OrderTracker::new();
+        tracker.apply(&event(r#"{"event":"order","id":7,"status":"open"}"#));
+
+        let account: AccountNumber = "VA000000".parse().unwrap();
+        assert_eq!(
+            tracker
+                .reconcile_from_rest(&client, &account)
+                .await
+                .unwrap(),
+            1
+        );
+        let order = tracker.get(7).unwrap();
+        assert_eq!(order.status, OrderStatus::Canceled);
+        assert_eq!(
+            (order.filled_quantity, order.avg_fill_price),
+            (3.0, Some(99.0))
+        );
+        assert_eq!(tracker.wait_for_terminal(7).await, order);
+    }
+}
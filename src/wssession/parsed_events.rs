@@ -0,0 +1,299 @@
+//! Parsed counterparts of the market event types.
+//!
+//! The types in [`crate::wssession::events`] mirror the upstream JSON, so
+//! trade, summary, time-and-sales and tradex prices and sizes arrive as
+//! strings and every timestamp is a millisecond-epoch string. The types
+//! here hold the same data as `f64` / `u64` numerics and
+//! `DateTime<Utc>` timestamps, parsed once when the event is converted.
+//!
+//! Convert a whole event with [`MarketEvent::parse`] or a single payload
+//! with `TryFrom`:
+//!
+//! ```
+//! use tradier::wssession::MarketEvent;
+//! use tradier::wssession::parsed_events::ParsedMarketEvent;
+//!
+//! let line = r#"{"type":"trade","symbol":"SPY","exch":"Q","price":"281.12","size":"100","cvol":"2500","date":"1557757189000","last":"281.12"}"#;
+//! let ParsedMarketEvent::Trade(trade) = MarketEvent::from_json(line)?.parse()? else {
+//!     unreachable!()
+//! };
+//! assert_eq!(trade.price, 281.12);
+//! assert_eq!(trade.cvol, 2500);
+//! assert_eq!(trade.date.timestamp_millis(), 1_557_757_189_000);
+//! # Ok::<(), tradier::Error>(())
+//! ```
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::events::{
+    MarketEvent, Quote, Summary, Timesale, Trade, TradeSession, Tradex, parse_epoch_millis,
+    parse_f64, parse_u64,
+};
+use crate::{Error, Result};
+
+/// A [`MarketEvent`] with every numeric and timestamp field parsed.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[non_exhaustive]
+pub enum ParsedMarketEvent {
+    Quote(ParsedQuote),
+    Trade(ParsedTrade),
+    Summary(ParsedSummary),
+    Timesale(ParsedTimesale),
+    Tradex(ParsedTradex),
+    /// [`MarketEvent::Unknown`], passed through as-is.
+    Unknown(serde_json::Value),
+}
+
+/// Parsed [`Quote`].
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct ParsedQuote {
+    pub symbol: String,
+    pub bid: f64,
+    pub bidsz: u64,
+    pub bidexch: String,
+    pub biddate: DateTime<Utc>,
+    pub ask: f64,
+    pub asksz: u64,
+    pub askexch: String,
+    pub askdate: DateTime<Utc>,
+}
+
+/// Parsed [`Trade`].
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct ParsedTrade {
+    pub symbol: String,
+    pub exch: String,
+    pub price: f64,
+    pub size: u64,
+    /// Cumulative day volume.
+    pub cvol: u64,
+    pub date: DateTime<Utc>,
+    pub last: f64,
+}
+
+/// Parsed [`Summary`].
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct ParsedSummary {
+    pub symbol: String,
+    pub open: f64,
+    pub high: f64,
+    pub low: f64,
+    pub prev_close: f64,
+}
+
+/// Parsed [`Timesale`].
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct ParsedTimesale {
+    pub symbol: String,
+    pub exch: String,
+    pub bid: f64,
+    pub ask: f64,
+    pub last: f64,
+    pub size: u64,
+    pub date: DateTime<Utc>,
+    pub seq: u64,
+    pub flag: String,
+    pub cancel: bool,
+    pub correction: bool,
+    pub session: TradeSession,
+}
+
+/// Parsed [`Tradex`].
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct ParsedTradex {
+    pub symbol: String,
+    pub exch: String,
+    pub price: f64,
+    pub size: u64,
+    /// Cumulative day volume.
+    pub cvol: u64,
+    pub date: DateTime<Utc>,
+    pub last: f64,
+}
+
+impl ParsedMarketEvent {
+    /// Returns the symbol the event is for.
+    #[must_use]
+    #[inline]
+    pub fn symbol(&self) -> &str {
+        match self {
+            ParsedMarketEvent::Quote(q) => &q.symbol,
+            ParsedMarketEvent::Trade(t) => &t.symbol,
+            ParsedMarketEvent::Summary(s) => &s.symbol,
+            ParsedMarketEvent::Timesale(ts) => &ts.symbol,
+            ParsedMarketEvent::Tradex(tx) => &tx.symbol,
+            ParsedMarketEvent::Unknown(value) => value
+                .get("symbol")
+                .and_then(serde_json::Value::as_str)
+                .unwrap_or_default(),
+        }
+    }
+}
+
+impl MarketEvent {
+    /// Parses every string-encoded numeric and timestamp field of the
+    /// event.
+    ///
+    /// # Errors
+    /// Returns [`Error::ParseFloat`] or [`Error::ParseInt`] with the
+    /// offending value if a price, size or timestamp is malformed.
+    pub fn parse(&self) -> Result<ParsedMarketEvent> {
+        Ok(match self {
+            MarketEvent::Quote(q) => ParsedMarketEvent::Quote(q.try_into()?),
+            MarketEvent::Trade(t) => ParsedMarketEvent::Trade(t.try_into()?),
+            MarketEvent::Summary(s) => ParsedMarketEvent::Summary(s.try_into()?),
+            MarketEvent::Timesale(ts) => ParsedMarketEvent::Timesale(ts.try_into()?),
+            MarketEvent::Tradex(tx) => ParsedMarketEvent::Tradex(tx.try_into()?),
+            MarketEvent::Unknown(value) => ParsedMarketEvent::Unknown(value.clone()),
+        })
+    }
+}
+
+impl TryFrom<&MarketEvent> for ParsedMarketEvent {
+    type Error = Error;
+
+    fn try_from(event: &MarketEvent) -> Result<Self> {
+        event.parse()
+    }
+}
+
+impl TryFrom<&Quote> for ParsedQuote {
+    type Error = Error;
+
+    fn try_from(quote: &Quote) -> Result<Self> {
+        Ok(ParsedQuote {
+            symbol: quote.symbol.clone(),
+            bid: quote.bid,
+            bidsz: quote.bidsz,
+            bidexch: quote.bidexch.clone(),
+            biddate: parse_epoch_millis(&quote.biddate)?,
+            ask: quote.ask,
+            asksz: quote.asksz,
+            askexch: quote.askexch.clone(),
+            askdate: parse_epoch_millis(&quote.askdate)?,
+        })
+    }
+}
+
+impl TryFrom<&Trade> for ParsedTrade {
+    type Error = Error;
+
+    fn try_from(trade: &Trade) -> Result<Self> {
+        Ok(ParsedTrade {
+            symbol: trade.symbol.clone(),
+            exch: trade.exch.clone(),
+            price: parse_f64(&trade.price)?,
+            size: parse_u64(&trade.size)?,
+            cvol: parse_u64(&trade.cvol)?,
+            date: parse_epoch_millis(&trade.date)?,
+            last: parse_f64(&trade.last)?,
+        })
+    }
+}
+
+impl TryFrom<&Summary> for ParsedSummary {
+    type Error = Error;
+
+    fn try_from(summary: &Summary) -> Result<Self> {
+        Ok(ParsedSummary {
+            symbol: summary.symbol.clone(),
+            open: parse_f64(&summary.open)?,
+            high: parse_f64(&summary.high)?,
+            low: parse_f64(&summary.low)?,
+            prev_close: parse_f64(&summary.prev_close)?,
+        })
+    }
+}
+
+impl TryFrom<&Timesale> for ParsedTimesale {
+    type Error = Error;
+
+    fn try_from(sale: &Timesale) -> Result<Self> {
+        Ok(ParsedTimesale {
+            symbol: sale.symbol.clone(),
+            exch: sale.exch.clone(),
+            bid: parse_f64(&sale.bid)?,
+            ask: parse_f64(&sale.ask)?,
+            last: parse_f64(&sale.last)?,
+            size: parse_u64(&sale.size)?,
+            date: parse_epoch_millis(&sale.date)?,
+            seq: sale.seq,
+            flag: sale.flag.clone(),
+            cancel: sale.cancel,
+            correction: sale.correction,
+            session: sale.session,
+        })
+    }
+}
+
+impl TryFrom<&Tradex> for ParsedTradex {
+    type Error = Error;
+
+    fn try_from(tradex: &Tradex) -> Result<Self> {
+        Ok(ParsedTradex {
+            symbol: tradex.symbol.clone(),
+            exch: tradex.exch.clone(),
+            price: parse_f64(&tradex.price)?,
+            size: parse_u64(&tradex.size)?,
+            cvol: parse_u64(&tradex.cvol)?,
+            date: parse_epoch_millis(&tradex.date)?,
+            last: parse_f64(&tradex.last)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_converts_numerics_and_timestamps() {
+        let quote = MarketEvent::from_json(
+            r#"{"type":"quote","symbol":"C","bid":281.84,"bidsz":60,"bidexch":"M","biddate":"1557757189000","ask":281.85,"asksz":6,"askexch":"Z","askdate":"1557757190000"}"#,
+        )
+        .unwrap();
+        let ParsedMarketEvent::Quote(quote) = quote.parse().unwrap() else {
+            panic!("expected a quote");
+        };
+        assert_eq!(quote.askdate.timestamp_millis(), 1_557_757_190_000);
+
+        let sale = MarketEvent::from_json(
+            r#"{"type":"timesale","symbol":"SPY","exch":"Q","bid":"281.10","ask":"281.20","last":"281.15","size":"300","date":"1557757189000","seq":42,"flag":"","cancel":false,"correction":true,"session":"post"}"#,
+        )
+        .unwrap();
+        let parsed = ParsedMarketEvent::try_from(&sale).unwrap();
+        assert_eq!(parsed.symbol(), "SPY");
+        let ParsedMarketEvent::Timesale(sale) = parsed else {
+            panic!("expected a timesale");
+        };
+        assert_eq!((sale.bid, sale.ask, sale.last), (281.10, 281.20, 281.15));
+        assert_eq!((sale.size, sale.seq), (300, 42));
+        assert!(sale.correction);
+        assert_eq!(sale.session, TradeSession::Post);
+
+        let summary = MarketEvent::from_json(
+            r#"{"type":"summary","symbol":"SPY","open":"280.00","high":"282.00","low":"279.50","prevClose":"279.00"}"#,
+        )
+        .unwrap();
+        let ParsedMarketEvent::Summary(summary) = summary.parse().unwrap() else {
+            panic!("expected a summary");
+        };
+        assert_eq!(summary.prev_close, 279.00);
+    }
+
+    #[test]
+    fn test_parse_reports_the_malformed_value() {
+        let tradex = MarketEvent::from_json(
+            r#"{"type":"tradex","symbol":"SPY","exch":"Q","price":"281.12","size":"100","cvol":"1","date":"yesterday","last":"281.12"}"#,
+        )
+        .unwrap();
+        assert!(matches!(tradex.parse(), Err(Error::ParseInt(v, _)) if v == "yesterday"));
+
+        let trade = MarketEvent::from_json(
+            r#"{"type":"trade","symbol":"SPY","exch":"Q","price":"n/a","size":"100","cvol":"1","date":"1","last":"1"}"#,
+        )
+        .unwrap();
+        assert!(matches!(trade.parse(), Err(Error::ParseFloat(v, _)) if v == "n/a"));
+    }
+}
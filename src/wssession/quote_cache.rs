@@ -0,0 +1,388 @@
+//! Latest-value top-of-book cache fed by market events.
+//!
+//! [`QuoteCache`] keeps one immutable [`QuoteSnapshot`] per symbol behind
+//! an `ArcSwap`. Writers build a new snapshot and swap it in; readers load
+//! the current one without taking a lock, so "what is the bid for X right
+//! now" never waits for the next tick.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use arc_swap::ArcSwap;
+use chrono::{DateTime, Utc};
+use futures_util::stream::{Stream, StreamExt};
+use tracing::trace;
+
+use super::events::{MarketEvent, Quote, Summary, Trade};
+use crate::Result;
+use crate::client::non_blocking::TradierRestClient;
+use crate::market_data::types::{GetQuotesResponse, Symbols};
+use crate::non_blocking::operation::MarketData;
+use crate::utils::OneOrMany;
+
+/// Current top of book and day statistics for one symbol.
+///
+/// Fields stay `None` until an event (or REST seed) has provided them.
+#[derive(Debug, Clone, PartialEq)]
+pub struct QuoteSnapshot {
+    pub symbol: String,
+    pub bid: Option<f64>,
+    pub bid_size: Option<u64>,
+    pub ask: Option<f64>,
+    pub ask_size: Option<u64>,
+    /// Price of the last trade.
+    pub last: Option<f64>,
+    /// Size of the last trade.
+    pub last_size: Option<u64>,
+    /// Cumulative day volume.
+    pub volume: Option<u64>,
+    pub open: Option<f64>,
+    pub high: Option<f64>,
+    pub low: Option<f64>,
+    pub prev_close: Option<f64>,
+    /// When the cache last changed this snapshot.
+    pub updated_at: DateTime<Utc>,
+}
+
+impl QuoteSnapshot {
+    fn empty(symbol: &str) -> Self {
+        QuoteSnapshot {
+            symbol: symbol.to_owned(),
+            bid: None,
+            bid_size: None,
+            ask: None,
+            ask_size: None,
+            last: None,
+            last_size: None,
+            volume: None,
+            open: None,
+            high: None,
+            low: None,
+            prev_close: None,
+            updated_at: Utc::now(),
+        }
+    }
+
+    /// Returns the midpoint of bid and ask when both are known.
+    #[must_use]
+    pub fn mid(&self) -> Option<f64> {
+        Some((self.bid? + self.ask?) / 2.0)
+    }
+
+    /// Returns `ask - bid` when both are known.
+    #[must_use]
+    pub fn spread(&self) -> Option<f64> {
+        Some(self.ask? - self.bid?)
+    }
+
+    fn apply_quote(&mut self, quote: &Quote) {
+        self.bid = Some(quote.bid);
+        self.bid_size = Some(quote.bidsz);
+        self.ask = Some(quote.ask);
+        self.ask_size = Some(quote.asksz);
+    }
+
+    fn apply_trade(&mut self, trade: &Trade) {
+        if let Ok(price) = trade.price_f64() {
+            self.last = Some(price);
+            self.open.get_or_insert(price);
+            self.high = Some(self.high.map_or(price, |high| high.max(price)));
+            self.low = Some(self.low.map_or(price, |low| low.min(price)));
+        }
+        if let Ok(size) = trade.size_u64() {
+            self.last_size = Some(size);
+        }
+        if let Ok(volume) = trade.cvol.parse() {
+            self.volume = Some(volume);
+        }
+    }
+
+    fn apply_summary(&mut self, summary: &Summary) {
+        let parse = |value: &str| value.parse::<f64>().ok();
+        self.open = parse(&summary.open).or(self.open);
+        self.high = parse(&summary.high).or(self.high);
+        self.low = parse(&summary.low).or(self.low);
+        self.prev_close = parse(&summary.prev_close).or(self.prev_close);
+    }
+
+    fn apply_rest(&mut self, quote: &crate::types::Quote) {
+        self.bid = quote.bid.or(self.bid);
+        self.bid_size = quote.bidsize.or(self.bid_size);
+        self.ask = quote.ask.or(self.ask);
+        self.ask_size = quote.asksize.or(self.ask_size);
+        self.last = quote.last.or(self.last);
+        self.last_size = quote.last_volume.or(self.last_size);
+        self.volume = quote.volume.or(self.volume);
+        self.open = quote.open.or(self.open);
+        self.high = quote.high.or(self.high);
+        self.low = quote.low.or(self.low);
+        self.prev_close = quote.prevclose.or(self.prev_close);
+    }
+}
+
+type Slot = Arc<ArcSwap<QuoteSnapshot>>;
+
+/// Concurrent latest-value cache of [`QuoteSnapshot`]s keyed by symbol.
+///
+/// Consumes [`MarketEvent::Quote`] (bid / ask and sizes),
+/// [`MarketEvent::Trade`] (last, size, cumulative volume, and the day
+/// high / low) and [`MarketEvent::Summary`] (day open / high / low /
+/// previous close). Other event types are ignored.
+///
+/// Reads are lock-free: [`Self::get`] returns the snapshot that was current
+/// at the time of the call, which stays valid however many updates follow.
+///
+/// # Example
+/// ```no_run
+/// use std::sync::Arc;
+/// use tradier::Config;
+/// use tradier::non_blocking::Client;
+/// use tradier::types::{Symbol, Symbols};
+/// use tradier::wssession::{MarketSession, MarketSessionPayload, QuoteCache};
+///
+/// # async fn run() -> tradier::Result<()> {
+/// let config = Config::new();
+/// let client = Client::new(config.clone());
+/// let cache = Arc::new(QuoteCache::new());
+/// let symbols = Symbols::new(["SPY".parse::<Symbol>()?]);
+/// cache.seed_from_rest(&client, &symbols).await?;
+///
+/// let session = MarketSession::new(&config).await?;
+/// let names = vec!["SPY".to_string()];
+/// let payload = MarketSessionPayload::recommended(&names, session.get_session_id());
+/// let events = session.event_stream(payload).await?;
+/// tokio::spawn({
+///     let cache = Arc::clone(&cache);
+///     async move { cache.feed(events).await }
+/// });
+///
+/// if let Some(spy) = cache.get("SPY") {
+///     println!("SPY {:?} x {:?}", spy.bid, spy.ask);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Default)]
+pub struct QuoteCache {
+    books: ArcSwap<HashMap<String, Slot>>,
+}
+
+impl QuoteCache {
+    /// Creates an empty cache.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the latest snapshot for `symbol`, if any event or seed has
+    /// been seen for it.
+    pub fn get(&self, symbol: &str) -> Option<Arc<QuoteSnapshot>> {
+        self.books.load().get(symbol).map(|slot| slot.load_full())
+    }
+
+    /// Returns the symbols the cache holds snapshots for.
+    pub fn symbols(&self) -> Vec<String> {
+        self.books.load().keys().cloned().collect()
+    }
+
+    /// Returns the number of symbols in the cache.
+    pub fn len(&self) -> usize {
+        self.books.load().len()
+    }
+
+    /// Returns `true` if the cache holds no symbols.
+    pub fn is_empty(&self) -> bool {
+        self.books.load().is_empty()
+    }
+
+    /// Updates the snapshot of the event's symbol.
+    ///
+    /// # Returns
+    /// `true` if the event type is one the cache tracks.
+    pub fn apply(&self, event: &MarketEvent) -> bool {
+        match event {
+            MarketEvent::Quote(quote) => self.update(&quote.symbol, |s| s.apply_quote(quote)),
+            MarketEvent::Trade(trade) => self.update(&trade.symbol, |s| s.apply_trade(trade)),
+            MarketEvent::Summary(summary) => {
+                self.update(&summary.symbol, |s| s.apply_summary(summary))
+            }
+            _ => return false,
+        }
+        true
+    }
+
+    /// Applies every event of `events` until the stream ends. Stream errors
+    /// are skipped.
+    pub async fn feed<S>(&self, events: S)
+    where
+        S: Stream<Item = Result<MarketEvent>>,
+    {
+        futures_util::pin_mut!(events);
+        while let Some(item) = events.next().await {
+            match item {
+                Ok(event) => {
+                    self.apply(&event);
+                }
+                Err(e) => trace!(error = %e, "quote cache skipped stream error"),
+            }
+        }
+    }
+
+    /// Seeds snapshots from a REST quotes response. Fields the response
+    /// leaves empty keep their cached value.
+    ///
+    /// # Returns
+    /// The number of quotes applied.
+    pub fn seed(&self, response: &GetQuotesResponse) -> usize {
+        let quotes = match &response.quotes.quote {
+            Some(OneOrMany::One(quote)) => std::slice::from_ref(quote),
+            Some(OneOrMany::Many(quotes)) => quotes.as_slice(),
+            None => &[],
+        };
+        for quote in quotes {
+            self.update(&quote.symbol, |s| s.apply_rest(quote));
+        }
+        quotes.len()
+    }
+
+    /// Fetches `symbols` with `GET /v1/markets/quotes` and seeds the cache
+    /// with the result, so snapshots are populated before the first tick.
+    ///
+    /// # Returns
+    /// The number of quotes applied.
+    ///
+    /// # Errors
+    /// Any error of the quotes request.
+    pub async fn seed_from_rest(
+        &self,
+        client: &TradierRestClient,
+        symbols: &Symbols,
+    ) -> Result<usize> {
+        let response = client.get_quotes(symbols, None).await?;
+        Ok(self.seed(&response))
+    }
+
+    fn update(&self, symbol: &str, change: impl Fn(&mut QuoteSnapshot)) {
+        self.slot(symbol).rcu(|current| {
+            let mut next = QuoteSnapshot::clone(current);
+            change(&mut next);
+            next.updated_at = Utc::now();
+            next
+        });
+    }
+
+    fn slot(&self, symbol: &str) -> Slot {
+        if let Some(slot) = self.books.load().get(symbol) {
+            return Arc::clone(slot);
+        }
+        self.books.rcu(|books| {
+            if books.contains_key(symbol) {
+                return Arc::clone(books);
+            }
+            let mut books = HashMap::clone(books);
+            let snapshot = QuoteSnapshot::empty(symbol);
+            books.insert(symbol.to_owned(), Arc::new(ArcSwap::from_pointee(snapshot)));
+            Arc::new(books)
+        });
+        // Symbols are never removed, so the slot is present now.
+        Arc::clone(&self.books.load()[symbol])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures_util::stream;
+
+    fn event(json: &str) -> MarketEvent {
+        MarketEvent::from_json(json).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_feed_builds_top_of_book_and_day_stats() {
+        let cache = QuoteCache::new();
+        let events = stream::iter([
+            Ok(event(
+                r#"{"type":"summary","symbol":"SPY","open":"280.00","high":"282.00","low":"279.50","prevClose":"279.00"}"#,
+            )),
+            Ok(event(
+                r#"{"type":"quote","symbol":"SPY","bid":281.10,"bidsz":5,"bidexch":"Q","biddate":"1","ask":281.20,"asksz":7,"askexch":"Z","askdate":"2"}"#,
+            )),
+            Err(crate::Error::StreamDecodeError("x".into(), "bad".into())),
+            Ok(event(
+                r#"{"type":"trade","symbol":"SPY","exch":"Q","price":"283.00","size":"100","cvol":"5000","date":"3","last":"283.00"}"#,
+            )),
+            Ok(event(
+                r#"{"type":"quote","symbol":"AAPL","bid":190.0,"bidsz":1,"bidexch":"Q","biddate":"1","ask":190.5,"asksz":2,"askexch":"Q","askdate":"1"}"#,
+            )),
+        ]);
+        cache.feed(events).await;
+
+        assert_eq!(cache.len(), 2);
+        let spy = cache.get("SPY").unwrap();
+        assert_eq!(spy.bid, Some(281.10));
+        assert_eq!(spy.ask_size, Some(7));
+        assert_eq!(spy.last, Some(283.00));
+        assert_eq!(spy.last_size, Some(100));
+        assert_eq!(spy.volume, Some(5000));
+        assert_eq!(spy.open, Some(280.00));
+        assert_eq!(spy.high, Some(283.00), "trade above the summary high");
+        assert_eq!(spy.low, Some(279.50));
+        assert_eq!(spy.prev_close, Some(279.00));
+        assert!((spy.mid().unwrap() - 281.15).abs() < 1e-9);
+        assert!(cache.get("MSFT").is_none());
+    }
+
+    #[test]
+    fn test_snapshots_are_immutable_once_loaded() {
+        let cache = QuoteCache::new();
+        let quote = |bid: f64| {
+            event(&format!(
+                r#"{{"type":"quote","symbol":"C","bid":{bid},"bidsz":1,"bidexch":"Q","biddate":"1","ask":99.0,"asksz":1,"askexch":"Q","askdate":"1"}}"#
+            ))
+        };
+        assert!(cache.apply(&quote(1.0)));
+        let before = cache.get("C").unwrap();
+        cache.apply(&quote(2.0));
+        assert_eq!(before.bid, Some(1.0));
+        assert_eq!(cache.get("C").unwrap().bid, Some(2.0));
+        assert!(before.updated_at <= cache.get("C").unwrap().updated_at);
+    }
+
+    #[test]
+    fn test_seed_fills_fields_from_rest_quotes() {
+        let cache = QuoteCache::new();
+        let response: GetQuotesResponse = serde_json::from_str(
+            r#"{"quotes":{"quote":{"symbol":"SPY","description":"SPDR","exch":"P","type":"etf","last":280.5,"volume":1200,"open":279.0,"high":281.0,"low":278.0,"bid":280.4,"ask":280.6,"prevclose":277.0,"bidsize":3,"asksize":4}}}"#,
+        )
+        .unwrap();
+        assert_eq!(cache.seed(&response), 1);
+
+        let spy = cache.get("SPY").unwrap();
+        assert_eq!(spy.last, Some(280.5));
+        assert_eq!(spy.volume, Some(1200));
+        assert_eq!(spy.bid_size, Some(3));
+        assert_eq!(spy.prev_close, Some(277.0));
+        assert_eq!(spy.spread(), Some(280.6 - 280.4));
+    }
+
+    #[test]
+    fn test_concurrent_writers_do_not_lose_symbols() {
+        let cache = Arc::new(QuoteCache::new());
+        let handles: Vec<_> = (0..8)
+            .map(|i| {
+                let cache = Arc::clone(&cache);
+                std::thread::spawn(move || {
+                    for j in 0..50 {
+                        cache.apply(&event(&format!(
+                            r#"{{"type":"quote","symbol":"S{i}-{j}","bid":1.0,"bidsz":1,"bidexch":"Q","biddate":"1","ask":2.0,"asksz":1,"askexch":"Q","askdate":"1"}}"#
+                        )));
+                    }
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+        assert_eq!(cache.len(), 400);
+    }
+}
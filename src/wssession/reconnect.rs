@@ -0,0 +1,445 @@
+//! Supervised market stream that survives dropped connections.
+//!
+//! [`crate::wssession::MarketSession::supervised_event_stream`] wraps the
+//! plain WebSocket decoder in a small state machine:
+//!
+//! - **Connect**: (re)create the session if it was dropped or its id has
+//!   gone stale, patch the session id into the subscription payload, open
+//!   the socket and subscribe.
+//! - **Streaming**: forward decoded [`MarketEvent`]s. Decode failures pass
+//!   through untouched; a close, transport error or idle timeout
+//!   (`Error::StreamIdle`) moves to backoff.
+//! - **Backoff**: sleep according to the [`ReconnectPolicy`], then connect
+//!   again.
+//!
+//! The old session is always released before a new one is requested, so
+//! the supervisor never holds more than one slot of its
+//! [`SessionManager`].
+//!
+//! The attempt counter only resets once a new connection delivers its first
+//! event: a socket that opens and closes straight away (a rejected session,
+//! a server-side refusal) keeps backing off and counts against
+//! [`ReconnectPolicy::max_attempts`].
+
+use std::borrow::Cow;
+use std::collections::VecDeque;
+use std::time::Duration;
+
+use futures_util::stream::{self, BoxStream, Stream, StreamExt};
+use tracing::{info, warn};
+
+use super::events::MarketEvent;
+use super::market::{MarketSession, MarketSessionPayload, connect_market_stream};
+use super::session_manager::SessionManager;
+use crate::config::{Config, StreamingConfig};
+use crate::utils::telemetry;
+use crate::{Error, Result};
+
+/// Upper bound for the default backoff when the configured interval is
+/// shorter than this.
+const DEFAULT_MAX_DELAY: Duration = Duration::from_secs(60);
+
+/// Backoff settings for [`MarketSession::supervised_event_stream`].
+///
+/// The delay before attempt `n` (1-based) is
+/// `initial_delay * 2^(n - 1)`, capped at `max_delay`. The attempt counter
+/// resets once a reconnected stream delivers its first event.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReconnectPolicy {
+    /// Delay before the first reconnect attempt.
+    pub initial_delay: Duration,
+    /// Largest delay between two attempts.
+    pub max_delay: Duration,
+    /// Consecutive failed attempts after which the stream gives up, or
+    /// `None` to retry forever.
+    pub max_attempts: Option<u32>,
+}
+
+#[bon::bon]
+impl ReconnectPolicy {
+    /// Constructs a new `ReconnectPolicy`.
+    ///
+    /// # Arguments
+    /// - `initial_delay`: Delay before the first reconnect attempt.
+    /// - `max_delay`: Cap on the exponential backoff. Defaults to the larger
+    ///   of 60 seconds and `initial_delay`.
+    /// - `max_attempts`: Give up after this many consecutive failures.
+    #[builder(builder_type(vis = "pub"))]
+    fn new(
+        initial_delay: Duration,
+        max_delay: Option<Duration>,
+        max_attempts: Option<u32>,
+    ) -> Self {
+        ReconnectPolicy {
+            initial_delay,
+            max_delay: max_delay.unwrap_or(DEFAULT_MAX_DELAY.max(initial_delay)),
+            max_attempts,
+        }
+    }
+
+    /// Builds a policy from [`StreamingConfig::reconnect_interval`] that
+    /// retries forever.
+    pub fn from_config(config: &StreamingConfig) -> Self {
+        Self::builder()
+            .initial_delay(Duration::from_secs(config.reconnect_interval))
+            .build()
+    }
+
+    /// Returns the delay to wait before the 1-based reconnect `attempt`.
+    pub fn delay_for(&self, attempt: u32) -> Duration {
+        let factor = 2u32.saturating_pow(attempt.saturating_sub(1));
+        self.initial_delay
+            .checked_mul(factor)
+            .map_or(self.max_delay, |delay| delay.min(self.max_delay))
+    }
+}
+
+/// Item yielded by [`MarketSession::supervised_event_stream`].
+#[derive(Debug, Clone)]
+pub enum SupervisedMarketEvent {
+    /// A decoded market event.
+    Event(MarketEvent),
+    /// The connection was lost (or could not be re-established) and the
+    /// supervisor will try again after `delay`.
+    Reconnecting {
+        /// 1-based count of consecutive attempts.
+        attempt: u32,
+        /// Time the supervisor waits before the attempt.
+        delay: Duration,
+        /// Why the previous connection or attempt failed.
+        reason: String,
+    },
+    /// A new session was created and the subscription re-sent.
+    Reconnected {
+        /// The id of the replacement session.
+        session_id: String,
+    },
+}
+
+enum Phase {
+    Connect,
+    Streaming(BoxStream<'static, Result<MarketEvent>>),
+    Backoff(Duration),
+    Done,
+}
+
+struct SupervisorState<'a> {
+    config: Config,
+    payload: MarketSessionPayload<'static>,
+    policy: ReconnectPolicy,
+    session: Option<MarketSession<'a>>,
+    manager: &'a SessionManager,
+    phase: Phase,
+    attempt: u32,
+    pending: VecDeque<Result<SupervisedMarketEvent>>,
+}
+
+impl<'a> SupervisorState<'a> {
+    /// Tears down the current connection and schedules the next attempt,
+    /// or ends the stream once the policy's attempt budget is spent.
+    fn fail(&mut self, reason: String) {
+        // Dropping the session frees its slot for the replacement.
+        self.session = None;
+        self.attempt += 1;
+        if self
+            .policy
+            .max_attempts
+            .is_some_and(|max| self.attempt > max)
+        {
+            warn!(attempts = self.attempt - 1, reason = %reason, "giving up on market stream");
+            self.phase = Phase::Done;
+            self.pending
+                .push_back(Err(Error::ReconnectAttemptsExhausted(
+                    self.attempt - 1,
+                    reason,
+                )));
+            return;
+        }
+        let delay = self.policy.delay_for(self.attempt);
+        warn!(attempt = self.attempt, delay_ms = delay.as_millis() as u64, reason = %reason, "market stream lost, reconnecting");
+        telemetry::record_stream_reconnect(telemetry::TRANSPORT_WS);
+        self.phase = Phase::Backoff(delay);
+        self.pending
+            .push_back(Ok(SupervisedMarketEvent::Reconnecting {
+                attempt: self.attempt,
+                delay,
+                reason,
+            }));
+    }
+
+    async fn connect(&mut self) -> Result<BoxStream<'static, Result<MarketEvent>>> {
+        self.session = Some(match self.session.take() {
+            Some(session) => session.renew_if_expired(&self.config).await?,
+            None => MarketSession::new_with_session_manager(&self.config, self.manager).await?,
+        });
+        let session = self.session.as_ref().expect("session created above");
+        self.payload.session_id = Cow::Owned(session.get_session_id().to_owned());
+        let stream = connect_market_stream(
+            session.get_websocket_url(),
+            &self.payload,
+            session.heartbeat(),
+        )
+        .await?;
+        Ok(stream.boxed())
+    }
+}
+
+/// Drives `session` under the supervision described in the module docs.
+pub(super) fn supervised_market_stream<'a>(
+    session: MarketSession<'a>,
+    config: Config,
+    payload: MarketSessionPayload<'static>,
+    policy: ReconnectPolicy,
+) -> impl Stream<Item = Result<SupervisedMarketEvent>> + 'a {
+    let state = SupervisorState {
+        config,
+        payload,
+        policy,
+        manager: session.session_manager(),
+        session: Some(session),
+        phase: Phase::Connect,
+        attempt: 0,
+        pending: VecDeque::new(),
+    };
+
+    stream::unfold(state, |mut state| async move {
+        loop {
+            if let Some(item) = state.pending.pop_front() {
+                return Some((item, state));
+            }
+            match &mut state.phase {
+                Phase::Done => return None,
+                Phase::Backoff(delay) => {
+                    let delay = *delay;
+                    tokio::time::sleep(delay).await;
+                    state.phase = Phase::Connect;
+                }
+                Phase::Connect => match state.connect().await {
+                    Ok(stream) => {
+                        state.phase = Phase::Streaming(stream);
+                        if state.attempt > 0 {
+                            let session_id = state.payload.session_id.clone().into_owned();
+                            info!("market stream reconnected");
+                            state
+                                .pending
+                                .push_back(Ok(SupervisedMarketEvent::Reconnected { session_id }));
+                        }
+                    }
+                    Err(e) => state.fail(e.to_string()),
+                },
+                Phase::Streaming(stream) => match stream.next().await {
+                    Some(Ok(event)) => {
+                        // Only a connection that actually delivers counts as
+                        // recovered.
+                        state.attempt = 0;
+                        return Some((Ok(SupervisedMarketEvent::Event(event)), state));
+                    }
+                    Some(Err(e @ Error::StreamDecodeError(_, _))) => {
+                        return Some((Err(e), state));
+                    }
+                    Some(Err(e)) => state.fail(e.to_string()),
+                    None => state.fail("connection closed".to_owned()),
+                },
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::{Arc, Mutex};
+
+    use super::*;
+    use crate::utils::tests::{
+        ScriptedWsAction, create_test_config, free_tcp_port, scripted_reconnecting_websocket_server,
+    };
+    use mockito::Server;
+
+    const QUOTE_FRAME: &str = r#"{"type":"quote","symbol":"SPY","bid":281.84,"bidsz":60,"bidexch":"M","biddate":"1557757189000","ask":281.85,"asksz":6,"askexch":"Z","askdate":"1557757190000"}"#;
+    const TRADE_FRAME: &str = r#"{"type":"trade","symbol":"SPY","exch":"J","price":"281.1","size":"100","cvol":"20000","date":"1557757190000","last":"281.1"}"#;
+
+    #[test]
+    fn test_reconnect_policy_backoff_doubles_and_caps() {
+        let policy = ReconnectPolicy::builder()
+            .initial_delay(Duration::from_secs(5))
+            .max_delay(Duration::from_secs(30))
+            .build();
+        assert_eq!(policy.delay_for(1), Duration::from_secs(5));
+        assert_eq!(policy.delay_for(2), Duration::from_secs(10));
+        assert_eq!(policy.delay_for(3), Duration::from_secs(20));
+        assert_eq!(policy.delay_for(4), Duration::from_secs(30));
+        assert_eq!(policy.delay_for(u32::MAX), Duration::from_secs(30));
+    }
+
+    #[test]
+    fn test_reconnect_policy_from_config_uses_reconnect_interval() {
+        let config = crate::utils::tests::create_test_config()
+            .server_url("http://localhost")
+            .finish();
+        let policy = ReconnectPolicy::from_config(&config.streaming);
+        assert_eq!(policy.initial_delay, Duration::from_secs(5));
+        assert_eq!(policy.max_delay, Duration::from_secs(60));
+        assert_eq!(policy.max_attempts, None);
+    }
+
+    #[tokio::test]
+    async fn test_supervised_stream_resubscribes_with_fresh_session_after_close() {
+        let port = free_tcp_port();
+        let mut server = Server::new_async().await;
+        let sessions_created = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&sessions_created);
+        server
+            .mock("POST", "/v1/markets/events/session")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body_from_request(move |_| {
+                let n = counter.fetch_add(1, Ordering::SeqCst);
+                format!(
+                    r#"{{"stream":{{"url":"ws://127.0.0.1:{port}/v1/markets/events","sessionid":"session-{n}"}}}}"#
+                )
+                .into()
+            })
+            .create_async()
+            .await;
+
+        let subscriptions = Arc::new(Mutex::new(Vec::new()));
+        let seen = Arc::clone(&subscriptions);
+        scripted_reconnecting_websocket_server(
+            ("127.0.0.1", port),
+            vec![
+                vec![
+                    ScriptedWsAction::SendText(QUOTE_FRAME),
+                    ScriptedWsAction::SendClose,
+                ],
+                vec![
+                    ScriptedWsAction::SendText(TRADE_FRAME),
+                    ScriptedWsAction::SendClose,
+                ],
+            ],
+            move |_, msg| seen.lock().unwrap().push(msg.to_owned()),
+        )
+        .await;
+
+        let config = create_test_config().server_url(&server.url()).finish();
+        let manager = SessionManager::default();
+        let session = MarketSession::new_with_session_manager(&config, &manager)
+            .await
+            .expect("market session");
+        let symbols = ["SPY".to_string()];
+        let session_id = session.get_session_id().to_owned();
+        let payload = MarketSessionPayload::builder()
+            .symbols(&symbols)
+            .session_id(&session_id)
+            .build();
+        let policy = ReconnectPolicy::builder()
+            .initial_delay(Duration::ZERO)
+            .max_attempts(1)
+            .build();
+
+        let items: Vec<Result<SupervisedMarketEvent>> = session
+            .supervised_event_stream(&config, payload, policy)
+            .collect()
+            .await;
+
+        assert_eq!(items.len(), 6, "unexpected items: {items:?}");
+        assert!(matches!(
+            items[0],
+            Ok(SupervisedMarketEvent::Event(MarketEvent::Quote(_)))
+        ));
+        assert!(matches!(
+            items[1],
+            Ok(SupervisedMarketEvent::Reconnecting { attempt: 1, delay, .. }) if delay.is_zero()
+        ));
+        assert!(matches!(
+            &items[2],
+            Ok(SupervisedMarketEvent::Reconnected { session_id }) if session_id == "session-1"
+        ));
+        assert!(matches!(
+            items[3],
+            Ok(SupervisedMarketEvent::Event(MarketEvent::Trade(_)))
+        ));
+        assert!(matches!(
+            items[4],
+            Ok(SupervisedMarketEvent::Reconnecting { attempt: 1, .. })
+        ));
+        assert!(matches!(
+            items[5],
+            Err(Error::ReconnectAttemptsExhausted(1, _))
+        ));
+
+        let subscriptions = subscriptions.lock().unwrap();
+        assert_eq!(subscriptions.len(), 2);
+        assert!(subscriptions[0].contains(r#""sessionid":"session-0""#));
+        assert!(subscriptions[1].contains(r#""sessionid":"session-1""#));
+        assert!(subscriptions[1].contains(r#""symbols":["SPY"]"#));
+        // The supervisor hands the slot back once it gives up.
+        assert_eq!(manager.active_market_sessions(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_supervised_stream_backs_off_when_connections_close_at_once() {
+        let port = free_tcp_port();
+        let mut server = Server::new_async().await;
+        server
+            .mock("POST", "/v1/markets/events/session")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(format!(
+                r#"{{"stream":{{"url":"ws://127.0.0.1:{port}/v1/markets/events","sessionid":"session"}}}}"#
+            ))
+            .create_async()
+            .await;
+        scripted_reconnecting_websocket_server(
+            ("127.0.0.1", port),
+            vec![vec![ScriptedWsAction::SendClose]; 3],
+            |_, _| {},
+        )
+        .await;
+
+        let config = create_test_config().server_url(&server.url()).finish();
+        let manager = SessionManager::default();
+        let session = MarketSession::new_with_session_manager(&config, &manager)
+            .await
+            .expect("market session");
+        let symbols = ["SPY".to_string()];
+        let session_id = session.get_session_id().to_owned();
+        let payload = MarketSessionPayload::builder()
+            .symbols(&symbols)
+            .session_id(&session_id)
+            .build();
+        let policy = ReconnectPolicy::builder()
+            .initial_delay(Duration::from_millis(10))
+            .max_attempts(2)
+            .build();
+
+        let items: Vec<Result<SupervisedMarketEvent>> = session
+            .supervised_event_stream(&config, payload, policy)
+            .collect()
+            .await;
+
+        assert_eq!(items.len(), 5, "unexpected items: {items:?}");
+        assert!(matches!(
+            items[0],
+            Ok(SupervisedMarketEvent::Reconnecting { attempt: 1, delay, .. })
+                if delay == Duration::from_millis(10)
+        ));
+        assert!(matches!(
+            items[1],
+            Ok(SupervisedMarketEvent::Reconnected { .. })
+        ));
+        assert!(matches!(
+            items[2],
+            Ok(SupervisedMarketEvent::Reconnecting { attempt: 2, delay, .. })
+                if delay == Duration::from_millis(20)
+        ));
+        assert!(matches!(
+            items[3],
+            Ok(SupervisedMarketEvent::Reconnected { .. })
+        ));
+        assert!(matches!(
+            items[4],
+            Err(Error::ReconnectAttemptsExhausted(2, _))
+        ));
+    }
+}
@@ -0,0 +1,556 @@
+//! Record event streams to NDJSON files and replay them.
+//!
+//! [`StreamRecorder`] tees any stream of serializable events (such as
+//! [`crate::wssession::MarketEvent`] or [`crate::wssession::AccountEvent`])
+//! to a newline-delimited JSON file, one [`RecordedEvent`] per line with
+//! the time the event was received. [`StreamReplayer`] reads such a file
+//! back as the same typed stream, at the original pace, accelerated, or as
+//! fast as possible.
+//!
+//! With the `gzip` feature, recordings can be gzip-compressed; the
+//! replayer detects compressed files on its own.
+//!
+//! File I/O is buffered and synchronous: each recorded event is a small
+//! in-memory write. The buffer is flushed once it holds 64 KiB or a second
+//! after the previous flush, so a long-running recording reaches the file
+//! while it is being written, and again when the stream ends.
+
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+use std::pin::Pin;
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Utc};
+use futures_util::stream::{self, Stream, StreamExt};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use crate::{Error, Result};
+
+/// One line of a recording: an event and when it was received.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct RecordedEvent<T> {
+    pub received_at: DateTime<Utc>,
+    pub event: T,
+}
+
+/// Compression of a recording file.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Compression {
+    /// Plain NDJSON.
+    #[default]
+    None,
+    /// Gzip-compressed NDJSON.
+    #[cfg(feature = "gzip")]
+    Gzip,
+}
+
+enum Output {
+    Plain(BufWriter<Box<dyn Write + Send>>),
+    #[cfg(feature = "gzip")]
+    Gzip(flate2::write::GzEncoder<BufWriter<Box<dyn Write + Send>>>),
+}
+
+impl Output {
+    fn writer(&mut self) -> &mut dyn Write {
+        match self {
+            Output::Plain(w) => w,
+            #[cfg(feature = "gzip")]
+            Output::Gzip(w) => w,
+        }
+    }
+}
+
+/// Unflushed bytes after which a [`StreamRecorder`] flushes.
+const FLUSH_BYTES: usize = 64 * 1024;
+/// Time after the previous flush at which a [`StreamRecorder`] flushes.
+const FLUSH_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Writes events to an NDJSON recording.
+///
+/// Events are buffered and flushed every 64 KiB or second, checked as
+/// events are recorded. Gzip recordings are flushed with a sync flush, so
+/// the data written so far can be decompressed before the recording is
+/// finished. Dropping the recorder without [`Self::finish`] still flushes
+/// the buffer and writes the gzip trailer, but ignores any error doing so.
+///
+/// # Example
+/// ```no_run
+/// use futures_util::StreamExt;
+/// use tradier::Config;
+/// use tradier::wssession::{
+///     Compression, MarketEvent, MarketSession, MarketSessionPayload, StreamRecorder,
+/// };
+///
+/// # async fn run() -> tradier::Result<()> {
+/// let config = Config::new();
+/// let session = MarketSession::new(&config).await?;
+/// let symbols = vec!["SPY".to_string()];
+/// let payload = MarketSessionPayload::recommended(&symbols, session.get_session_id());
+/// let events = session.event_stream(payload).await?;
+///
+/// let recorder = StreamRecorder::create("spy.ndjson", Compression::None)?;
+/// let events = recorder.tee(events);
+/// futures_util::pin_mut!(events);
+/// while let Some(event) = events.next().await {
+///     let event: MarketEvent = event?;
+///     println!("{}", event.symbol());
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub struct StreamRecorder {
+    output: Output,
+    unflushed: usize,
+    last_flush: Instant,
+}
+
+impl StreamRecorder {
+    /// Creates (or truncates) the file at `path` and records into it.
+    ///
+    /// # Errors
+    /// - [`Error::IoError`] if the file cannot be created.
+    pub fn create(path: impl AsRef<Path>, compression: Compression) -> Result<Self> {
+        let file = File::create(path)?;
+        Ok(Self::from_writer(file, compression))
+    }
+
+    /// Records into an arbitrary writer.
+    pub fn from_writer(writer: impl Write + Send + 'static, compression: Compression) -> Self {
+        let writer = BufWriter::new(Box::new(writer) as Box<dyn Write + Send>);
+        let output = match compression {
+            Compression::None => Output::Plain(writer),
+            #[cfg(feature = "gzip")]
+            Compression::Gzip => Output::Gzip(flate2::write::GzEncoder::new(
+                writer,
+                flate2::Compression::default(),
+            )),
+        };
+        StreamRecorder {
+            output,
+            unflushed: 0,
+            last_flush: Instant::now(),
+        }
+    }
+
+    /// Appends `event` with the current time as its receive time.
+    ///
+    /// # Errors
+    /// - [`Error::JsonParsingError`] if the event cannot be serialized.
+    /// - [`Error::IoError`] if the write fails.
+    pub fn record<T: Serialize>(&mut self, event: &T) -> Result<()> {
+        self.record_at(Utc::now(), event)
+    }
+
+    /// Appends `event` with an explicit receive time.
+    ///
+    /// # Errors
+    /// Same as [`Self::record`].
+    pub fn record_at<T: Serialize>(&mut self, received_at: DateTime<Utc>, event: &T) -> Result<()> {
+        let line = serde_json::to_string(&RecordedEvent { received_at, event })?;
+        let writer = self.output.writer();
+        writer.write_all(line.as_bytes())?;
+        writer.write_all(b"\n")?;
+        self.unflushed += line.len() + 1;
+        if self.unflushed >= FLUSH_BYTES || self.last_flush.elapsed() >= FLUSH_INTERVAL {
+            self.flush()?;
+        }
+        Ok(())
+    }
+
+    /// Writes buffered events through to the underlying writer. Gzip
+    /// recordings are sync-flushed and stay open for more events.
+    ///
+    /// # Errors
+    /// - [`Error::IoError`] if the flush fails.
+    pub fn flush(&mut self) -> Result<()> {
+        self.output.writer().flush()?;
+        self.unflushed = 0;
+        self.last_flush = Instant::now();
+        Ok(())
+    }
+
+    /// Flushes buffered events and, for gzip recordings, writes the
+    /// compressed stream's trailer.
+    ///
+    /// # Errors
+    /// - [`Error::IoError`] if the flush fails.
+    pub fn finish(self) -> Result<()> {
+        match self.output {
+            Output::Plain(mut w) => w.flush()?,
+            #[cfg(feature = "gzip")]
+            Output::Gzip(w) => w.finish()?.flush()?,
+        }
+        Ok(())
+    }
+
+    /// Passes `events` through unchanged while recording every `Ok` event.
+    ///
+    /// Stream errors are passed through and not recorded. If a write fails,
+    /// the event is still yielded, followed by `Err(Error::IoError(_))`,
+    /// and recording stops while the stream keeps running. The recording
+    /// is finished when `events` ends; dropping the returned stream before
+    /// that drops the recorder, with the behavior described on
+    /// [`StreamRecorder`].
+    pub fn tee<S, T>(self, events: S) -> impl Stream<Item = Result<T>>
+    where
+        S: Stream<Item = Result<T>>,
+        T: Serialize,
+    {
+        struct Tee<S> {
+            events: Pin<Box<S>>,
+            recorder: Option<StreamRecorder>,
+        }
+
+        stream::unfold(
+            Tee {
+                events: Box::pin(events),
+                recorder: Some(self),
+            },
+            |mut tee| async move {
+                let Some(item) = tee.events.next().await else {
+                    let finished = tee.recorder.take().map(StreamRecorder::finish);
+                    return match finished {
+                        Some(Err(e)) => Some((vec![Err(e)], tee)),
+                        _ => None,
+                    };
+                };
+                let mut out = Vec::with_capacity(2);
+                if let (Ok(event), Some(recorder)) = (&item, tee.recorder.as_mut())
+                    && let Err(e) = recorder.record(event)
+                {
+                    warn!(error = %e, "stream recording failed, recording stopped");
+                    tee.recorder = None;
+                    out.push(Err(e));
+                }
+                out.insert(0, item);
+                Some((out, tee))
+            },
+        )
+        .flat_map(stream::iter)
+    }
+}
+
+/// Pace at which a [`StreamReplayer`] yields events.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum ReplaySpeed {
+    /// Wait between events as long as they were apart when recorded.
+    Original,
+    /// Like [`ReplaySpeed::Original`] with the gaps divided by the factor,
+    /// e.g. `Accelerated(10.0)` replays ten times faster. Factors that are
+    /// not positive replay as fast as possible; gaps too long to represent
+    /// are clamped to [`Duration::MAX`].
+    Accelerated(f64),
+    /// Yield events back to back.
+    #[default]
+    AsFastAsPossible,
+}
+
+impl ReplaySpeed {
+    fn delay(self, gap: chrono::TimeDelta) -> Option<Duration> {
+        let gap = gap.to_std().ok()?;
+        match self {
+            ReplaySpeed::Original => Some(gap),
+            ReplaySpeed::Accelerated(factor) if factor > 0.0 => Some(
+                Duration::try_from_secs_f64(gap.as_secs_f64() / factor).unwrap_or(Duration::MAX),
+            ),
+            ReplaySpeed::Accelerated(_) | ReplaySpeed::AsFastAsPossible => None,
+        }
+    }
+}
+
+/// Reads an NDJSON recording written by [`StreamRecorder`].
+///
+/// # Example
+/// ```no_run
+/// use futures_util::StreamExt;
+/// use tradier::wssession::{MarketEvent, ReplaySpeed, StreamReplayer};
+///
+/// # async fn run() -> tradier::Result<()> {
+/// let events = StreamReplayer::open("spy.ndjson")?.replay::<MarketEvent>(ReplaySpeed::Accelerated(60.0));
+/// futures_util::pin_mut!(events);
+/// while let Some(event) = events.next().await {
+///     println!("{}", event?.symbol());
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub struct StreamReplayer {
+    reader: Box<dyn BufRead + Send>,
+}
+
+impl StreamReplayer {
+    /// Opens the recording at `path`. Gzip-compressed files are detected
+    /// from their header.
+    ///
+    /// # Errors
+    /// - [`Error::IoError`] if the file cannot be opened, or if it is
+    ///   gzip-compressed and the `gzip` feature is disabled.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        Self::from_reader(BufReader::new(File::open(path)?))
+    }
+
+    /// Reads a recording from an arbitrary reader. Gzip-compressed input is
+    /// detected from its header.
+    ///
+    /// # Errors
+    /// Same as [`Self::open`].
+    pub fn from_reader(mut reader: impl BufRead + Send + 'static) -> Result<Self> {
+        let is_gzip = reader.fill_buf()?.starts_with(&[0x1f, 0x8b]);
+        if !is_gzip {
+            return Ok(StreamReplayer {
+                reader: Box::new(reader),
+            });
+        }
+        #[cfg(feature = "gzip")]
+        {
+            Ok(StreamReplayer {
+                reader: Box::new(BufReader::new(flate2::bufread::GzDecoder::new(reader))),
+            })
+        }
+        #[cfg(not(feature = "gzip"))]
+        {
+            Err(Error::IoError(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                "gzip-compressed recording requires the `gzip` feature",
+            )))
+        }
+    }
+
+    /// Yields the recorded events with their receive times.
+    ///
+    /// Lines that cannot be decoded are yielded as
+    /// `Err(Error::StreamDecodeError(_, _))` without ending the stream; a
+    /// read failure is yielded as `Err(Error::IoError(_))` and ends it.
+    pub fn replay_recorded<T>(
+        self,
+        speed: ReplaySpeed,
+    ) -> impl Stream<Item = Result<RecordedEvent<T>>>
+    where
+        T: DeserializeOwned,
+    {
+        struct Replay {
+            lines: std::io::Lines<Box<dyn BufRead + Send>>,
+            previous: Option<DateTime<Utc>>,
+        }
+
+        stream::unfold(
+            Replay {
+                lines: self.reader.lines(),
+                previous: None,
+            },
+            move |mut replay| async move {
+                loop {
+                    let line = match replay.lines.next()? {
+                        Ok(line) => line,
+                        Err(e) => {
+                            // Stop after a read failure rather than spin on it.
+                            replay.lines = empty_lines();
+                            return Some((Err(Error::IoError(e)), replay));
+                        }
+                    };
+                    if line.trim().is_empty() {
+                        continue;
+                    }
+                    let recorded: RecordedEvent<T> = match serde_json::from_str(&line) {
+                        Ok(recorded) => recorded,
+                        Err(e) => {
+                            let e = Error::StreamDecodeError(line, e.to_string());
+                            return Some((Err(e), replay));
+                        }
+                    };
+                    if let Some(delay) = replay
+                        .previous
+                        .and_then(|previous| speed.delay(recorded.received_at - previous))
+                    {
+                        tokio::time::sleep(delay).await;
+                    }
+                    replay.previous = Some(recorded.received_at);
+                    return Some((Ok(recorded), replay));
+                }
+            },
+        )
+    }
+
+    /// Yields the recorded events as the same typed stream that was
+    /// recorded. Errors behave as in [`Self::replay_recorded`].
+    pub fn replay<T>(self, speed: ReplaySpeed) -> impl Stream<Item = Result<T>>
+    where
+        T: DeserializeOwned,
+    {
+        self.replay_recorded(speed)
+            .map(|item| item.map(|recorded| recorded.event))
+    }
+}
+
+fn empty_lines() -> std::io::Lines<Box<dyn BufRead + Send>> {
+    (Box::new(std::io::empty()) as Box<dyn BufRead + Send>).lines()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use super::*;
+    use crate::wssession::{AccountEvent, MarketEvent};
+
+    /// A writer whose contents stay readable after the recorder drops it.
+    #[derive(Clone, Default)]
+    struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for SharedBuffer {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl SharedBuffer {
+        fn contents(&self) -> Vec<u8> {
+            self.0.lock().unwrap().clone()
+        }
+    }
+
+    fn market_events() -> Vec<MarketEvent> {
+        [
+            r#"{"type":"quote","symbol":"C","bid":281.84,"bidsz":60,"bidexch":"M","biddate":"1","ask":281.85,"asksz":6,"askexch":"Z","askdate":"2"}"#,
+            r#"{"type":"trade","symbol":"SPY","exch":"Q","price":"281.12","size":"100","cvol":"1","date":"3","last":"281.12"}"#,
+        ]
+        .into_iter()
+        .map(|line| MarketEvent::from_json(line).unwrap())
+        .collect()
+    }
+
+    async fn round_trip(compression: Compression) {
+        let buffer = SharedBuffer::default();
+        let recorder = StreamRecorder::from_writer(buffer.clone(), compression);
+        let upstream = stream::iter(vec![
+            Ok(market_events()[0].clone()),
+            Err(Error::StreamDecodeError("junk".into(), "bad".into())),
+            Ok(market_events()[1].clone()),
+        ]);
+        let passed: Vec<Result<MarketEvent>> = recorder.tee(upstream).collect().await;
+        assert_eq!(passed.len(), 3, "tee passes every item through");
+
+        let replayer =
+            StreamReplayer::from_reader(std::io::Cursor::new(buffer.contents())).unwrap();
+        let replayed: Vec<MarketEvent> = replayer
+            .replay(ReplaySpeed::AsFastAsPossible)
+            .map(|event| event.unwrap())
+            .collect()
+            .await;
+        assert_eq!(replayed, market_events());
+    }
+
+    #[tokio::test]
+    async fn test_tee_records_ok_events_and_replays_them() {
+        round_trip(Compression::None).await;
+    }
+
+    #[cfg(feature = "gzip")]
+    #[tokio::test]
+    async fn test_gzip_recording_round_trips() {
+        round_trip(Compression::Gzip).await;
+    }
+
+    #[tokio::test]
+    async fn test_replay_paces_by_receive_times() {
+        let buffer = SharedBuffer::default();
+        let mut recorder = StreamRecorder::from_writer(buffer.clone(), Compression::None);
+        let start = Utc::now();
+        let event: AccountEvent =
+            serde_json::from_str(r#"{"event":"drop","reason":"expired"}"#).unwrap();
+        recorder.record_at(start, &event).unwrap();
+        recorder
+            .record_at(start + chrono::TimeDelta::milliseconds(400), &event)
+            .unwrap();
+        recorder.finish().unwrap();
+
+        let replay = |speed| {
+            let replayer =
+                StreamReplayer::from_reader(std::io::Cursor::new(buffer.contents())).unwrap();
+            async move {
+                let started = tokio::time::Instant::now();
+                let items: Vec<Result<RecordedEvent<AccountEvent>>> =
+                    replayer.replay_recorded(speed).collect().await;
+                assert_eq!(items.len(), 2);
+                assert_eq!(
+                    items[1].as_ref().unwrap().received_at - items[0].as_ref().unwrap().received_at,
+                    chrono::TimeDelta::milliseconds(400)
+                );
+                started.elapsed()
+            }
+        };
+        assert!(replay(ReplaySpeed::Original).await >= Duration::from_millis(400));
+        assert!(replay(ReplaySpeed::Accelerated(4.0)).await < Duration::from_millis(350));
+        assert!(replay(ReplaySpeed::AsFastAsPossible).await < Duration::from_millis(50));
+    }
+
+    #[test]
+    fn test_recorder_flushes_before_finish() {
+        let buffer = SharedBuffer::default();
+        let mut recorder = StreamRecorder::from_writer(buffer.clone(), Compression::None);
+        let event = &market_events()[1];
+        let at = Utc::now();
+        let line = serde_json::to_string(&RecordedEvent {
+            received_at: at,
+            event,
+        })
+        .unwrap();
+        recorder.record_at(at, event).unwrap();
+        assert!(buffer.contents().is_empty(), "small writes stay buffered");
+        let mut written = line.len() + 1;
+        while written < FLUSH_BYTES {
+            recorder.record_at(at, event).unwrap();
+            written += line.len() + 1;
+        }
+        assert_eq!(buffer.contents().len(), written);
+
+        #[cfg(feature = "gzip")]
+        {
+            let buffer = SharedBuffer::default();
+            let mut recorder = StreamRecorder::from_writer(buffer.clone(), Compression::Gzip);
+            recorder.record(event).unwrap();
+            recorder.flush().unwrap();
+            let mut decoded = String::new();
+            let compressed = buffer.contents();
+            let mut decoder = flate2::read::GzDecoder::new(&compressed[..]);
+            // The trailer is missing until `finish`, so only the data is checked.
+            let _ = std::io::Read::read_to_string(&mut decoder, &mut decoded);
+            assert!(decoded.contains("\"SPY\""));
+        }
+    }
+
+    #[test]
+    fn test_replay_delay_clamps_tiny_factors() {
+        let gap = chrono::TimeDelta::seconds(10);
+        assert_eq!(
+            ReplaySpeed::Accelerated(f64::MIN_POSITIVE).delay(gap),
+            Some(Duration::MAX)
+        );
+        assert_eq!(
+            ReplaySpeed::Accelerated(2.0).delay(gap),
+            Some(Duration::from_secs(5))
+        );
+        assert_eq!(ReplaySpeed::Accelerated(f64::NAN).delay(gap), None);
+    }
+
+    #[tokio::test]
+    async fn test_replay_yields_decode_errors_and_continues() {
+        let data = "not json\n\n{\"received_at\":\"2024-01-01T00:00:00Z\",\"event\":{\"type\":\"summary\",\"symbol\":\"SPY\",\"open\":\"1\",\"high\":\"2\",\"low\":\"0.5\",\"prevClose\":\"1\"}}\n";
+        let replayer = StreamReplayer::from_reader(std::io::Cursor::new(data)).unwrap();
+        let items: Vec<Result<MarketEvent>> = replayer
+            .replay(ReplaySpeed::AsFastAsPossible)
+            .collect()
+            .await;
+        assert_eq!(items.len(), 2);
+        assert!(matches!(items[0], Err(Error::StreamDecodeError(..))));
+        assert!(matches!(items[1], Ok(MarketEvent::Summary(_))));
+    }
+}
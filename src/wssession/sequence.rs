@@ -0,0 +1,327 @@
+//! Sequence checking for time-and-sales events.
+//!
+//! Every [`Timesale`] carries a per-symbol `seq` number. After a reconnect
+//! the stream may replay prints already seen, or skip some entirely.
+//! [`SequenceTracker`] remembers the last sequence number per symbol, drops
+//! replayed prints and reports holes as [`SequenceGap`]s, with the time
+//! window to backfill through `GET /v1/markets/timesales`. When upstream
+//! numbering restarts, e.g. on a new trading day, the tracker starts over
+//! from the new sequence number instead of dropping every later print.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+
+use chrono::{DateTime, Utc};
+use futures_util::stream::{self, Stream, StreamExt};
+use tracing::{debug, warn};
+
+use super::events::{MarketEvent, Timesale, parse_epoch_millis};
+use crate::Result;
+
+/// A hole in a symbol's time-and-sales sequence.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SequenceGap {
+    pub symbol: String,
+    /// First missing sequence number.
+    pub expected: u64,
+    /// Sequence number that arrived instead.
+    pub received: u64,
+    /// Time of the last print before the gap, if it could be parsed.
+    pub from: Option<DateTime<Utc>>,
+    /// Time of the print after the gap, if it could be parsed.
+    pub to: Option<DateTime<Utc>>,
+}
+
+impl SequenceGap {
+    /// Returns how many sequence numbers are missing.
+    #[must_use]
+    pub fn missing(&self) -> u64 {
+        self.received - self.expected
+    }
+}
+
+/// Backwards jump in sequence numbers treated as a restart rather than a
+/// replay, even when the print's time does not move forward.
+const RESTART_GAP: u64 = 10_000;
+
+/// Outcome of [`SequenceTracker::observe`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SequenceStatus {
+    /// First print seen for the symbol.
+    First,
+    /// The print directly follows the previous one.
+    InOrder,
+    /// The print's sequence number was already seen.
+    Duplicate,
+    /// Upstream numbering restarted: the sequence number went backwards
+    /// while the print is newer than the last one, or by at least 10,000.
+    /// The tracker continues from this print.
+    Restarted,
+    /// Sequence numbers were skipped before this print.
+    Gap(SequenceGap),
+}
+
+/// Item of [`SequenceTracker::track`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum SequencedEvent {
+    /// An event from the upstream stream, duplicates removed.
+    Event(MarketEvent),
+    /// Prints are missing before the next [`SequencedEvent::Event`] for
+    /// that symbol.
+    Gap(SequenceGap),
+}
+
+#[derive(Debug, Clone, Copy)]
+struct LastSeen {
+    seq: u64,
+    time: Option<DateTime<Utc>>,
+}
+
+/// Tracks per-symbol [`Timesale`] sequence numbers.
+///
+/// The tracker is a cheap handle: clones share the same state, so one
+/// clone can drive [`Self::track`] while another inspects or resets it.
+///
+/// # Example
+/// ```no_run
+/// use futures_util::StreamExt;
+/// use tradier::Config;
+/// use tradier::wssession::{
+///     MarketSession, MarketSessionFilter, MarketSessionPayload, SequenceTracker, SequencedEvent,
+/// };
+///
+/// # async fn run() -> tradier::Result<()> {
+/// let config = Config::new();
+/// let session = MarketSession::new(&config).await?;
+/// let symbols = vec!["SPY".to_string()];
+/// let filters = [MarketSessionFilter::TIMESALE];
+/// let payload = MarketSessionPayload::builder()
+///     .symbols(&symbols)
+///     .filters(&filters)
+///     .session_id(session.get_session_id())
+///     .build();
+/// let events = SequenceTracker::new().track(session.event_stream(payload).await?);
+/// futures_util::pin_mut!(events);
+/// while let Some(item) = events.next().await {
+///     match item? {
+///         SequencedEvent::Event(event) => println!("{event:?}"),
+///         SequencedEvent::Gap(gap) => {
+///             eprintln!("{} missed {} prints between {:?} and {:?}", gap.symbol, gap.missing(), gap.from, gap.to);
+///         }
+///     }
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct SequenceTracker {
+    last: Arc<Mutex<HashMap<String, LastSeen>>>,
+}
+
+impl SequenceTracker {
+    /// Creates a tracker that has seen no prints.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn last(&self) -> MutexGuard<'_, HashMap<String, LastSeen>> {
+        self.last.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Returns the sequence number of the last print tracked for `symbol`.
+    pub fn last_seq(&self, symbol: &str) -> Option<u64> {
+        self.last().get(symbol).map(|last| last.seq)
+    }
+
+    /// Forgets `symbol`, so its next print starts a new sequence. Restarts
+    /// are detected on their own; use this when that detection does not
+    /// fit, e.g. after a restart with only a small backwards jump and no
+    /// usable print time.
+    pub fn reset(&self, symbol: &str) {
+        self.last().remove(symbol);
+    }
+
+    /// Records `sale` and classifies its sequence number.
+    pub fn observe(&self, sale: &Timesale) -> SequenceStatus {
+        let time = parse_epoch_millis(&sale.date).ok();
+        let current = LastSeen {
+            seq: sale.seq,
+            time,
+        };
+        let mut all = self.last();
+        let Some(last) = all.get_mut(&sale.symbol) else {
+            all.insert(sale.symbol.clone(), current);
+            return SequenceStatus::First;
+        };
+        if sale.seq <= last.seq {
+            let newer = matches!((time, last.time), (Some(now), Some(then)) if now > then);
+            if newer || last.seq - sale.seq >= RESTART_GAP {
+                *last = current;
+                return SequenceStatus::Restarted;
+            }
+            return SequenceStatus::Duplicate;
+        }
+        let previous = std::mem::replace(last, current);
+        if sale.seq == previous.seq + 1 {
+            return SequenceStatus::InOrder;
+        }
+        SequenceStatus::Gap(SequenceGap {
+            symbol: sale.symbol.clone(),
+            expected: previous.seq + 1,
+            received: sale.seq,
+            from: previous.time,
+            to: time,
+        })
+    }
+
+    /// Drops duplicate time-and-sales prints from `events` and yields a
+    /// [`SequencedEvent::Gap`] ahead of the first print after a hole.
+    /// Prints that restart the sequence are passed through. Other event
+    /// types and stream errors pass through unchanged.
+    pub fn track<S>(&self, events: S) -> impl Stream<Item = Result<SequencedEvent>> + use<S>
+    where
+        S: Stream<Item = Result<MarketEvent>>,
+    {
+        let tracker = self.clone();
+        events.flat_map(move |item| {
+            let items = match item {
+                Ok(MarketEvent::Timesale(sale)) => match tracker.observe(&sale) {
+                    SequenceStatus::Duplicate => {
+                        debug!(symbol = %sale.symbol, seq = sale.seq, "dropping duplicate timesale");
+                        vec![]
+                    }
+                    SequenceStatus::Gap(gap) => {
+                        warn!(
+                            symbol = %gap.symbol,
+                            expected = gap.expected,
+                            received = gap.received,
+                            "timesale sequence gap"
+                        );
+                        vec![
+                            Ok(SequencedEvent::Gap(gap)),
+                            Ok(SequencedEvent::Event(MarketEvent::Timesale(sale))),
+                        ]
+                    }
+                    SequenceStatus::Restarted => {
+                        debug!(symbol = %sale.symbol, seq = sale.seq, "timesale sequence restarted");
+                        vec![Ok(SequencedEvent::Event(MarketEvent::Timesale(sale)))]
+                    }
+                    SequenceStatus::First | SequenceStatus::InOrder => {
+                        vec![Ok(SequencedEvent::Event(MarketEvent::Timesale(sale)))]
+                    }
+                },
+                other => vec![other.map(SequencedEvent::Event)],
+            };
+            stream::iter(items)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn timesale(symbol: &str, seq: u64, millis: i64) -> MarketEvent {
+        MarketEvent::from_json(&format!(
+            r#"{{"type":"timesale","symbol":"{symbol}","exch":"Q","bid":"1","ask":"2","last":"1.5","size":"10","date":"{millis}","seq":{seq},"flag":"","cancel":false,"correction":false,"session":"normal"}}"#
+        ))
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_track_drops_duplicates_and_reports_gaps() {
+        let quote = MarketEvent::from_json(
+            r#"{"type":"quote","symbol":"SPY","bid":1.0,"bidsz":1,"bidexch":"Q","biddate":"1","ask":2.0,"asksz":1,"askexch":"Q","askdate":"1"}"#,
+        )
+        .unwrap();
+        let events = stream::iter(vec![
+            Ok(timesale("SPY", 10, 1_000)),
+            Ok(timesale("SPY", 11, 2_000)),
+            Ok(quote),
+            Ok(timesale("AAPL", 3, 2_500)),
+            // Reconnect replays the last prints, then skips ahead.
+            Ok(timesale("SPY", 10, 1_000)),
+            Ok(timesale("SPY", 11, 2_000)),
+            Ok(timesale("SPY", 15, 9_000)),
+            Ok(timesale("AAPL", 4, 9_500)),
+        ]);
+        let items: Vec<SequencedEvent> = SequenceTracker::new()
+            .track(events)
+            .map(|item| item.unwrap())
+            .collect()
+            .await;
+
+        let seqs: Vec<String> = items
+            .iter()
+            .map(|item| match item {
+                SequencedEvent::Event(MarketEvent::Timesale(t)) => format!("{}{}", t.symbol, t.seq),
+                SequencedEvent::Event(other) => other.symbol().to_owned(),
+                SequencedEvent::Gap(gap) => format!("gap {}..{}", gap.expected, gap.received),
+            })
+            .collect();
+        assert_eq!(
+            seqs,
+            [
+                "SPY10",
+                "SPY11",
+                "SPY",
+                "AAPL3",
+                "gap 12..15",
+                "SPY15",
+                "AAPL4"
+            ]
+        );
+
+        let SequencedEvent::Gap(gap) = &items[4] else {
+            panic!("expected a gap");
+        };
+        assert_eq!(gap.missing(), 3);
+        assert_eq!(gap.from.unwrap().timestamp_millis(), 2_000);
+        assert_eq!(gap.to.unwrap().timestamp_millis(), 9_000);
+    }
+
+    #[test]
+    fn test_reset_starts_a_new_sequence() {
+        let tracker = SequenceTracker::new();
+        let MarketEvent::Timesale(high) = timesale("SPY", 500, 2) else {
+            unreachable!()
+        };
+        let MarketEvent::Timesale(low) = timesale("SPY", 1, 1) else {
+            unreachable!()
+        };
+        assert_eq!(tracker.observe(&high), SequenceStatus::First);
+        assert_eq!(tracker.observe(&low), SequenceStatus::Duplicate);
+        tracker.reset("SPY");
+        assert_eq!(tracker.observe(&low), SequenceStatus::First);
+        assert_eq!(tracker.last_seq("SPY"), Some(1));
+    }
+
+    #[tokio::test]
+    async fn test_restart_is_detected_and_rebaselined() {
+        let tracker = SequenceTracker::new();
+        let events = stream::iter(vec![
+            Ok(timesale("SPY", 90_000, 1_000)),
+            Ok(timesale("SPY", 90_001, 2_000)),
+            // Next day: numbering starts again from a low value.
+            Ok(timesale("SPY", 5, 86_402_000)),
+            Ok(timesale("SPY", 6, 86_403_000)),
+            Ok(timesale("SPY", 6, 86_403_000)),
+            Ok(timesale("AAPL", 20_000, 1_000)),
+            // No usable time, but far enough back to be a restart.
+            Ok(timesale("AAPL", 1, 0)),
+        ]);
+        let seqs: Vec<u64> = tracker
+            .track(events)
+            .filter_map(|item| async move {
+                match item.unwrap() {
+                    SequencedEvent::Event(MarketEvent::Timesale(t)) => Some(t.seq),
+                    _ => None,
+                }
+            })
+            .collect()
+            .await;
+        assert_eq!(seqs, [90_000, 90_001, 5, 6, 20_000, 1]);
+        assert_eq!(tracker.last_seq("SPY"), Some(6));
+        assert_eq!(tracker.last_seq("AAPL"), Some(1));
+    }
+}
@@ -1,4 +1,3 @@
-use crate::constants::TRADIER_SESSION_TIMEOUT;
 use crate::error::Result;
 use crate::{config::Config, error::Error};
 use chrono::{DateTime, Duration, Utc};
@@ -7,19 +6,25 @@ use serde::{Deserialize, Serialize};
 use std::fmt::Display;
 use tracing::debug;
 
-use super::session_manager::SessionManager;
+use super::session_manager::{SessionGuard, SessionManager};
+#[cfg(feature = "wssession")]
+use super::ws_decode::Heartbeat;
 
 /// Represents a Tradier API session, handling WebSocket streaming configuration for either
 /// account or market data.
-#[allow(dead_code)]
-#[derive(Debug, Clone)]
+#[derive(Debug)]
 pub(crate) struct Session<'a> {
     /// The type of session, either `Account` or `Market`.
     pub session_type: SessionType,
     /// Contains information about the WebSocket stream, including URL and session ID.
     pub stream_info: StreamInfo,
     created_at: DateTime<Utc>,
-    session_manager: &'a SessionManager,
+    expires_at: DateTime<Utc>,
+    /// Keep-alive settings applied to streams opened with this session.
+    #[cfg(feature = "wssession")]
+    heartbeat: Heartbeat,
+    /// Holds this session's slot in its `SessionManager` until dropped.
+    guard: SessionGuard<'a>,
 }
 
 /// Response structure for the Tradier API session request. Holds the stream information.
@@ -41,7 +46,7 @@ pub struct StreamInfo {
 
 /// Specifies the type of Tradier API session, either `Market` for market data
 /// or `Account` for account-related data.
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum SessionType {
     Market,
     Account,
@@ -61,12 +66,12 @@ impl<'a> Session<'a> {
     /// managed by the provided `SessionManager`.
     ///
     /// This method handles the creation of a Tradier WebSocket session for either market or
-    /// account events. It ensures that only one session is active at any given time by leveraging
-    /// the `SessionManager` for singleton enforcement.
+    /// account events. The `SessionManager` caps how many sessions of each type may be open at
+    /// once.
     ///
     /// # Parameters
-    /// - **`session_manager`**: A reference to the `SessionManager` that manages session state and ensures
-    ///   only one active session exists at a time.
+    /// - **`session_manager`**: A reference to the `SessionManager` that tracks open sessions and
+    ///   enforces the per-type limits.
     /// - **`session_type`**: Specifies the type of session to create:
     ///   - `SessionType::Market`: For streaming market data.
     ///   - `SessionType::Account`: For streaming account-related events.
@@ -78,14 +83,16 @@ impl<'a> Session<'a> {
     /// - **`Err(Error)`**: An error if session creation fails.
     ///
     /// # Behavior
-    /// - Acquires a lock from the `SessionManager` to ensure a singleton session.
+    /// - Reserves a slot for `session_type` in the `SessionManager`. The slot is held by the
+    ///   returned `Session` and released when it is dropped or if any later step fails.
     /// - Constructs the appropriate URL based on the session type.
     /// - Sends a POST request to the Tradier API to initialize the session.
     /// - Parses the response to extract the session stream information.
     ///
     /// # Errors
     /// This method will return an error if:
-    /// - **Singleton Restriction**: Another session is already active (`SessionManager` prevents new sessions).
+    /// - **Session Limit**: The `SessionManager` already holds as many sessions of this type as it
+    ///   allows (`Error::SessionAlreadyExists`).
     /// - **Missing Access Token**: The `access_token` field in the provided `Config` is `None`.
     /// - **Network or API Issues**: The HTTP request to create the session fails due to:
     ///   - Network errors.
@@ -99,7 +106,7 @@ impl<'a> Session<'a> {
     /// #[tokio::main]
     /// async fn main() {
     ///     let config = Config::new();
-    ///     let session_manager = SessionManager::new();
+    ///     let session_manager = SessionManager::default();
     ///
     ///     match Session::new_with_session_manager(&session_manager, SessionType::Account, &config).await {
     ///         Ok(session) => {
@@ -113,80 +120,109 @@ impl<'a> Session<'a> {
     /// ```
     ///
     /// # Debugging Information
-    /// The method logs various debug messages, including:
-    /// - The URL used to request the session.
-    /// - Response headers, status code, and body.
+    /// The method logs the session type, endpoint URL and response status
+    /// at debug level. Response headers and bodies are never logged since
+    /// they carry the session id.
     ///
-    /// This can help diagnose issues with API connectivity or authentication.
-    ///
-    /// # Note
-    /// The session must be explicitly released by the `SessionManager` when no longer needed to allow
-    /// creation of new sessions.
     pub(crate) async fn new_with_session_manager(
         session_manager: &'a SessionManager,
         session_type: SessionType,
         config: &Config,
     ) -> Result<Self> {
-        match session_manager.acquire_session() {
-            Ok(_) => {
-                let client = HttpClient::new();
-                let url = match session_type {
-                    SessionType::Market => {
-                        format!("{}/v1/markets/events/session", config.rest_api.base_url)
-                    }
-                    SessionType::Account => {
-                        format!("{}/v1/accounts/events/session", config.rest_api.base_url)
-                    }
-                };
-                debug!("Url to use to get the Session ID: {}", url);
-
-                let access_token = config
-                    .credentials
-                    .access_token
-                    .as_ref()
-                    .ok_or(Error::MissingAccessToken)?;
-
-                let response = client
-                    .post(&url)
-                    .header("Authorization", format!("Bearer {}", access_token))
-                    .header("Accept", "application/json")
-                    .header("Content-Length", "0")
-                    .body("")
-                    .send()
-                    .await?;
-
-                let status = response.status();
-                let headers = response.headers().clone();
-                debug!("Response status: {}", status);
-                debug!("Response headers: {:?}", headers);
-
-                let body = response.text().await?;
-                debug!("Response body: {}", body);
-
-                if status.is_success() {
-                    let session_response: SessionResponse = serde_json::from_str(&body)?;
-                    Ok(Session {
-                        session_type,
-                        stream_info: session_response.stream,
-                        created_at: Utc::now(),
-                        session_manager,
-                    })
-                } else {
-                    session_manager.release_session();
-                    Err(Error::CreateSessionError(session_type, status, body))
-                }
+        let guard = session_manager.acquire_session(session_type)?;
+        Self::create(guard, session_type, config).await
+    }
+
+    /// Performs the session-create request once the slot is held. The slot
+    /// is released when `guard` is dropped, including on every error path.
+    async fn create(
+        guard: SessionGuard<'a>,
+        session_type: SessionType,
+        config: &Config,
+    ) -> Result<Self> {
+        let client = HttpClient::new();
+        let url = match session_type {
+            SessionType::Market => {
+                format!("{}/v1/markets/events/session", config.rest_api.base_url)
+            }
+            SessionType::Account => {
+                format!("{}/v1/accounts/events/session", config.rest_api.base_url)
             }
-            Err(e) => Err(e),
+        };
+
+        let access_token = config
+            .credentials
+            .access_token
+            .as_ref()
+            .ok_or(Error::MissingAccessToken)?;
+
+        let response = client
+            .post(&url)
+            .header("Authorization", format!("Bearer {}", access_token))
+            .header("Accept", "application/json")
+            .header("Content-Length", "0")
+            .body("")
+            .send()
+            .await?;
+
+        let status = response.status();
+        debug!(session_type = %session_type, url = %url, status = %status, "session create response");
+
+        let body = response.text().await?;
+
+        if status.is_success() {
+            let session_response: SessionResponse = serde_json::from_str(&body)?;
+            let created_at = Utc::now();
+            let timeout = Duration::seconds(
+                i64::try_from(config.streaming.session_timeout).unwrap_or(i64::MAX),
+            );
+            Ok(Session {
+                session_type,
+                stream_info: session_response.stream,
+                created_at,
+                expires_at: created_at
+                    .checked_add_signed(timeout)
+                    .unwrap_or(DateTime::<Utc>::MAX_UTC),
+                #[cfg(feature = "wssession")]
+                heartbeat: Heartbeat::from_config(&config.streaming),
+                guard,
+            })
+        } else {
+            Err(Error::CreateSessionError(session_type, status, body))
         }
     }
 
+    /// Returns when the session id was issued.
+    pub fn created_at(&self) -> DateTime<Utc> {
+        self.created_at
+    }
+
+    /// Returns when the session id goes stale, i.e. `created_at` plus
+    /// `StreamingConfig::session_timeout`.
+    pub fn expires_at(&self) -> DateTime<Utc> {
+        self.expires_at
+    }
+
     /// Checks if the session has expired based on the configured session timeout.
     ///
     /// # Returns
-    /// - `true` if the session duration exceeds `TRADIER_SESSION_TIMEOUT`, otherwise `false`.
-    #[allow(dead_code)]
+    /// - `true` once `expires_at` has passed, otherwise `false`.
     pub fn is_expired(&self) -> bool {
-        Utc::now() - self.created_at > Duration::minutes(TRADIER_SESSION_TIMEOUT)
+        Utc::now() >= self.expires_at
+    }
+
+    /// Returns `self` unchanged while it is fresh; otherwise releases it and
+    /// creates a replacement of the same type through the same
+    /// `SessionManager`.
+    pub(crate) async fn renew_if_expired(self, config: &Config) -> Result<Self> {
+        if !self.is_expired() {
+            return Ok(self);
+        }
+        let manager = self.session_manager();
+        let session_type = self.session_type;
+        debug!(session_type = %session_type, "session id expired, creating a new one");
+        drop(self);
+        Self::new_with_session_manager(manager, session_type, config).await
     }
 
     /// Retrieves the WebSocket URL associated with the session.
@@ -204,6 +240,18 @@ impl<'a> Session<'a> {
     pub fn get_session_id(&self) -> &str {
         &self.stream_info.session_id
     }
+
+    /// Returns the keep-alive settings captured from the config the
+    /// session was created with.
+    #[cfg(feature = "wssession")]
+    pub(crate) fn heartbeat(&self) -> Heartbeat {
+        self.heartbeat
+    }
+
+    /// Returns the `SessionManager` holding this session's slot.
+    pub(crate) fn session_manager(&self) -> &'a SessionManager {
+        self.guard.manager()
+    }
 }
 
 #[cfg(test)]
@@ -338,8 +386,9 @@ mod tests_session {
                 .contains("Session already exists")
         );
 
-        // Drop the first session to reset the state
+        // Dropping the first session frees its slot
         drop(session1);
+        assert_eq!(session_manager.active_market_sessions(), 0);
 
         mock.assert_async().await;
     }
@@ -394,6 +443,9 @@ mod tests_session {
                 ws_base_url: "".to_string(),
                 events_path: "".to_string(),
                 reconnect_interval: 5,
+                session_timeout: 300,
+                idle_timeout: 0,
+                ping_interval: 0,
             },
         };
 
@@ -424,6 +476,7 @@ mod tests_session {
             assert_eq!(status.as_u16(), 500);
             assert_eq!(body, "Internal Server Error");
         }
+        assert_eq!(session_manager.active_market_sessions(), 0);
 
         mock.assert_async().await;
     }
@@ -449,4 +502,90 @@ mod tests_session {
 
         mock.assert_async().await;
     }
+
+    #[tokio::test]
+    async fn test_market_and_account_sessions_coexist() {
+        let mut server = Server::new_async().await;
+        for (path, url) in [
+            (
+                "/v1/markets/events/session",
+                "wss://ws.tradier.com/v1/markets/events",
+            ),
+            (
+                "/v1/accounts/events/session",
+                "wss://ws.tradier.com/v1/accounts/events",
+            ),
+        ] {
+            server
+                .mock("POST", path)
+                .with_status(200)
+                .with_header("content-type", "application/json")
+                .with_body(format!(
+                    r#"{{"stream":{{"url":"{url}","sessionid":"id"}}}}"#
+                ))
+                .create_async()
+                .await;
+        }
+
+        let config = create_test_config().server_url(&server.url()).finish();
+        let session_manager = SessionManager::default();
+        let market =
+            Session::new_with_session_manager(&session_manager, SessionType::Market, &config)
+                .await
+                .unwrap();
+        let account =
+            Session::new_with_session_manager(&session_manager, SessionType::Account, &config)
+                .await
+                .unwrap();
+        assert_eq!(session_manager.active_market_sessions(), 1);
+        assert_eq!(session_manager.active_account_sessions(), 1);
+
+        drop(market);
+        drop(account);
+        assert_eq!(session_manager.active_market_sessions(), 0);
+        assert_eq!(session_manager.active_account_sessions(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_renew_if_expired_replaces_only_stale_sessions() {
+        let mut server = Server::new_async().await;
+        let created = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let counter = std::sync::Arc::clone(&created);
+        server
+            .mock("POST", "/v1/markets/events/session")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body_from_request(move |_| {
+                let n = counter.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+                format!(r#"{{"stream":{{"url":"wss://ws.tradier.com","sessionid":"id-{n}"}}}}"#)
+                    .into()
+            })
+            .create_async()
+            .await;
+
+        let mut config = create_test_config().server_url(&server.url()).finish();
+        let session_manager = SessionManager::default();
+        let fresh =
+            Session::new_with_session_manager(&session_manager, SessionType::Market, &config)
+                .await
+                .unwrap();
+        assert!(!fresh.is_expired());
+        assert_eq!(
+            fresh.expires_at() - fresh.created_at(),
+            Duration::seconds(300)
+        );
+        let fresh = fresh.renew_if_expired(&config).await.unwrap();
+        assert_eq!(fresh.get_session_id(), "id-0");
+        drop(fresh);
+
+        config.streaming.session_timeout = 0;
+        let stale =
+            Session::new_with_session_manager(&session_manager, SessionType::Market, &config)
+                .await
+                .unwrap();
+        assert!(stale.is_expired());
+        let renewed = stale.renew_if_expired(&config).await.unwrap();
+        assert_eq!(renewed.get_session_id(), "id-2");
+        assert_eq!(session_manager.active_market_sessions(), 1);
+    }
 }
@@ -1,105 +1,227 @@
 use std::sync::LazyLock;
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::atomic::{AtomicUsize, Ordering};
 
 use crate::Error;
 use crate::Result;
+use crate::config::Config;
 
-/// Manages the existence of a single session within a system.
-///
-/// `SessionManager` is an internal utility that enforces a single active session
-/// at any given time. It is primarily used internally by higher-level abstractions like [`Session`]
-/// to coordinate access to WebSocket sessions or other shared resources.
-///
-/// ### Key Features
-/// - **Thread-Safe**: Uses atomic operations to safely manage session state in multi-threaded environments.
-/// - **Lifecycle Management**: Ensures proper acquisition and release of session state.
-/// - **Internal Use Only**: This type is not exposed outside of the crate. Use [`Session`] or
-///   other public abstractions for interacting with session-related functionality.
+use super::session::SessionType;
+use super::{AccountSession, MarketSession};
+
+/// Limits how many streaming sessions of each [`SessionType`] may be open at
+/// once.
 ///
-/// ### Warning
-/// - Only one `SessionManager` instance should exist in the system. Use the
-///   [`GLOBAL_SESSION_MANAGER`] for global access in multi-threaded contexts.
+/// Market and account sessions are counted separately, so a market stream and
+/// an account stream can run side by side. Each slot is held by a guard
+/// owned by the session itself; dropping a
+/// [`crate::wssession::MarketSession`] or [`crate::wssession::AccountSession`]
+/// (including after a failed stream) frees the slot again.
 ///
-/// ### Internal Use
-/// Developers working within this crate can use `SessionManager` to manage session lifecycle
-/// explicitly. For most external users, session management is handled transparently by [`Session`].
+/// [`crate::wssession::MarketSession::new`] and
+/// [`crate::wssession::AccountSession::new`] share a process-wide manager
+/// that allows one session per type. Applications that need other limits,
+/// or want their session budget scoped to a client object, create their own
+/// manager and open sessions through it.
 ///
 /// # Examples
 ///
-/// ## Internal Use: Acquiring and Releasing a Session
+/// ```no_run
+/// use tradier::Config;
+/// use tradier::wssession::SessionManager;
 ///
-/// The following example demonstrates how crate-internal code can acquire and release a session:
+/// # async fn run() -> tradier::Result<()> {
+/// let config = Config::new();
+/// let sessions = SessionManager::builder().market_limit(2).build();
 ///
-/// ```ignore
-/// use tradier::wssession::session_manager::SessionManager;
+/// let market = sessions.market_session(&config).await?;
+/// let account = sessions.account_session(&config).await?;
+/// assert_eq!(sessions.active_market_sessions(), 1);
 ///
-/// let manager = SessionManager::default();
-/// assert!(manager.acquire_session().is_ok());
-/// assert!(manager.acquire_session().is_err()); // Second acquisition fails
-/// manager.release_session(); // Releases the session
-/// assert!(manager.acquire_session().is_ok()); // Session can be reacquired
+/// drop(market); // frees the market slot
+/// assert_eq!(sessions.active_market_sessions(), 0);
+/// # drop(account);
+/// # Ok(())
+/// # }
 /// ```
-#[derive(Default, Debug)]
-pub(crate) struct SessionManager {
-    session_exists: AtomicBool,
+#[derive(Debug)]
+pub struct SessionManager {
+    market: SessionSlots,
+    account: SessionSlots,
+}
+
+#[derive(Debug)]
+struct SessionSlots {
+    active: AtomicUsize,
+    limit: usize,
+}
+
+impl SessionSlots {
+    fn new(limit: usize) -> Self {
+        SessionSlots {
+            active: AtomicUsize::new(0),
+            limit,
+        }
+    }
+}
+
+impl Default for SessionManager {
+    /// One market and one account session, matching Tradier's per-user
+    /// streaming allowance.
+    fn default() -> Self {
+        Self::builder().build()
+    }
 }
 
+#[bon::bon]
 impl SessionManager {
-    /// Attempts to acquire a session, ensuring only one active session at a time.
+    /// Constructs a new `SessionManager`.
     ///
-    /// This method atomically sets the session state to "active". If a session is already
-    /// active, it returns an error indicating that the session cannot be acquired.
+    /// # Arguments
+    /// - `market_limit`: Concurrent market sessions allowed. Defaults to 1.
+    /// - `account_limit`: Concurrent account sessions allowed. Defaults to 1.
+    #[builder(builder_type(vis = "pub"))]
+    fn new(
+        #[builder(default = 1)] market_limit: usize,
+        #[builder(default = 1)] account_limit: usize,
+    ) -> Self {
+        SessionManager {
+            market: SessionSlots::new(market_limit),
+            account: SessionSlots::new(account_limit),
+        }
+    }
+
+    fn slots(&self, session_type: &SessionType) -> &SessionSlots {
+        match session_type {
+            SessionType::Market => &self.market,
+            SessionType::Account => &self.account,
+        }
+    }
+
+    /// Returns how many sessions of `session_type` may be open at once.
+    pub fn limit(&self, session_type: SessionType) -> usize {
+        self.slots(&session_type).limit
+    }
+
+    /// Returns the number of market sessions currently open.
+    pub fn active_market_sessions(&self) -> usize {
+        self.market.active.load(Ordering::Acquire)
+    }
+
+    /// Returns the number of account sessions currently open.
+    pub fn active_account_sessions(&self) -> usize {
+        self.account.active.load(Ordering::Acquire)
+    }
+
+    /// Opens a market session that holds one of this manager's market slots.
     ///
-    /// This method is for internal use by higher-level abstractions like [`Session`].
+    /// # Errors
+    /// - [`Error::SessionAlreadyExists`] if the market limit is reached.
+    /// - Any error of [`crate::wssession::MarketSession::new`].
+    pub async fn market_session(&self, config: &Config) -> Result<MarketSession<'_>> {
+        MarketSession::new_with_session_manager(config, self).await
+    }
+
+    /// Opens an account session that holds one of this manager's account
+    /// slots.
     ///
-    /// # Returns
-    /// - `Ok(())` if the session was successfully acquired.
-    /// - `Err(Error::SessionAlreadyExists)` if a session is already active.
-    pub(crate) fn acquire_session(&self) -> Result<()> {
-        if self
-            .session_exists
-            .compare_exchange(false, true, Ordering::Acquire, Ordering::Relaxed)
-            .is_err()
-        {
-            return Err(Error::SessionAlreadyExists);
-        }
-        Ok(())
+    /// # Errors
+    /// - [`Error::SessionAlreadyExists`] if the account limit is reached.
+    /// - Any error of [`crate::wssession::AccountSession::new`].
+    pub async fn account_session(&self, config: &Config) -> Result<AccountSession<'_>> {
+        AccountSession::new_with_session_manager(config, self).await
     }
 
-    /// Releases the active session, allowing a new session to be acquired.
+    /// Reserves a slot for a session of `session_type`.
     ///
-    /// This method atomically sets the session state to "inactive". It is for internal
-    /// use by components like [`Session`] to manage session lifecycles.
-    pub(crate) fn release_session(&self) {
-        self.session_exists.store(false, Ordering::Release);
+    /// # Returns
+    /// - `Ok(SessionGuard)` holding the slot until it is dropped.
+    /// - `Err(Error::SessionAlreadyExists)` if the limit for that type is
+    ///   already reached.
+    pub(crate) fn acquire_session(&self, session_type: SessionType) -> Result<SessionGuard<'_>> {
+        let slots = self.slots(&session_type);
+        slots
+            .active
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |active| {
+                (active < slots.limit).then_some(active + 1)
+            })
+            .map_err(|_| Error::SessionAlreadyExists)?;
+        Ok(SessionGuard {
+            manager: self,
+            session_type,
+        })
     }
 }
 
-impl Drop for SessionManager {
-    /// Automatically releases the session when the `SessionManager` is dropped.
-    ///
-    /// This ensures that any active session is released when a `SessionManager` instance
-    /// goes out of scope. This method is primarily for internal use.
+/// RAII reservation of one session slot in a [`SessionManager`].
+///
+/// The slot is released when the guard is dropped.
+#[derive(Debug)]
+pub(crate) struct SessionGuard<'a> {
+    manager: &'a SessionManager,
+    session_type: SessionType,
+}
+
+impl<'a> SessionGuard<'a> {
+    /// Returns the manager the slot belongs to.
+    pub(crate) fn manager(&self) -> &'a SessionManager {
+        self.manager
+    }
+}
+
+impl Drop for SessionGuard<'_> {
     fn drop(&mut self) {
-        self.release_session();
+        self.manager
+            .slots(&self.session_type)
+            .active
+            .fetch_sub(1, Ordering::AcqRel);
     }
 }
 
-/// A globally accessible, lazily-initialized singleton instance of `SessionManager`.
+/// Process-wide [`SessionManager`] used by [`crate::wssession::MarketSession::new`]
+/// and [`crate::wssession::AccountSession::new`].
 ///
-/// The `GLOBAL_SESSION_MANAGER` provides a single, thread-safe instance of `SessionManager`
-/// for use within the crate. It is not directly accessible outside the crate due to its
-/// `pub(crate)` visibility.
-///
-/// Most session-related operations should use higher-level abstractions like [`Session`].
-///
-/// ### Key Features
-/// - **Lazy Initialization**: The `SessionManager` is created only when first accessed.
-/// - **Thread-Safe**: Ensures safe, concurrent access across threads.
-///
-/// ### Internal Usage
-///
-/// Direct access to `GLOBAL_SESSION_MANAGER` is intended only for crate-internal use. External
-/// consumers of the library should use public abstractions like [`Session`].
+/// Allows one session per [`SessionType`]. Applications that need different
+/// limits create their own `SessionManager` instead.
 pub(crate) static GLOBAL_SESSION_MANAGER: LazyLock<SessionManager> =
     LazyLock::new(SessionManager::default);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_limits_are_tracked_per_session_type() {
+        let manager = SessionManager::default();
+        let market = manager.acquire_session(SessionType::Market).unwrap();
+        let account = manager.acquire_session(SessionType::Account).unwrap();
+        assert!(matches!(
+            manager.acquire_session(SessionType::Market),
+            Err(Error::SessionAlreadyExists)
+        ));
+        assert!(matches!(
+            manager.acquire_session(SessionType::Account),
+            Err(Error::SessionAlreadyExists)
+        ));
+        assert_eq!(manager.active_market_sessions(), 1);
+        assert_eq!(manager.active_account_sessions(), 1);
+
+        drop(market);
+        assert_eq!(manager.active_market_sessions(), 0);
+        assert!(manager.acquire_session(SessionType::Market).is_ok());
+        drop(account);
+        assert_eq!(manager.active_account_sessions(), 0);
+    }
+
+    #[test]
+    fn test_configured_limit_allows_several_sessions() {
+        let manager = SessionManager::builder()
+            .market_limit(2)
+            .account_limit(0)
+            .build();
+        assert_eq!(manager.limit(SessionType::Market), 2);
+        let _first = manager.acquire_session(SessionType::Market).unwrap();
+        let _second = manager.acquire_session(SessionType::Market).unwrap();
+        assert!(manager.acquire_session(SessionType::Market).is_err());
+        assert!(manager.acquire_session(SessionType::Account).is_err());
+    }
+}
@@ -0,0 +1,145 @@
+//! Live subscription handle for an open market WebSocket.
+//!
+//! Tradier has no incremental subscribe / unsubscribe messages: every change
+//! is made by sending the complete payload again on the same socket. The
+//! [`MarketSubscription`] handle keeps the current payload and the socket's
+//! write half, applies edits to a copy and re-sends the whole payload; the
+//! copy replaces the current payload only once it has been sent.
+
+use std::borrow::Cow;
+
+use futures_util::SinkExt;
+use futures_util::stream::SplitSink;
+use tokio_tungstenite::{MaybeTlsStream, WebSocketStream};
+use tracing::debug;
+use tungstenite::Message;
+
+use super::market::{MarketSessionFilter, MarketSessionPayload};
+use super::ws_decode::SharedSink;
+use crate::Result;
+
+/// Write half of a market WebSocket connection.
+pub(super) type MarketSink =
+    SplitSink<WebSocketStream<MaybeTlsStream<tokio::net::TcpStream>>, Message>;
+
+/// Handle returned by [`crate::wssession::MarketSession::subscribe`] for
+/// changing what an open market WebSocket streams.
+///
+/// Every mutating method re-sends the edited payload in full, so the server
+/// replaces the previous subscription. If sending fails the handle keeps the
+/// previous payload, which is still what the server last received. The
+/// event stream returned alongside the handle keeps running; dropping the
+/// handle only gives up the ability to change the subscription.
+pub struct MarketSubscription {
+    sink: SharedSink<MarketSink>,
+    payload: MarketSessionPayload<'static>,
+}
+
+impl MarketSubscription {
+    pub(super) fn new(
+        sink: SharedSink<MarketSink>,
+        payload: MarketSessionPayload<'static>,
+    ) -> Self {
+        MarketSubscription { sink, payload }
+    }
+
+    /// Returns the payload most recently sent to the server.
+    pub fn payload(&self) -> &MarketSessionPayload<'static> {
+        &self.payload
+    }
+
+    /// Returns the symbols currently subscribed to.
+    pub fn symbols(&self) -> &[String] {
+        &self.payload.symbols
+    }
+
+    /// Adds `symbols` that are not already subscribed and re-sends the
+    /// payload.
+    ///
+    /// # Errors
+    /// - [`crate::Error::WebSocketError`] if the payload cannot be sent.
+    pub async fn add_symbols<I, S>(&mut self, symbols: I) -> Result<()>
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let mut payload = self.payload.clone();
+        let current = payload.symbols.to_mut();
+        for symbol in symbols {
+            let symbol = symbol.into();
+            if !current.contains(&symbol) {
+                current.push(symbol);
+            }
+        }
+        self.send(payload).await
+    }
+
+    /// Removes `symbols` from the subscription and re-sends the payload.
+    ///
+    /// # Errors
+    /// - [`crate::Error::WebSocketError`] if the payload cannot be sent.
+    pub async fn remove_symbols<I, S>(&mut self, symbols: I) -> Result<()>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let removed: Vec<S> = symbols.into_iter().collect();
+        let mut payload = self.payload.clone();
+        payload
+            .symbols
+            .to_mut()
+            .retain(|s| !removed.iter().any(|r| r.as_ref() == s));
+        self.send(payload).await
+    }
+
+    /// Replaces the whole symbol list and re-sends the payload.
+    ///
+    /// # Errors
+    /// - [`crate::Error::WebSocketError`] if the payload cannot be sent.
+    pub async fn set_symbols<I, S>(&mut self, symbols: I) -> Result<()>
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let mut payload = self.payload.clone();
+        payload.symbols = Cow::Owned(symbols.into_iter().map(Into::into).collect());
+        self.send(payload).await
+    }
+
+    /// Replaces the event filters (`None` streams every event type) and
+    /// re-sends the payload.
+    ///
+    /// # Errors
+    /// - [`crate::Error::WebSocketError`] if the payload cannot be sent.
+    pub async fn set_filters(&mut self, filters: Option<Vec<MarketSessionFilter>>) -> Result<()> {
+        let mut payload = self.payload.clone();
+        payload.filters = filters.map(Cow::Owned);
+        self.send(payload).await
+    }
+
+    /// Closes the WebSocket, which also ends the paired event stream.
+    ///
+    /// # Errors
+    /// - [`crate::Error::WebSocketError`] if the close frame cannot be sent.
+    pub async fn close(self) -> Result<()> {
+        self.sink.lock().await.close().await.map_err(Box::new)?;
+        Ok(())
+    }
+
+    /// Sends `payload` and makes it the current payload once sent.
+    async fn send(&mut self, payload: MarketSessionPayload<'static>) -> Result<()> {
+        let message = payload.get_message()?;
+        self.sink
+            .lock()
+            .await
+            .send(message)
+            .await
+            .map_err(Box::new)?;
+        debug!(
+            symbols = payload.symbols.len(),
+            "re-sent market subscription payload"
+        );
+        self.payload = payload;
+        Ok(())
+    }
+}
@@ -16,48 +16,125 @@
 //!   and then ends the stream.
 //! - Ignores `Ping`, `Pong`, and raw `Frame` messages;
 //!   `tokio_tungstenite` handles the automatic `Pong` reply.
+//! - Optionally ([`Heartbeat`]) sends a client `Ping` every
+//!   `ping_interval` and, if no frame of any kind arrives within
+//!   `idle_timeout`, yields `Err(Error::StreamIdle(_))` and ends the
+//!   stream.
 
+use futures_util::lock::Mutex;
 use futures_util::stream::{self, Stream, StreamExt};
+use futures_util::{Sink, SinkExt};
 use std::collections::VecDeque;
-use tracing::warn;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::time::{Instant, Interval, MissedTickBehavior};
+use tracing::{trace, warn};
 use tungstenite::Message;
 
 use crate::Result;
+use crate::config::StreamingConfig;
+use crate::utils::telemetry;
+
+/// Write half of a WebSocket shared between the decoder (for pings) and
+/// any handle that re-sends subscriptions.
+pub(super) type SharedSink<K> = Arc<Mutex<K>>;
+
+/// Keep-alive settings for [`ws_event_stream`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) struct Heartbeat {
+    /// Fail the stream if nothing is received for this long.
+    pub idle_timeout: Option<Duration>,
+    /// Send a client `Ping` this often.
+    pub ping_interval: Option<Duration>,
+}
+
+impl Heartbeat {
+    pub(crate) fn from_config(config: &StreamingConfig) -> Self {
+        Heartbeat {
+            idle_timeout: config.idle_timeout(),
+            ping_interval: config.ping_interval(),
+        }
+    }
+}
 
 /// State carried across calls to [`stream::unfold`].
-struct DecoderState<S, T, F> {
+struct DecoderState<S, K, T, F> {
     read: S,
+    sink: SharedSink<K>,
     pending: VecDeque<Result<T>>,
     decode: F,
     finished: bool,
+    idle_timeout: Option<Duration>,
+    last_frame: Instant,
+    ping: Option<Interval>,
 }
 
 /// Drives a WebSocket read half and yields decoded events.
 ///
 /// `decode` is applied to every non-empty, newline-separated JSON line
-/// from a `Message::Text` (or `Message::Binary`) frame.
+/// from a `Message::Text` (or `Message::Binary`) frame. `sink` is only
+/// used to send heartbeat pings.
 #[inline]
-pub(super) fn ws_event_stream<S, T, F>(read: S, decode: F) -> impl Stream<Item = Result<T>>
+pub(super) fn ws_event_stream<S, K, T, F>(
+    read: S,
+    sink: SharedSink<K>,
+    heartbeat: Heartbeat,
+    decode: F,
+) -> impl Stream<Item = Result<T>>
 where
     S: Stream<Item = std::result::Result<Message, tungstenite::Error>> + Unpin,
+    K: Sink<Message, Error = tungstenite::Error> + Unpin,
     F: Fn(&str) -> Result<T>,
 {
+    let ping = heartbeat.ping_interval.map(|period| {
+        let mut interval = tokio::time::interval_at(Instant::now() + period, period);
+        interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        interval
+    });
     let state = DecoderState {
         read,
+        sink,
         pending: VecDeque::new(),
         decode,
         finished: false,
+        idle_timeout: heartbeat.idle_timeout,
+        last_frame: Instant::now(),
+        ping,
     };
 
     stream::unfold(state, |mut state| async move {
         loop {
             if let Some(item) = state.pending.pop_front() {
+                telemetry::record_stream_item(telemetry::TRANSPORT_WS, &item);
                 return Some((item, state));
             }
             if state.finished {
                 return None;
             }
-            match state.read.next().await {
+            let idle_deadline = state.idle_timeout.map(|timeout| state.last_frame + timeout);
+            let frame = tokio::select! {
+                frame = state.read.next() => frame,
+                _ = sleep_until(idle_deadline), if idle_deadline.is_some() => {
+                    let timeout = state.idle_timeout.unwrap_or_default();
+                    warn!(timeout_ms = timeout.as_millis() as u64, "websocket stream idle, terminating stream");
+                    state.finished = true;
+                    state.pending.push_back(Err(crate::Error::StreamIdle(timeout)));
+                    continue;
+                }
+                _ = tick(state.ping.as_mut()), if state.ping.is_some() => {
+                    trace!("sending websocket heartbeat ping");
+                    if let Err(e) = state.sink.lock().await.send(Message::Ping(Default::default())).await {
+                        warn!(error = %e, "websocket heartbeat ping failed, terminating stream");
+                        state.finished = true;
+                        state
+                            .pending
+                            .push_back(Err(crate::Error::WebSocketError(Box::new(e))));
+                    }
+                    continue;
+                }
+            };
+            state.last_frame = Instant::now();
+            match frame {
                 Some(Ok(Message::Text(text))) => {
                     push_lines(&mut state.pending, text.as_ref(), &state.decode);
                 }
@@ -88,6 +165,22 @@ where
     })
 }
 
+async fn sleep_until(deadline: Option<Instant>) {
+    match deadline {
+        Some(deadline) => tokio::time::sleep_until(deadline).await,
+        None => std::future::pending().await,
+    }
+}
+
+async fn tick(interval: Option<&mut Interval>) {
+    match interval {
+        Some(interval) => {
+            interval.tick().await;
+        }
+        None => std::future::pending().await,
+    }
+}
+
 #[inline]
 fn push_lines<T, F>(out: &mut VecDeque<Result<T>>, text: &str, decode: &F)
 where
@@ -101,3 +194,78 @@ where
         out.push_back(decode(trimmed));
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::pin::Pin;
+
+    use super::*;
+    use crate::Error;
+    use futures_util::stream;
+
+    type RecordingSink = Pin<Box<dyn Sink<Message, Error = tungstenite::Error> + Send>>;
+
+    /// A sink that records every message it is handed.
+    fn recording_sink() -> (
+        SharedSink<RecordingSink>,
+        Arc<std::sync::Mutex<Vec<Message>>>,
+    ) {
+        let sent = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let log = Arc::clone(&sent);
+        let sink: RecordingSink = Box::pin(futures_util::sink::unfold(
+            (),
+            move |(), message: Message| {
+                let log = Arc::clone(&log);
+                async move {
+                    log.lock().unwrap().push(message);
+                    Ok::<_, tungstenite::Error>(())
+                }
+            },
+        ));
+        (Arc::new(Mutex::new(sink)), sent)
+    }
+
+    fn decode(line: &str) -> Result<String> {
+        Ok(line.to_owned())
+    }
+
+    #[tokio::test]
+    async fn test_idle_timeout_yields_stream_idle_and_ends() {
+        let read = stream::iter([Ok(Message::Text("one".into()))]).chain(stream::pending());
+        let (sink, _) = recording_sink();
+        let heartbeat = Heartbeat {
+            idle_timeout: Some(Duration::from_millis(50)),
+            ping_interval: None,
+        };
+
+        let items: Vec<Result<String>> = ws_event_stream(read, sink, heartbeat, decode)
+            .collect()
+            .await;
+        assert_eq!(items.len(), 2, "unexpected items: {items:?}");
+        assert_eq!(items[0].as_deref().unwrap(), "one");
+        assert!(matches!(items[1], Err(Error::StreamIdle(d)) if d == Duration::from_millis(50)));
+    }
+
+    #[tokio::test]
+    async fn test_ping_interval_sends_client_pings() {
+        let read = stream::pending::<std::result::Result<Message, tungstenite::Error>>();
+        let (sink, sent) = recording_sink();
+        let heartbeat = Heartbeat {
+            idle_timeout: None,
+            ping_interval: Some(Duration::from_millis(10)),
+        };
+
+        let events = ws_event_stream(read, sink, heartbeat, decode);
+        futures_util::pin_mut!(events);
+        let waited = tokio::time::timeout(Duration::from_millis(80), events.next()).await;
+        assert!(waited.is_err(), "no item expected while only pinging");
+
+        let sent = sent.lock().unwrap();
+        assert!(
+            sent.len() >= 2,
+            "expected several pings, got {}",
+            sent.len()
+        );
+        assert!(sent.iter().all(|m| matches!(m, Message::Ping(_))));
+    }
+}